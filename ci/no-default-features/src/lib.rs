@@ -0,0 +1,29 @@
+//! Renders without the `axum` feature, so CI notices when code outside the
+//! web integration starts to depend on it.
+
+use azumi::html;
+
+#[azumi::component]
+pub fn greeting<'a>(name: &'a str) -> impl azumi::Component + 'a {
+    html! { <p class={"greeting"}>"Hello, " {name}</p> }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use azumi::Component;
+
+    #[test]
+    fn test_renders_without_axum() {
+        let page = azumi::render_to_string(&html! {
+            <main>
+                @greeting(name = "<Ann>")
+                @for i in 0..2 { <span>{i}</span> }
+            </main>
+        });
+        assert_eq!(
+            page,
+            r#"<main><p class="greeting">Hello, &lt;Ann&gt;</p><span>0</span><span>1</span></main>"#
+        );
+    }
+}
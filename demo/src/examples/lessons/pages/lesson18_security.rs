@@ -30,9 +30,10 @@ mod tests {
             is_admin: false,
         };
         let signed_scope = state.to_scope();
+        let parts = || axum::http::Request::new(()).into_parts().0;
 
         // 1. Verify normal request works (Implicitly signed)
-        let response = __azumi_live_handlers_securecounter::increment_handler(signed_scope.clone()).await;
+        let response = __azumi_live_handlers_securecounter::increment_handler(parts(), signed_scope.clone()).await;
         assert_eq!(
             response.status(),
             StatusCode::OK,
@@ -47,7 +48,7 @@ mod tests {
 
         // 3. Verify the handler REJECTS it automatically
         // We didn't write any verification code in SecureCounter, but the macro provided it.
-        let response = __azumi_live_handlers_securecounter::increment_handler(tampered_scope).await;
+        let response = __azumi_live_handlers_securecounter::increment_handler(parts(), tampered_scope).await;
         assert_eq!(
             response.status(),
             StatusCode::BAD_REQUEST,
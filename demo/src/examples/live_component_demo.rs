@@ -86,10 +86,7 @@ pub async fn unified_demo_handler() -> axum::response::Html<String> {
 }
 
 azumi::inventory::submit! {
-    azumi::action::ActionEntry {
-        path: "/unified_demo",
-        handler: unified_demo_router,
-    }
+    azumi::action::ActionEntry::new("/unified_demo", unified_demo_router)
 }
 
 #[allow(non_snake_case)]
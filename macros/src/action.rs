@@ -1,9 +1,79 @@
+use crate::middleware::RouteMiddleware;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, FnArg, ItemFn};
+use syn::{parse_macro_input, FnArg, ItemFn, Type};
 
-pub fn expand_action(item: TokenStream) -> TokenStream {
+/// How the request body is decoded into the payload argument
+#[derive(Clone, Copy, PartialEq)]
+enum PayloadMode {
+    /// `application/json` (default)
+    Json,
+    /// `application/x-www-form-urlencoded` (plain `<form method="post">`)
+    Form,
+    /// `multipart/form-data`, streamed through `azumi::action::Multipart`
+    Multipart,
+}
+
+/// Last path segment of a type (`axum::extract::Form<T>` -> `Form`)
+fn type_ident(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// `T` of `Form<T>` / `Json<T>`
+fn first_type_argument(ty: &Type) -> Option<Type> {
+    let Type::Path(p) = ty else { return None };
+    let syn::PathArguments::AngleBracketed(args) = &p.path.segments.last()?.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        syn::GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    })
+}
+
+pub fn expand_action(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input_fn = parse_macro_input!(item as ItemFn);
+    let args = parse_macro_input!(attr with syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated);
+
+    // #[azumi::action(form)] / #[azumi::action(multipart)] select the payload
+    // mode explicitly; otherwise it follows the payload type.
+    // `guard = f` / `layer = expr` wrap the generated route.
+    // `validate` checks the payload with `azumi::validate::Validate` first.
+    let mut explicit_mode = None;
+    let mut validate = false;
+    let mut middleware = RouteMiddleware::default();
+    for arg in &args {
+        match middleware.parse_arg(arg) {
+            Ok(true) => continue,
+            Ok(false) => {}
+            Err(e) => return e.to_compile_error().into(),
+        }
+        if matches!(arg, syn::Meta::Path(path) if path.is_ident("validate")) {
+            validate = true;
+            continue;
+        }
+        let mode = match arg {
+            syn::Meta::Path(path) if path.is_ident("json") => PayloadMode::Json,
+            syn::Meta::Path(path) if path.is_ident("form") => PayloadMode::Form,
+            syn::Meta::Path(path) if path.is_ident("multipart") => PayloadMode::Multipart,
+            other => {
+                return syn::Error::new_spanned(
+                    other,
+                    "Unknown #[azumi::action] argument. Expected: json, form, multipart, validate, guard = fn, layer = expr, rate_limit(..)",
+                )
+                .to_compile_error()
+                .into();
+            }
+        };
+        if explicit_mode.replace(mode).is_some() {
+            return syn::Error::new_spanned(arg, "Only one payload mode can be selected")
+                .to_compile_error()
+                .into();
+        }
+    }
 
     let fn_name = &input_fn.sig.ident;
     let fn_vis = &input_fn.vis;
@@ -50,6 +120,13 @@ pub fn expand_action(item: TokenStream) -> TokenStream {
 
     let (payload_type, _payload_pat) = if let Some(arg) = payload_arg {
         (&arg.ty, &arg.pat)
+    } else if explicit_mode.is_some() || validate {
+        return syn::Error::new_spanned(
+            &input_fn.sig,
+            "A payload mode or `validate` was selected but the action has no payload argument",
+        )
+        .to_compile_error()
+        .into();
     } else {
         // No payload
         return quote! {
@@ -61,6 +138,92 @@ pub fn expand_action(item: TokenStream) -> TokenStream {
         .into();
     };
 
+    // Payload types that are already extractors (`Form<T>`, `Json<T>`,
+    // `Multipart`) are passed through untouched; plain types are decoded
+    // according to the payload mode.
+    let type_name = type_ident(payload_type);
+    let is_extractor = matches!(type_name.as_deref(), Some("Form" | "Json" | "Multipart"));
+    let mode = explicit_mode.unwrap_or(match type_name.as_deref() {
+        Some("Form") => PayloadMode::Form,
+        Some("Multipart") => PayloadMode::Multipart,
+        _ => PayloadMode::Json,
+    });
+    let mode_matches = match type_name.as_deref() {
+        Some("Form") => mode == PayloadMode::Form,
+        Some("Json") => mode == PayloadMode::Json,
+        Some("Multipart") => mode == PayloadMode::Multipart,
+        _ => mode != PayloadMode::Multipart,
+    };
+    if !mode_matches {
+        return syn::Error::new_spanned(
+            payload_type,
+            "Payload type does not match the selected payload mode \
+             (multipart actions take `azumi::action::Multipart`)",
+        )
+        .to_compile_error()
+        .into();
+    }
+    // Registry metadata: the deserialized type (`T` of `Form<T>` / `Json<T>`)
+    // carries the schema; multipart payloads have none.
+    let content_type = match mode {
+        PayloadMode::Json => "application/json",
+        PayloadMode::Form => "application/x-www-form-urlencoded",
+        PayloadMode::Multipart => "multipart/form-data",
+    };
+    let schema_type = if mode == PayloadMode::Multipart {
+        None
+    } else if is_extractor {
+        first_type_argument(payload_type)
+    } else {
+        Some((**payload_type).clone())
+    };
+    let payload_schema = match schema_type {
+        Some(ty) => quote! {
+            |components| {
+                #[allow(unused_imports)]
+                use azumi::openapi::FallbackSchema;
+                azumi::openapi::SchemaOf::<#ty>::new().schema(components)
+            }
+        },
+        None => quote! { |_| None },
+    };
+    let response_type = match fn_output {
+        syn::ReturnType::Type(_, ty) => quote! { .response_type(stringify!(#ty)) },
+        syn::ReturnType::Default => quote! {},
+    };
+
+    if validate && mode == PayloadMode::Multipart {
+        return syn::Error::new_spanned(
+            payload_type,
+            "`validate` needs a deserialized payload; multipart payloads are read field by field",
+        )
+        .to_compile_error()
+        .into();
+    }
+    // `422 Unprocessable Entity` with the field errors before the action runs
+    let validate_payload = if !validate {
+        quote! {}
+    } else {
+        let value = if is_extractor {
+            quote! { payload.0 }
+        } else {
+            quote! { payload }
+        };
+        quote! {
+            if let Err(errors) = azumi::validate::Validate::validate(&#value) {
+                return azumi::axum::response::IntoResponse::into_response(errors);
+            }
+        }
+    };
+
+    let payload_extractor = if is_extractor {
+        quote! { payload: #payload_type }
+    } else if mode == PayloadMode::Form {
+        quote! { azumi::axum::extract::Form(payload): azumi::axum::extract::Form<#payload_type> }
+    } else {
+        quote! { azumi::axum::extract::Json(payload): azumi::axum::extract::Json<#payload_type> }
+    };
+
     // Reconstruct args for the wrapper
     // Extractors are passed through
     // Payload is wrapped in Json<...>
@@ -77,6 +240,18 @@ pub fn expand_action(item: TokenStream) -> TokenStream {
     // Helper to return MethodRouter
     // We need a unique name for this too
     let router_helper_name = quote::format_ident!("{}_router", fn_name);
+    if middleware.limits_by_state() {
+        return syn::Error::new_spanned(
+            &input_fn.sig.ident,
+            "rate_limit(.., key = state) is only available on live methods",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let router_body = middleware.wrap(
+        quote! { azumi::axum::routing::post(#wrapper_name) },
+        quote! { concat!("/_azumi/action/", stringify!(#fn_name)) },
+    );
 
     let expanded = quote! {
         // Original function (modified to be called by wrapper if needed, or just keep it)
@@ -96,8 +271,8 @@ pub fn expand_action(item: TokenStream) -> TokenStream {
         // We use a different name so we don't conflict
         pub async fn #wrapper_name(
             #(#extractor_args,)*
-            axum::extract::Json(payload): axum::extract::Json<#payload_type>
-        ) -> impl axum::response::IntoResponse {
+            #payload_extractor
+        ) -> azumi::axum::response::Response {
             // Call the original function
             // We need to match the arguments.
             // The payload pattern `payload` matches the Json body.
@@ -108,23 +283,34 @@ pub fn expand_action(item: TokenStream) -> TokenStream {
             // If the argument is `data: MyStruct`, `payload` will be `MyStruct`.
             // So we can just pass `payload` as the last argument.
 
+            #validate_payload
             let result = #fn_name(#(#call_args,)* payload).await;
-            azumi::render_to_string(&result)
+            #[allow(unused_imports)]
+            use azumi::action::RenderActionOutput as _;
+            azumi::action::ActionOutput(result).into_response()
         }
 
         // Helper to return MethodRouter
         #[allow(non_snake_case)]
-        pub fn #router_helper_name() -> axum::routing::MethodRouter<()> {
-            axum::routing::post(#wrapper_name)
+        pub fn #router_helper_name() -> azumi::axum::routing::MethodRouter<()> {
+            #router_body
         }
 
         // Auto-registration using inventory
         // We need to ensure this runs.
         azumi::inventory::submit! {
-            azumi::action::ActionEntry {
-                path: concat!("/_azumi/action/", stringify!(#fn_name)),
-                handler: #router_helper_name,
-            }
+            azumi::action::ActionEntry::new(
+                concat!("/_azumi/action/", stringify!(#fn_name)),
+                #router_helper_name,
+            )
+            .kind(azumi::action::ActionKind::Action)
+            .http_method("POST")
+            .name(stringify!(#fn_name))
+            .payload_type(stringify!(#payload_type))
+            .content_type(#content_type)
+            #response_type
+            .payload_schema(#payload_schema)
+            .location(file!(), line!())
         }
     };
 
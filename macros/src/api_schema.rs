@@ -0,0 +1,215 @@
+//! `#[derive(azumi::ApiSchema)]`
+//!
+//! Generates `azumi::openapi::ApiSchema`: a named component schema for
+//! structs with named fields (object) and unit-only enums (string enum),
+//! following the serde attributes that change the wire format.
+
+use heck::{ToKebabCase, ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    skip: bool,
+    default: bool,
+}
+
+/// The subset of `#[serde(...)]` that affects the schema. Unknown entries
+/// are skipped (serde itself reports mistakes).
+fn serde_attrs(attrs: &[syn::Attribute]) -> SerdeAttrs {
+    let mut out = SerdeAttrs::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            let name = meta
+                .path
+                .get_ident()
+                .map(|i| i.to_string())
+                .unwrap_or_default();
+            match name.as_str() {
+                "rename" | "rename_all" if meta.input.peek(syn::Token![=]) => {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    if name == "rename" {
+                        out.rename = Some(lit.value());
+                    } else {
+                        out.rename_all = Some(lit.value());
+                    }
+                }
+                "skip" | "skip_deserializing" => out.skip = true,
+                "default" => {
+                    out.default = true;
+                    if meta.input.peek(syn::Token![=]) {
+                        let _: syn::LitStr = meta.value()?.parse()?;
+                    }
+                }
+                _ => {
+                    // Consume `key = value` / `key(...)` we do not care about
+                    if meta.input.peek(syn::Token![=]) {
+                        let _: syn::Expr = meta.value()?.parse()?;
+                    } else if meta.input.peek(syn::token::Paren) {
+                        let _ = meta.parse_nested_meta(|_| Ok(()));
+                    }
+                }
+            }
+            Ok(())
+        });
+    }
+    out
+}
+
+fn apply_rename_all(name: &str, rule: Option<&str>) -> String {
+    match rule {
+        Some("lowercase") => name.to_lowercase(),
+        Some("UPPERCASE") => name.to_uppercase(),
+        Some("camelCase") => name.to_lower_camel_case(),
+        Some("PascalCase") => name.to_upper_camel_case(),
+        Some("snake_case") => name.to_snake_case(),
+        Some("SCREAMING_SNAKE_CASE") => name.to_shouty_snake_case(),
+        Some("kebab-case") => name.to_kebab_case(),
+        Some("SCREAMING-KEBAB-CASE") => name.to_kebab_case().to_uppercase(),
+        _ => name.to_string(),
+    }
+}
+
+/// `/// doc` lines joined into a description
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            syn::Meta::NameValue(nv) => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    let doc = lines.join(" ").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+pub fn derive_api_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let container = serde_attrs(&input.attrs);
+    let schema_name = container.rename.clone().unwrap_or_else(|| name.to_string());
+    let description = match doc_comment(&input.attrs) {
+        Some(doc) => quote! { schema.insert("description".to_string(), serde_json::json!(#doc)); },
+        None => quote! {},
+    };
+
+    let build = match &input.data {
+        Data::Struct(data) => {
+            let Fields::Named(fields) = &data.fields else {
+                return syn::Error::new_spanned(
+                    &input,
+                    "#[derive(ApiSchema)] supports structs with named fields and unit-only enums",
+                )
+                .to_compile_error()
+                .into();
+            };
+            let mut properties = Vec::new();
+            for field in &fields.named {
+                let attrs = serde_attrs(&field.attrs);
+                if attrs.skip {
+                    continue;
+                }
+                let Some(ident) = &field.ident else { continue };
+                let ty = &field.ty;
+                let key = attrs.rename.unwrap_or_else(|| {
+                    apply_rename_all(
+                        ident.to_string().trim_start_matches("r#"),
+                        container.rename_all.as_deref(),
+                    )
+                });
+                let describe = match doc_comment(&field.attrs) {
+                    Some(doc) => quote! {
+                        if let serde_json::Value::Object(map) = &mut field_schema {
+                            if !map.contains_key("$ref") {
+                                map.insert("description".to_string(), serde_json::json!(#doc));
+                            }
+                        }
+                    },
+                    None => quote! {},
+                };
+                let required = if attrs.default || container.default {
+                    quote! {}
+                } else {
+                    quote! {
+                        if <#ty as azumi::openapi::ApiSchema>::api_required() {
+                            required.push(serde_json::json!(#key));
+                        }
+                    }
+                };
+                properties.push(quote! {
+                    let mut field_schema = <#ty as azumi::openapi::ApiSchema>::api_schema(components);
+                    #describe
+                    properties.insert(#key.to_string(), field_schema);
+                    #required
+                });
+            }
+            quote! {
+                let mut properties = serde_json::Map::new();
+                let mut required: Vec<serde_json::Value> = Vec::new();
+                #(#properties)*
+                let mut schema = serde_json::Map::new();
+                schema.insert("type".to_string(), serde_json::json!("object"));
+                schema.insert("properties".to_string(), serde_json::Value::Object(properties));
+                if !required.is_empty() {
+                    schema.insert("required".to_string(), serde_json::Value::Array(required));
+                }
+            }
+        }
+        Data::Enum(data) => {
+            let mut values = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return syn::Error::new_spanned(
+                        variant,
+                        "#[derive(ApiSchema)] only supports enums with unit variants",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+                let attrs = serde_attrs(&variant.attrs);
+                if attrs.skip {
+                    continue;
+                }
+                values.push(attrs.rename.unwrap_or_else(|| {
+                    apply_rename_all(&variant.ident.to_string(), container.rename_all.as_deref())
+                }));
+            }
+            quote! {
+                let _ = &components;
+                let mut schema = serde_json::Map::new();
+                schema.insert("type".to_string(), serde_json::json!("string"));
+                schema.insert("enum".to_string(), serde_json::json!([#(#values),*]));
+            }
+        }
+        Data::Union(_) => {
+            return syn::Error::new_spanned(&input, "#[derive(ApiSchema)] does not support unions")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    let expanded = quote! {
+        impl #impl_generics azumi::openapi::ApiSchema for #name #ty_generics #where_clause {
+            fn api_schema(components: &mut azumi::openapi::Components) -> serde_json::Value {
+                components.define(#schema_name, |components| {
+                    #build
+                    #description
+                    serde_json::Value::Object(schema)
+                })
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
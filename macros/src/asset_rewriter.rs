@@ -57,6 +57,23 @@ fn rewrite_node(node: &mut Node) {
             Block::For(for_block) => {
                 rewrite_nodes(&mut for_block.body);
             }
+            Block::Suspense(suspense_block) => {
+                rewrite_nodes(&mut suspense_block.body);
+            }
+            Block::Cache(cache_block) => {
+                rewrite_nodes(&mut cache_block.body);
+            }
+            Block::Try(try_block) => {
+                for body in [&mut try_block.body, &mut try_block.fallback] {
+                    rewrite_nodes(body);
+                }
+            }
+            Block::Provide(provide_block) => {
+                rewrite_nodes(&mut provide_block.body);
+            }
+            Block::Slot(slot_block) => {
+                rewrite_nodes(&mut slot_block.body);
+            }
             Block::Match(match_block) => {
                 for arm in &mut match_block.arms {
                     rewrite_nodes(&mut arm.body);
@@ -1,5 +1,5 @@
-use quote::quote;
-use syn::{parse_macro_input, FnArg, ItemFn, Pat, PatType, Type};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, FnArg, GenericParam, ItemFn, Pat, PatType, Type};
 
 pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(item as ItemFn);
@@ -19,6 +19,8 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
     let mut struct_fields = Vec::new();
     let mut has_children = false;
     let mut children_type = None;
+    // Named slots: (name, required, type parameter of Props, declared bounds)
+    let mut slots = Vec::new();
 
     for arg in &input.sig.inputs {
         if let FnArg::Typed(PatType { pat, ty, attrs, .. }) = arg {
@@ -32,6 +34,23 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
                     continue; // Don't add to Props
                 }
 
+                // #[slot] name: impl Component (required) or Option<impl Component>
+                if attrs.iter().any(|attr| attr.path().is_ident("slot")) {
+                    let (required, bounds) = match slot_kind(ty) {
+                        Some(kind) => kind,
+                        None => {
+                            let err = syn::Error::new_spanned(
+                                ty,
+                                "a slot is declared as `impl Component` (required) or `Option<impl Component>` (optional)",
+                            );
+                            return proc_macro::TokenStream::from(err.to_compile_error());
+                        }
+                    };
+                    let param = format_ident!("__AzumiSlot{}", slots.len());
+                    slots.push((ident.clone(), required, param, bounds));
+                    continue;
+                }
+
                 // Check for #[prop(default = ...)]
                 let mut default_value = None;
                 for attr in attrs {
@@ -96,7 +115,119 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
     }
 
     let fn_generics = &input.sig.generics;
-    let (impl_generics, ty_generics, where_clause) = fn_generics.split_for_impl();
+    let (fn_impl_generics, _, where_clause) = fn_generics.split_for_impl();
+
+    // Props and its builder are generic over the content of each slot:
+    // `Unfilled` until `.slot_name(...)` sets it to `Filled<C>`
+    let mut props_generics = fn_generics.clone();
+    let fn_args: Vec<proc_macro2::TokenStream> = fn_generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(l) => {
+                let lifetime = &l.lifetime;
+                quote! { #lifetime }
+            }
+            GenericParam::Type(t) => {
+                let ident = &t.ident;
+                quote! { #ident }
+            }
+            GenericParam::Const(c) => {
+                let ident = &c.ident;
+                quote! { #ident }
+            }
+        })
+        .collect();
+    let mut slot_bounds = Vec::new();
+    let mut slot_traits = Vec::new();
+    let mut slot_setters = Vec::new();
+    let builder_idents: Vec<_> = struct_fields
+        .iter()
+        .map(|ident| (*ident).clone())
+        .chain(slots.iter().map(|(ident, _, _, _)| ident.clone()))
+        .collect();
+    for (i, (ident, required, param, _)) in slots.iter().enumerate() {
+        props_generics.params.push(parse_quote!(#param));
+        props_fields.push(quote! { pub #ident: #param });
+        builder_fields.push(quote! { #ident: #param });
+        builder_init.push(quote! { #ident: azumi::slot::Unfilled });
+        build_logic.push(quote! { let #ident = self.#ident; });
+
+        if *required {
+            props_init.push(quote! {
+                let #ident = azumi::slot::Required::into_content(props.#ident);
+            });
+            let marker = format_ident!("__required_slot_{}", ident);
+            let message = format!("missing required slot `{}` in `@{}`", ident, fn_name);
+            let label = format!("add `@slot {} {{ ... }}` to this call", ident);
+            slot_traits.push(quote! {
+                #[doc(hidden)]
+                #[allow(non_camel_case_types)]
+                #[diagnostic::on_unimplemented(message = #message, label = #label)]
+                pub trait #marker {}
+                impl<C: azumi::Component> #marker for azumi::slot::Filled<C> {}
+            });
+            slot_bounds.push(quote! { #param: #marker });
+        } else {
+            props_init.push(quote! {
+                let #ident = azumi::slot::Optional::into_option(props.#ident);
+            });
+            slot_bounds.push(quote! { #param: azumi::slot::Optional });
+        }
+
+        // Builder type with this slot filled with `__C`
+        let filled_args = fn_args.iter().cloned().chain(slots.iter().enumerate().map(
+            |(j, (_, _, other, _))| {
+                if i == j {
+                    quote! { azumi::slot::Filled<__C> }
+                } else {
+                    quote! { #other }
+                }
+            },
+        ));
+        let setter = format_ident!("slot_{}", ident);
+        let others = builder_idents.iter().filter(|other| *other != ident);
+        slot_setters.push(quote! {
+            pub fn #setter<__C: azumi::Component>(self, content: __C) -> PropsBuilder<#(#filled_args),*> {
+                PropsBuilder {
+                    #ident: azumi::slot::Filled(content),
+                    #(#others: self.#others),*
+                }
+            }
+        });
+    }
+    let unfilled_args = {
+        let mut args = fn_args.clone();
+        args.extend(slots.iter().map(|_| quote! { azumi::slot::Unfilled }));
+        if args.is_empty() {
+            quote! {}
+        } else {
+            quote! { <#(#args),*> }
+        }
+    };
+    let build_where = if slot_bounds.is_empty() {
+        quote! {}
+    } else {
+        quote! { where #(#slot_bounds),* }
+    };
+    let (impl_generics, ty_generics, _) = props_generics.split_for_impl();
+    let mut render_generics = props_generics.clone();
+    for (_, required, param, bounds) in &slots {
+        // The content must meet the declared bounds (e.g. `impl Component + 'a`)
+        let predicates: [syn::WherePredicate; 2] = if *required {
+            [
+                parse_quote!(#param: azumi::slot::Required),
+                parse_quote!(<#param as azumi::slot::Required>::Content: #bounds),
+            ]
+        } else {
+            [
+                parse_quote!(#param: azumi::slot::Optional),
+                parse_quote!(<#param as azumi::slot::Optional>::Content: #bounds),
+            ]
+        };
+        render_generics.make_where_clause().predicates.extend(predicates);
+    }
+    let (render_impl_generics, _, render_where_clause) = render_generics.split_for_impl();
 
     // Check for live state parameter (first argument)
     // Only trigger for parameters named "state" with a reference to a user-defined type
@@ -133,7 +264,17 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
                 azumi::from_fn(move |f| {
                     let scope_json = <_ as azumi::LiveState>::to_scope(#state_ident);
                     let struct_name = <#live_state_type as azumi::LiveStateMetadata>::struct_name();
-                    write!(f, "<div az-scope=\"{}\" az-struct=\"{}\" style=\"display: contents\">", azumi::Escaped(&scope_json), azumi::Escaped(struct_name))?;
+                    write!(f, "<div az-scope=\"{}\" az-struct=\"{}\"", azumi::Escaped(&scope_json), azumi::Escaped(struct_name))?;
+                    if let Some(live_id) = <_ as azumi::LiveState>::live_id(#state_ident) {
+                        write!(f, " az-id=\"{}\"", azumi::Escaped(&live_id))?;
+                    }
+                    azumi::__live_mount_attr(f)?;
+                    let history = <#live_state_type as azumi::history::LiveHistory>::CAPACITY;
+                    if history > 0 {
+                        let storage = <#live_state_type as azumi::history::LiveHistory>::STORAGE;
+                        write!(f, " az-history=\"{}\" az-history-storage=\"{}\"", history, storage.as_str())?;
+                    }
+                    write!(f, " style=\"display: contents\">")?;
                     let inner = #fn_block;
                     inner.render(f)?;
                     write!(f, "</div>")?;
@@ -145,7 +286,7 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
         };
 
         quote! {
-            pub fn render #impl_generics (props: Props #ty_generics, children: #children_ty) #fn_output #where_clause {
+            pub fn render #render_impl_generics (props: Props #ty_generics, children: #children_ty) #fn_output #render_where_clause {
                 #(#props_init)*
                 #body
             }
@@ -156,7 +297,17 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
                 azumi::from_fn(move |f| {
                     let scope_json = <_ as azumi::LiveState>::to_scope(#state_ident);
                     let struct_name = <#live_state_type as azumi::LiveStateMetadata>::struct_name();
-                    write!(f, "<div az-scope=\"{}\" az-struct=\"{}\" style=\"display: contents\">", azumi::Escaped(&scope_json), azumi::Escaped(struct_name))?;
+                    write!(f, "<div az-scope=\"{}\" az-struct=\"{}\"", azumi::Escaped(&scope_json), azumi::Escaped(struct_name))?;
+                    if let Some(live_id) = <_ as azumi::LiveState>::live_id(#state_ident) {
+                        write!(f, " az-id=\"{}\"", azumi::Escaped(&live_id))?;
+                    }
+                    azumi::__live_mount_attr(f)?;
+                    let history = <#live_state_type as azumi::history::LiveHistory>::CAPACITY;
+                    if history > 0 {
+                        let storage = <#live_state_type as azumi::history::LiveHistory>::STORAGE;
+                        write!(f, " az-history=\"{}\" az-history-storage=\"{}\"", history, storage.as_str())?;
+                    }
+                    write!(f, " style=\"display: contents\">")?;
                     let inner = #fn_block;
                     inner.render(f)?;
                     write!(f, "</div>")?;
@@ -168,7 +319,7 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
         };
 
         quote! {
-            pub fn render #impl_generics (props: Props #ty_generics) #fn_output #where_clause {
+            pub fn render #render_impl_generics (props: Props #ty_generics) #fn_output #render_where_clause {
                 #(#props_init)*
                 #body
             }
@@ -206,16 +357,18 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
             use super::*;
             use azumi::Component;
 
-            pub struct Props #fn_generics #where_clause {
+            pub struct Props #props_generics #where_clause {
                 #(#props_fields),*
             }
 
-            pub struct PropsBuilder #fn_generics #where_clause {
+            pub struct PropsBuilder #props_generics #where_clause {
                 #(#builder_fields),*
             }
 
-            impl #impl_generics Props #ty_generics #where_clause {
-                pub fn builder() -> PropsBuilder #ty_generics {
+            #(#slot_traits)*
+
+            impl #fn_impl_generics Props #unfilled_args #where_clause {
+                pub fn builder() -> PropsBuilder #unfilled_args {
                     PropsBuilder {
                         #(#builder_init),*
                     }
@@ -224,11 +377,12 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
 
             impl #impl_generics PropsBuilder #ty_generics #where_clause {
                 #(#builder_setters)*
+                #(#slot_setters)*
 
-                pub fn build(self) -> Result<Props #ty_generics, &'static str> {
+                pub fn build(self) -> Result<Props #ty_generics, &'static str> #build_where {
                     #(#build_logic)*
                     Ok(Props {
-                        #(#struct_fields: #struct_fields),*
+                        #(#builder_idents: #builder_idents),*
                     })
                 }
             }
@@ -241,3 +395,32 @@ pub fn expand_component(item: proc_macro::TokenStream) -> proc_macro::TokenStrea
 
     proc_macro::TokenStream::from(expanded)
 }
+
+/// Whether a slot declared as `ty` is required (`impl Trait`) or optional
+/// (`Option<impl Trait>`), with the declared bounds. `None` for other types.
+fn slot_kind(
+    ty: &Type,
+) -> Option<(
+    bool,
+    syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>,
+)> {
+    match ty {
+        Type::ImplTrait(impl_trait) => Some((true, impl_trait.bounds.clone())),
+        Type::Path(path) if path.qself.is_none() => {
+            let last = path.path.segments.last()?;
+            if last.ident != "Option" {
+                return None;
+            }
+            let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+                return None;
+            };
+            match args.args.first()? {
+                syn::GenericArgument::Type(Type::ImplTrait(impl_trait)) => {
+                    Some((false, impl_trait.bounds.clone()))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -51,6 +51,23 @@ fn collect_css_files(nodes: &[Node], css_files: &mut Vec<String>) {
                 Block::For(for_block) => {
                     collect_css_files(&for_block.body, css_files);
                 }
+                Block::Suspense(suspense_block) => {
+                    collect_css_files(&suspense_block.body, css_files);
+                }
+                Block::Cache(cache_block) => {
+                    collect_css_files(&cache_block.body, css_files);
+                }
+                Block::Try(try_block) => {
+                    for body in [&try_block.body, &try_block.fallback] {
+                        collect_css_files(body, css_files);
+                    }
+                }
+                Block::Provide(provide_block) => {
+                    collect_css_files(&provide_block.body, css_files);
+                }
+                Block::Slot(slot_block) => {
+                    collect_css_files(&slot_block.body, css_files);
+                }
                 Block::Match(match_block) => {
                     for arm in &match_block.arms {
                         collect_css_files(&arm.body, css_files);
@@ -239,6 +239,12 @@ pub fn validate_node_order(nodes: &[Node]) -> Vec<TokenStream> {
                             crate::token_parser::Block::Call(c) => c.span,
                             crate::token_parser::Block::Component(c) => c.span,
                             crate::token_parser::Block::Let(l) => l.span,
+                            crate::token_parser::Block::Flush(f) => f.span,
+                            crate::token_parser::Block::Suspense(s) => s.span,
+                            crate::token_parser::Block::Slot(s) => s.span,
+                            crate::token_parser::Block::Provide(p) => p.span,
+                            crate::token_parser::Block::Try(t) => t.span,
+                            crate::token_parser::Block::Cache(c) => c.span,
                             _ => proc_macro2::Span::call_site(), // Should match matches above
                         },
                         _ => proc_macro2::Span::call_site(),
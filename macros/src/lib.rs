@@ -2,17 +2,20 @@ mod component;
 
 mod accessibility_validator;
 mod action;
+mod api_schema;
 mod asset_rewriter;
 mod css;
 mod css_validator;
 mod head;
 mod html_structure_validator;
 mod live;
+mod middleware;
 mod page;
 #[cfg(feature = "schema")]
 mod schema;
 mod style;
 mod token_parser;
+mod validate;
 
 use proc_macro::TokenStream;
 use quote::{quote, quote_spanned};
@@ -36,14 +39,24 @@ pub fn derive_schema(input: TokenStream) -> TokenStream {
     schema::derive_schema(input)
 }
 
+#[proc_macro_derive(ApiSchema, attributes(serde))]
+pub fn derive_api_schema(input: TokenStream) -> TokenStream {
+    api_schema::derive_api_schema(input)
+}
+
+#[proc_macro_derive(Validate, attributes(validate))]
+pub fn derive_validate(input: TokenStream) -> TokenStream {
+    validate::derive_validate(input)
+}
+
 #[proc_macro_attribute]
 pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
     component::expand_component(item)
 }
 
 #[proc_macro_attribute]
-pub fn action(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    action::expand_action(item)
+pub fn action(attr: TokenStream, item: TokenStream) -> TokenStream {
+    action::expand_action(attr, item)
 }
 
 #[proc_macro_attribute]
@@ -61,6 +74,21 @@ pub fn predict(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
 
+/// Marks a live method as the handler for events emitted by a child live struct.
+/// Consumed by `#[azumi::live_impl]`.
+#[proc_macro_attribute]
+pub fn on_emit(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+/// Guards, layers and rate limit for the route of a live method:
+/// `#[azumi::route(guard = require_admin, rate_limit(5, per_minute))]`.
+/// Consumed by `#[azumi::live_impl]`.
+#[proc_macro_attribute]
+pub fn route(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
 // Helpers for parsing Component arguments
 struct KeyValueArg {
     key: syn::Ident,
@@ -107,7 +135,7 @@ pub fn html(input: TokenStream) -> TokenStream {
     let (style_bindings, _scoped_css, _global_css) = process_styles(&nodes);
 
     // 2. Generate HTML string construction code
-    let html_construction = generate_nodes(&nodes);
+    let (html_construction, size_hint) = generate_nodes(&nodes);
 
     // 3. Generate bind validation checks
     let mut validation_checks = Vec::new();
@@ -118,6 +146,8 @@ pub fn html(input: TokenStream) -> TokenStream {
             // Import FallbackRender to ensure render_azumi works even if trait not imported by user
             #[allow(unused_imports)]
             use azumi::FallbackRender;
+            #[allow(unused_imports)]
+            use azumi::TryRender;
 
             // Inject style bindings (hoisted)
             #style_bindings
@@ -135,9 +165,12 @@ pub fn html(input: TokenStream) -> TokenStream {
             //
             // `FnOnceComponent` caches its rendered result, so the closure is only
             // invoked once - which is the typical case for a complete HTML page.
-            azumi::from_fn_once(move |f| {
+            //
+            // The body runs in `azumi::error::run` so that `?` works in it.
+            azumi::from_fn_once(move |f| azumi::error::run(|| {
                 #html_construction
-            })
+            }))
+            .with_size_hint(#size_hint)
         }
     };
 
@@ -193,6 +226,38 @@ fn process_styles(nodes: &[token_parser::Node]) -> (proc_macro2::TokenStream, St
                     scoped_css.push_str(&s);
                     global_css.push_str(&g);
                 }
+                token_parser::Block::Suspense(suspense_block) => {
+                    let (b, s, g) = process_styles(&suspense_block.body);
+                    bindings.extend(b);
+                    scoped_css.push_str(&s);
+                    global_css.push_str(&g);
+                }
+                token_parser::Block::Cache(cache_block) => {
+                    let (b, s, g) = process_styles(&cache_block.body);
+                    bindings.extend(b);
+                    scoped_css.push_str(&s);
+                    global_css.push_str(&g);
+                }
+                token_parser::Block::Try(try_block) => {
+                    for body in [&try_block.body, &try_block.fallback] {
+                        let (b, s, g) = process_styles(body);
+                        bindings.extend(b);
+                        scoped_css.push_str(&s);
+                        global_css.push_str(&g);
+                    }
+                }
+                token_parser::Block::Provide(provide_block) => {
+                    let (b, s, g) = process_styles(&provide_block.body);
+                    bindings.extend(b);
+                    scoped_css.push_str(&s);
+                    global_css.push_str(&g);
+                }
+                token_parser::Block::Slot(slot_block) => {
+                    let (b, s, g) = process_styles(&slot_block.body);
+                    bindings.extend(b);
+                    scoped_css.push_str(&s);
+                    global_css.push_str(&g);
+                }
                 token_parser::Block::Match(match_block) => {
                     for arm in &match_block.arms {
                         let (b, s, g) = process_styles(&arm.body);
@@ -252,6 +317,23 @@ fn collect_bind_checks(nodes: &[token_parser::Node], checks: &mut Vec<proc_macro
                 token_parser::Block::For(for_block) => {
                     collect_bind_checks(&for_block.body, checks);
                 }
+                token_parser::Block::Suspense(suspense_block) => {
+                    collect_bind_checks(&suspense_block.body, checks);
+                }
+                token_parser::Block::Cache(cache_block) => {
+                    collect_bind_checks(&cache_block.body, checks);
+                }
+                token_parser::Block::Try(try_block) => {
+                    for body in [&try_block.body, &try_block.fallback] {
+                        collect_bind_checks(body, checks);
+                    }
+                }
+                token_parser::Block::Provide(provide_block) => {
+                    collect_bind_checks(&provide_block.body, checks);
+                }
+                token_parser::Block::Slot(slot_block) => {
+                    collect_bind_checks(&slot_block.body, checks);
+                }
                 token_parser::Block::Match(match_block) => {
                     for arm in &match_block.arms {
                         collect_bind_checks(&arm.body, checks);
@@ -340,12 +422,44 @@ fn is_valid_identifier(s: &str) -> bool {
     chars.all(|c| c.is_alphanumeric() || c == '_')
 }
 
-fn generate_nodes(nodes: &[token_parser::Node]) -> proc_macro2::TokenStream {
-    let body = generate_body(nodes, first_node_span(nodes));
-    quote! {
-        #body
-        Ok(())
-    }
+/// Render code for `html!` and a lower bound on the rendered length
+fn generate_nodes(nodes: &[token_parser::Node]) -> (proc_macro2::TokenStream, usize) {
+    let (body, size_hint) = generate_body(nodes, first_node_span(nodes));
+    (
+        quote! {
+            #body
+            Ok(())
+        },
+        size_hint,
+    )
+}
+
+/// Span of the first `@suspense` block in `nodes`, at any depth
+fn find_suspense(nodes: &[token_parser::Node]) -> Option<proc_macro2::Span> {
+    use token_parser::{Block, Node};
+    nodes.iter().find_map(|node| match node {
+        Node::Element(elem) => find_suspense(&elem.children),
+        Node::Fragment(frag) => find_suspense(&frag.children),
+        Node::Block(block) => match block {
+            Block::Suspense(suspense_block) => Some(suspense_block.span),
+            Block::If(if_block) => find_suspense(&if_block.then_branch)
+                .or_else(|| if_block.else_branch.as_deref().and_then(find_suspense)),
+            Block::For(for_block) => find_suspense(&for_block.body),
+            Block::Match(match_block) => match_block
+                .arms
+                .iter()
+                .find_map(|arm| find_suspense(&arm.body)),
+            Block::Call(call_block) => find_suspense(&call_block.children),
+            Block::Slot(slot_block) => find_suspense(&slot_block.body),
+            Block::Provide(provide_block) => find_suspense(&provide_block.body),
+            Block::Try(try_block) => {
+                find_suspense(&try_block.body).or_else(|| find_suspense(&try_block.fallback))
+            }
+            Block::Cache(cache_block) => find_suspense(&cache_block.body),
+            _ => None,
+        },
+        _ => None,
+    })
 }
 
 fn first_node_span(nodes: &[token_parser::Node]) -> Option<(usize, usize)> {
@@ -395,6 +509,41 @@ fn first_node_span(nodes: &[token_parser::Node]) -> Option<(usize, usize)> {
                     let loc = for_block.span.start();
                     Some((loc.line, loc.column))
                 }
+                token_parser::Block::Suspense(suspense_block) => {
+                    if let Some(span) = first_node_span(&suspense_block.body) {
+                        return Some(span);
+                    }
+                    let loc = suspense_block.span.start();
+                    Some((loc.line, loc.column))
+                }
+                token_parser::Block::Cache(cache_block) => {
+                    if let Some(span) = first_node_span(&cache_block.body) {
+                        return Some(span);
+                    }
+                    let loc = cache_block.span.start();
+                    Some((loc.line, loc.column))
+                }
+                token_parser::Block::Try(try_block) => {
+                    if let Some(span) = first_node_span(&try_block.body) {
+                        return Some(span);
+                    }
+                    let loc = try_block.span.start();
+                    Some((loc.line, loc.column))
+                }
+                token_parser::Block::Provide(provide_block) => {
+                    if let Some(span) = first_node_span(&provide_block.body) {
+                        return Some(span);
+                    }
+                    let loc = provide_block.span.start();
+                    Some((loc.line, loc.column))
+                }
+                token_parser::Block::Slot(slot_block) => {
+                    if let Some(span) = first_node_span(&slot_block.body) {
+                        return Some(span);
+                    }
+                    let loc = slot_block.span.start();
+                    Some((loc.line, loc.column))
+                }
                 token_parser::Block::Match(match_block) => {
                     for arm in &match_block.arms {
                         if let Some(span) = first_node_span(&arm.body) {
@@ -423,6 +572,10 @@ fn first_node_span(nodes: &[token_parser::Node]) -> Option<(usize, usize)> {
                     let loc = comp_block.span.start();
                     Some((loc.line, loc.column))
                 }
+                token_parser::Block::Flush(flush_block) => {
+                    let loc = flush_block.span.start();
+                    Some((loc.line, loc.column))
+                }
             },
             token_parser::Node::Comment(comment) => {
                 let loc = comment.span.start();
@@ -452,6 +605,44 @@ fn azumi_scope_id_from_span(line: usize, col: usize) -> String {
     format!("s{:x}", hasher.finish())
 }
 
+/// Swap modes understood by the client after `-> target`
+/// (mirrors `azumi::fragments::Swap`)
+const SWAP_MODES: &[&str] = &[
+    "morph",
+    "inner",
+    "outer",
+    "beforebegin",
+    "afterbegin",
+    "beforeend",
+    "afterend",
+    "delete",
+    "none",
+];
+
+/// Check the `-> target swap` part of an `az-on` / `on:event` call
+fn validate_swap(dsl: &str) -> Result<(), String> {
+    // Normalize like the client: TokenStream spacing turns "-> #id" into "- > # id"
+    let dsl = dsl.replace("- >", "->").replace("->", " -> ").replace("# ", "#");
+    let tokens: Vec<&str> = dsl.split_whitespace().collect();
+    let Some(arrow) = tokens.iter().position(|t| *t == "->") else {
+        return Ok(());
+    };
+    match &tokens[arrow + 1..] {
+        [] => Err("`->` must be followed by a target selector (e.g. `-> #list`)".to_string()),
+        [_target] => Ok(()),
+        [_target, swap] if SWAP_MODES.contains(swap) => Ok(()),
+        [_target, swap] => Err(format!(
+            "Unknown swap mode `{}`. Expected one of: {}",
+            swap,
+            SWAP_MODES.join(", ")
+        )),
+        [_target, _swap, extra, ..] => Err(format!(
+            "Unexpected `{}` after the swap mode. Format: `-> target [swap]`",
+            extra
+        )),
+    }
+}
+
 fn strip_outer_quotes(s: &str) -> String {
     let trimmed = s.trim();
     if trimmed.len() >= 2
@@ -493,6 +684,8 @@ struct GenerationContext {
     scope_id: Option<String>,
     valid_classes: std::collections::HashSet<String>,
     valid_ids: std::collections::HashSet<String>,
+    /// Struct of the enclosing `<form bind={...}>`, for HTML5 constraint attributes
+    bind_struct: Option<syn::Path>,
 }
 
 impl GenerationContext {
@@ -502,6 +695,7 @@ impl GenerationContext {
             scope_id: None,
             valid_classes: std::collections::HashSet::new(),
             valid_ids: std::collections::HashSet::new(),
+            bind_struct: None,
         }
     }
 
@@ -515,6 +709,7 @@ impl GenerationContext {
             scope_id: Some(scope_id),
             valid_classes,
             valid_ids,
+            bind_struct: None,
         }
     }
 
@@ -524,10 +719,70 @@ impl GenerationContext {
             scope_id: self.scope_id.clone(),
             valid_classes: self.valid_classes.clone(),
             valid_ids: self.valid_ids.clone(),
+            bind_struct: self.bind_struct.clone(),
         }
     }
 }
 
+/// Render code under construction. Adjacent static HTML is merged into a
+/// single `f.write_str` call instead of one `write!` per piece.
+#[derive(Default)]
+struct Emitter {
+    code: Vec<proc_macro2::TokenStream>,
+    text: String,
+    /// Static bytes written on every render (blocks not included)
+    static_len: usize,
+}
+
+impl Emitter {
+    /// Static HTML, written as-is
+    fn text(&mut self, html: &str) {
+        self.text.push_str(html);
+        self.static_len += html.len();
+    }
+
+    /// Static text, escaped here rather than on every render
+    fn escaped(&mut self, text: &str) {
+        self.text(&escape_html(text));
+    }
+
+    fn code(&mut self, tokens: proc_macro2::TokenStream) {
+        self.flush_text();
+        self.code.push(tokens);
+    }
+
+    fn flush_text(&mut self) {
+        if !self.text.is_empty() {
+            let text = std::mem::take(&mut self.text);
+            self.code.push(quote! { f.write_str(#text)?; });
+        }
+    }
+
+    fn finish(mut self) -> proc_macro2::TokenStream {
+        self.flush_text();
+        let code = self.code;
+        quote! { #(#code)* }
+    }
+}
+
+/// Same escaping as `azumi::write_escaped` (which the macro can't call at
+/// expansion time); `test_static_escaping_matches_runtime` in
+/// `tests/render_tests.rs` keeps the two in sync
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#x27;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
 fn collect_all_styles(nodes: &[token_parser::Node]) -> (String, String) {
     let mut global_css = String::new();
     let mut scoped_css = String::new();
@@ -581,6 +836,23 @@ fn collect_styles_recursive(
                 token_parser::Block::For(for_block) => {
                     collect_styles_recursive(&for_block.body, global_css, scoped_css);
                 }
+                token_parser::Block::Suspense(suspense_block) => {
+                    collect_styles_recursive(&suspense_block.body, global_css, scoped_css);
+                }
+                token_parser::Block::Cache(cache_block) => {
+                    collect_styles_recursive(&cache_block.body, global_css, scoped_css);
+                }
+                token_parser::Block::Try(try_block) => {
+                    for body in [&try_block.body, &try_block.fallback] {
+                        collect_styles_recursive(body, global_css, scoped_css);
+                    }
+                }
+                token_parser::Block::Provide(provide_block) => {
+                    collect_styles_recursive(&provide_block.body, global_css, scoped_css);
+                }
+                token_parser::Block::Slot(slot_block) => {
+                    collect_styles_recursive(&slot_block.body, global_css, scoped_css);
+                }
                 token_parser::Block::Match(match_block) => {
                     for arm in &match_block.arms {
                         collect_styles_recursive(&arm.body, global_css, scoped_css);
@@ -599,10 +871,10 @@ fn collect_styles_recursive(
 fn generate_body(
     nodes: &[token_parser::Node],
     span: Option<(usize, usize)>,
-) -> proc_macro2::TokenStream {
+) -> (proc_macro2::TokenStream, usize) {
     let css_validation_errors = css_validator::validate_component_css(nodes);
     if !css_validation_errors.is_empty() {
-        return css_validation_errors;
+        return (css_validation_errors, 0);
     }
 
     let order_errors = html_structure_validator::validate_node_order(nodes);
@@ -611,7 +883,7 @@ fn generate_body(
         for err in order_errors {
             tokens.extend(err);
         }
-        return tokens;
+        return (tokens, 0);
     }
 
     let raw_warnings = html_structure_validator::validate_raw_usage(nodes);
@@ -620,7 +892,7 @@ fn generate_body(
         for warn in raw_warnings {
             tokens.extend(warn);
         }
-        return tokens;
+        return (tokens, 0);
     }
 
     let (global_css, scoped_css) = collect_all_styles(nodes);
@@ -629,7 +901,7 @@ fn generate_body(
     let style_validation_errors =
         validate_nodes(nodes, &valid_classes, &valid_ids, !scoped_css.is_empty());
     if !style_validation_errors.is_empty() {
-        return style_validation_errors;
+        return (style_validation_errors, 0);
     }
 
     let has_global = !global_css.is_empty();
@@ -680,7 +952,7 @@ fn generate_body(
             String::new()
         };
 
-        let (body_content, css_injected) = if let Some(sid) = &scope_id {
+        let ((body_content, size_hint), css_injected) = if let Some(sid) = &scope_id {
             let mut working_nodes = nodes.to_vec();
             let injected = inject_css_into_head(&mut working_nodes, &css_to_inject);
             let ctx = GenerationContext::with_scope(
@@ -688,26 +960,29 @@ fn generate_body(
                 valid_classes.clone(),
                 valid_ids.clone(),
             );
-            (generate_body_with_context(&working_nodes, &ctx), injected)
+            (generate_body_sized(&working_nodes, &ctx), injected)
         } else {
             let mut temp_nodes = nodes.to_vec();
             let injected = inject_css_into_head(&mut temp_nodes, &css_to_inject);
             (
-                generate_body_with_context(&temp_nodes, &GenerationContext::normal()),
+                generate_body_sized(&temp_nodes, &GenerationContext::normal()),
                 injected,
             )
         };
 
         if css_injected {
-            body_content
+            (body_content, size_hint)
         } else {
-            quote! {
-                write!(f, "{}", #css_to_inject)?;
-                #body_content
-            }
+            (
+                quote! {
+                    f.write_str(#css_to_inject)?;
+                    #body_content
+                },
+                size_hint + css_to_inject.len(),
+            )
         }
     } else {
-        generate_body_with_context(nodes, &GenerationContext::normal())
+        generate_body_sized(nodes, &GenerationContext::normal())
     }
 }
 
@@ -783,6 +1058,24 @@ fn validate_nodes(
                             }
                         }
 
+                        if name == "az-on" || name.starts_with("on:") {
+                            let dsl = match &attr.value {
+                                token_parser::AttributeValue::Static(val) => {
+                                    Some(strip_outer_quotes(val))
+                                }
+                                token_parser::AttributeValue::Dynamic(tokens) => {
+                                    Some(tokens.to_string())
+                                }
+                                _ => None,
+                            };
+                            if let Some(Err(msg)) = dsl.as_deref().map(validate_swap) {
+                                let error_span = attr.value_span.unwrap_or(attr.span);
+                                errors.push(quote_spanned! { error_span =>
+                                    compile_error!(#msg);
+                                });
+                            }
+                        }
+
                         if name == "class" {
                             match &attr.value {
                                 token_parser::AttributeValue::Static(_) => {
@@ -984,6 +1277,68 @@ fn validate_nodes(
                             is_inside_anchor,
                         );
                     }
+                    token_parser::Block::Suspense(suspense_block) => {
+                        collect_errors_recursive(
+                            &suspense_block.body,
+                            valid_classes,
+                            valid_ids,
+                            _has_scoped_css,
+                            errors,
+                            is_inside_form,
+                            is_inside_button,
+                            is_inside_anchor,
+                        );
+                    }
+                    token_parser::Block::Cache(cache_block) => {
+                        collect_errors_recursive(
+                            &cache_block.body,
+                            valid_classes,
+                            valid_ids,
+                            _has_scoped_css,
+                            errors,
+                            is_inside_form,
+                            is_inside_button,
+                            is_inside_anchor,
+                        );
+                    }
+                    token_parser::Block::Try(try_block) => {
+                        for body in [&try_block.body, &try_block.fallback] {
+                            collect_errors_recursive(
+                                body,
+                                valid_classes,
+                                valid_ids,
+                                _has_scoped_css,
+                                errors,
+                                is_inside_form,
+                                is_inside_button,
+                                is_inside_anchor,
+                            );
+                        }
+                    }
+                    token_parser::Block::Provide(provide_block) => {
+                        collect_errors_recursive(
+                            &provide_block.body,
+                            valid_classes,
+                            valid_ids,
+                            _has_scoped_css,
+                            errors,
+                            is_inside_form,
+                            is_inside_button,
+                            is_inside_anchor,
+                        );
+                    }
+                    token_parser::Block::Slot(slot_block) => {
+                        collect_errors_recursive(
+                            &slot_block.body,
+                            valid_classes,
+                            valid_ids,
+                            _has_scoped_css,
+                            errors,
+                            is_inside_form,
+                            is_inside_button,
+                            is_inside_anchor,
+                        );
+                    }
                     token_parser::Block::Match(match_block) => {
                         for arm in &match_block.arms {
                             collect_errors_recursive(
@@ -1027,33 +1382,35 @@ fn generate_body_with_context(
     nodes: &[token_parser::Node],
     ctx: &GenerationContext,
 ) -> proc_macro2::TokenStream {
-    let mut instructions = Vec::new();
+    generate_body_sized(nodes, ctx).0
+}
+
+/// Render code plus the number of static bytes it always writes
+fn generate_body_sized(
+    nodes: &[token_parser::Node],
+    ctx: &GenerationContext,
+) -> (proc_macro2::TokenStream, usize) {
+    let mut out = Emitter::default();
+    emit_nodes(nodes, ctx, &mut out);
+    let static_len = out.static_len;
+    (out.finish(), static_len)
+}
 
+fn emit_nodes(nodes: &[token_parser::Node], ctx: &GenerationContext, out: &mut Emitter) {
     for node in nodes {
         match node {
             token_parser::Node::Text(text) => {
                 let content = &text.content;
                 let clean_content = strip_outer_quotes(content);
-                if !clean_content.is_empty() {
-                    instructions.push(quote! {
-                        write!(f, "{}", azumi::Escaped(&#clean_content))?;
-                    });
-                }
+                out.escaped(&clean_content);
             }
             token_parser::Node::RawText(text) => {
-                let content = &text.content;
-                if !content.is_empty() {
-                    instructions.push(quote! {
-                        write!(f, "{}", #content)?;
-                    });
-                }
+                out.text(&text.content);
             }
             token_parser::Node::Element(elem) => {
                 let name = &elem.name;
 
-                instructions.push(quote! {
-                   write!(f, "<{}", #name)?;
-                });
+                out.text(&format!("<{}", name));
 
                 for attr in &elem.attrs {
                     let attr_name = &attr.name;
@@ -1065,16 +1422,14 @@ fn generate_body_with_context(
                             match &attr.value {
                                 token_parser::AttributeValue::Dynamic(tokens) => {
                                     // Evaluate the expression and escape for HTML attribute
-                                    instructions.push(quote! {
+                                    out.code(quote! {
                                         let __scope_val: String = #tokens;
                                         write!(f, " {}=\"{}\"", #attr_name, azumi::Escaped(&__scope_val))?;
                                     });
                                 }
                                 token_parser::AttributeValue::Static(val) => {
                                     let clean = strip_outer_quotes(val);
-                                    instructions.push(quote! {
-                                        write!(f, " {}=\"{}\"", #attr_name, azumi::Escaped(&#clean))?;
-                                    });
+                                    out.text(&format!(" {}=\"{}\"", attr_name, escape_html(&clean)));
                                 }
                                 _ => {}
                             }
@@ -1085,15 +1440,11 @@ fn generate_body_with_context(
                         match &attr.value {
                             token_parser::AttributeValue::Dynamic(tokens) => {
                                 let s = tokens.to_string(); // Stringify tokens
-                                instructions.push(quote! {
-                                    write!(f, " {}=\"{}\"", #attr_name, azumi::Escaped(&#s))?;
-                                });
+                                out.text(&format!(" {}=\"{}\"", attr_name, escape_html(&s)));
                             }
                             token_parser::AttributeValue::Static(val) => {
                                 let clean = strip_outer_quotes(val);
-                                instructions.push(quote! {
-                                    write!(f, " {}=\"{}\"", #attr_name, azumi::Escaped(&#clean))?;
-                                });
+                                out.text(&format!(" {}=\"{}\"", attr_name, escape_html(&clean)));
                             }
                             _ => {}
                         }
@@ -1134,17 +1485,13 @@ fn generate_body_with_context(
 
                                 let event_name = attr_name.strip_prefix("on:").unwrap_or(attr_name);
                                 let dsl = format!("{} call {}", event_name, s);
-                                instructions.push(quote! {
-                                    write!(f, " az-on=\"{}\"", azumi::Escaped(&#dsl))?;
-                                });
+                                out.text(&format!(" az-on=\"{}\"", escape_html(&dsl)));
                             }
                             token_parser::AttributeValue::Static(val) => {
                                 let clean = strip_outer_quotes(val);
                                 let event_name = attr_name.strip_prefix("on:").unwrap_or(attr_name);
                                 let dsl = format!("{} call {}", event_name, clean);
-                                instructions.push(quote! {
-                                    write!(f, " az-on=\"{}\"", azumi::Escaped(&#dsl))?;
-                                });
+                                out.text(&format!(" az-on=\"{}\"", escape_html(&dsl)));
                             }
                             _ => {}
                         }
@@ -1155,9 +1502,7 @@ fn generate_body_with_context(
                         match &attr.value {
                             token_parser::AttributeValue::Static(val) => {
                                 let clean = strip_outer_quotes(val);
-                                instructions.push(quote! {
-                                    write!(f, " {}=\"{}\"", #attr_name, azumi::Escaped(&#clean))?;
-                                });
+                                out.text(&format!(" {}=\"{}\"", attr_name, escape_html(&clean)));
                             }
                             token_parser::AttributeValue::Dynamic(tokens) => {
                                 // Try parsing as multi-expr
@@ -1171,13 +1516,13 @@ fn generate_body_with_context(
                                         for e in exprs {
                                             format_args.push(quote! { #e });
                                         }
-                                        instructions.push(quote! {
+                                        out.code(quote! {
                                             write!(f, " class=\"{}\"", azumi::Escaped(&format!(#fmt, #(#format_args),*)))?;
                                         });
                                     }
                                     _ => {
                                         // Fallback or empty
-                                        instructions.push(quote! {
+                                        out.code(quote! {
                                             write!(f, " class=\"{}\"", azumi::Escaped(&#tokens))?;
                                         });
                                     }
@@ -1191,26 +1536,24 @@ fn generate_body_with_context(
                     if attr_name == "style" {
                         match &attr.value {
                             token_parser::AttributeValue::StyleDsl(props) => {
-                                instructions.push(quote! { write!(f, " style=\"")?; });
+                                out.text(" style=\"");
                                 for (i, (key, val)) in props.iter().enumerate() {
                                     if i > 0 {
-                                        instructions.push(quote! { write!(f, "; ")?; });
+                                        out.text("; ");
                                     }
-                                    instructions.push(quote! {
+                                    out.code(quote! {
                                         write!(f, "{}: {}", azumi::Escaped(&#key), azumi::escape_css_string(&#val))?;
                                     });
                                 }
-                                instructions.push(quote! { write!(f, "\"")?; });
+                                out.text("\"");
                             }
                             _ => match &attr.value {
                                 token_parser::AttributeValue::Static(val) => {
                                     let clean = strip_outer_quotes(val);
-                                    instructions.push(quote! {
-                                        write!(f, " {}=\"{}\"", #attr_name, azumi::Escaped(&#clean))?;
-                                    });
+                                    out.text(&format!(" {}=\"{}\"", attr_name, escape_html(&clean)));
                                 }
                                 token_parser::AttributeValue::Dynamic(expr) => {
-                                    instructions.push(quote! {
+                                    out.code(quote! {
                                               write!(f, " {}=\"{}\"", #attr_name, azumi::Escaped(&#expr))?;
                                           });
                                 }
@@ -1222,60 +1565,76 @@ fn generate_body_with_context(
                         match &attr.value {
                             token_parser::AttributeValue::Static(val) => {
                                 let clean = strip_outer_quotes(val);
-                                instructions.push(quote! {
-                                    write!(f, " {}=\"{}\"", #attr_name, azumi::Escaped(&#clean))?;
-                                });
+                                out.text(&format!(" {}=\"{}\"", attr_name, escape_html(&clean)));
                             }
                             token_parser::AttributeValue::Dynamic(expr) => {
-                                instructions.push(quote! {
+                                out.code(quote! {
                                     write!(f, " {}=\"{}\"", #attr_name, azumi::Escaped(&#expr))?;
                                 });
                             }
                             token_parser::AttributeValue::None => {
-                                instructions.push(quote! {
-                                    write!(f, " {}", #attr_name)?;
-                                });
+                                out.text(&format!(" {}", attr_name));
                             }
                             _ => {}
                         }
                     }
                 }
 
+                // Inputs of a bind form get the struct's HTML5 constraint attributes
+                // (required, maxlength, pattern, ...) from #[derive(azumi::Validate)]
+                if let Some(bind_struct) = &ctx.bind_struct {
+                    if matches!(name.as_str(), "input" | "textarea" | "select") {
+                        let field = elem.attrs.iter().find_map(|attr| match &attr.value {
+                            token_parser::AttributeValue::Static(val) if attr.name == "name" => {
+                                Some(strip_outer_quotes(val))
+                            }
+                            _ => None,
+                        });
+                        if let Some(field) = field {
+                            let existing = elem.attrs.iter().map(|attr| attr.name.as_str());
+                            out.code(quote! {
+                                {
+                                    #[allow(unused_imports)]
+                                    use azumi::validate::FallbackConstraints as _;
+                                    write!(f, "{}", azumi::validate::Constraints::<#bind_struct>::new().html_attrs(#field, &[#(#existing),*]))?;
+                                }
+                            });
+                        }
+                    }
+                }
+
                 if let Some(sid) = &ctx.scope_id {
-                    instructions.push(quote! {
-                        write!(f, " data-{}=\"{}\"", #sid, #sid)?;
-                    });
+                    out.text(&format!(" data-{}=\"{}\"", sid, sid));
                 }
 
-                instructions.push(quote! {
-                   write!(f, ">")?;
-                });
+                out.text(">");
 
-                let child_ctx = ctx.with_mode(if name == "script" {
+                let mut child_ctx = ctx.with_mode(if name == "script" {
                     Context::Script
                 } else {
                     ctx.mode.clone()
                 });
-                instructions.push(generate_body_with_context(&elem.children, &child_ctx));
+                if elem.bind_struct.is_some() {
+                    child_ctx.bind_struct = elem.bind_struct.clone();
+                }
+                emit_nodes(&elem.children, &child_ctx, out);
 
                 let void_elements = [
                     "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta",
                     "param", "source", "track", "wbr",
                 ];
                 if !void_elements.contains(&name.as_str()) {
-                    instructions.push(quote! {
-                        write!(f, "</{}>", #name)?;
-                    });
+                    out.text(&format!("</{}>", name));
                 }
             }
             token_parser::Node::Expression(expr) => {
                 let tokens = &expr.content;
-                instructions.push(quote! {
-                    azumi::RenderWrapper(&(#tokens)).render_azumi(f)?;
+                out.code(quote! {
+                    (&azumi::RenderWrapper(&(#tokens))).render_azumi(f)?;
                 });
             }
             token_parser::Node::Fragment(frag) => {
-                instructions.push(generate_body_with_context(&frag.children, ctx));
+                emit_nodes(&frag.children, ctx, out);
             }
             token_parser::Node::Block(block) => {
                 match block {
@@ -1289,7 +1648,7 @@ fn generate_body_with_context(
                             quote! {}
                         };
 
-                        instructions.push(quote! {
+                        out.code(quote! {
                             if #cond {
                                 #then_body
                             } #else_part
@@ -1300,7 +1659,7 @@ fn generate_body_with_context(
                         let iter = &for_block.iterator;
                         let body = generate_body_with_context(&for_block.body, ctx);
 
-                        instructions.push(quote! {
+                        out.code(quote! {
                             for #pat in #iter {
                                 #body
                             }
@@ -1316,7 +1675,7 @@ fn generate_body_with_context(
                                 #pat => { #body }
                             });
                         }
-                        instructions.push(quote! {
+                        out.code(quote! {
                             match #expr {
                                 #(#arms),*
                             }
@@ -1331,7 +1690,7 @@ fn generate_body_with_context(
                         let args_list = match parse_args(call_block.args.clone()) {
                             Ok(a) => a,
                             Err(e) => {
-                                instructions.push(e.to_compile_error());
+                                out.code(e.to_compile_error());
                                 Vec::new() // Should not proceed but this is best effort
                             }
                         };
@@ -1342,20 +1701,55 @@ fn generate_body_with_context(
                             quote! { .#key(#val) }
                         });
 
+                        // `@slot name { ... }` children fill named slots (`.slot_name(...)`
+                        // on the builder); the rest is `children`
+                        let mut slot_setters = Vec::new();
+                        let mut children = Vec::new();
+                        let mut seen_slots = std::collections::HashSet::new();
+                        for child in &call_block.children {
+                            let token_parser::Node::Block(token_parser::Block::Slot(slot_block)) =
+                                child
+                            else {
+                                children.push(child.clone());
+                                continue;
+                            };
+                            let name = &slot_block.name;
+                            if !seen_slots.insert(name.to_string()) {
+                                out.code(
+                                    syn::Error::new(
+                                        name.span(),
+                                        format!("slot `{}` is filled more than once", name),
+                                    )
+                                    .to_compile_error(),
+                                );
+                                continue;
+                            }
+                            let setter = quote::format_ident!("slot_{}", name, span = name.span());
+                            let body = generate_body_with_context(&slot_block.body, ctx);
+                            slot_setters.push(quote! {
+                                .#setter(azumi::from_fn_once(move |f| azumi::error::run(|| {
+                                    #body
+                                    Ok(())
+                                })))
+                            });
+                        }
+
+                        // Spanned so that a missing required slot points at the call
+                        let build = quote_spanned! {call_block.span=> .build() };
                         let builder_expr = quote! {
                             #func_mod_path::Props::builder()
                             #(#setters)*
-                            .build()
+                            #(#slot_setters)*
+                            #build
                             .expect("Failed to build props")
                         };
 
-                        if call_block.children.is_empty() {
-                            instructions.push(quote! {
+                        if children.is_empty() {
+                            out.code(quote! {
                                 #func_mod_path::render(#builder_expr).render(f)?;
                             });
                         } else {
-                            let children_body =
-                                generate_body_with_context(&call_block.children, ctx);
+                            let children_body = generate_body_with_context(&children, ctx);
                             // Wrap children in a component-compatible closure
                             // IMPORTANT: Use `from_fn_once` here instead of `from_fn` because
                             // children closures may capture owned values (via `move`) that were
@@ -1369,13 +1763,13 @@ fn generate_body_with_context(
                             // a loop), ensure they don't capture owned values, or use `from_fn`
                             // with `Arc<Clone>` for shared owned data.
                             let children_arg = quote! {
-                                azumi::from_fn_once(move |f| {
+                                azumi::from_fn_once(move |f| azumi::error::run(|| {
                                     #children_body
                                     Ok(())
-                                })
+                                }))
                             };
 
-                            instructions.push(quote! {
+                            out.code(quote! {
                                 #func_mod_path::render(#builder_expr, #children_arg).render(f)?;
                             });
                         }
@@ -1383,13 +1777,123 @@ fn generate_body_with_context(
                     token_parser::Block::Let(let_block) => {
                         let pat = &let_block.pattern;
                         let val = &let_block.value;
-                        instructions.push(quote! {
+                        out.code(quote! {
                             let #pat = #val;
                         });
                     }
                     token_parser::Block::Style(_) => {
                         // Handled in hoisting pass
                     }
+                    token_parser::Block::Flush(_) => {
+                        out.code(quote! {
+                            azumi::stream::flush();
+                        });
+                    }
+                    token_parser::Block::Cache(cache_block) => {
+                        if let Some(span) = find_suspense(&cache_block.body) {
+                            out.code(
+                                syn::Error::new(
+                                    span,
+                                    "@suspense can't be cached: move it out of the @cache block",
+                                )
+                                .to_compile_error(),
+                            );
+                            continue;
+                        }
+                        let key = &cache_block.key;
+                        let ttl = match &cache_block.ttl {
+                            Some(ttl) => quote! { Some(#ttl) },
+                            None => quote! { None },
+                        };
+                        let body = generate_body_with_context(&cache_block.body, ctx);
+                        out.code(quote! {
+                            azumi::Component::render(
+                                &azumi::cache::cached(
+                                    #key,
+                                    #ttl,
+                                    azumi::from_fn_once(move |f| azumi::error::run(|| {
+                                        #body
+                                        Ok(())
+                                    })),
+                                ),
+                                f,
+                            )?;
+                        });
+                    }
+                    token_parser::Block::Try(try_block) => {
+                        // The body is rendered into a buffer by `azumi::error::catch`,
+                        // which renders the fallback instead if it fails
+                        let body = generate_body_with_context(&try_block.body, ctx);
+                        let fallback = generate_body_with_context(&try_block.fallback, ctx);
+                        let error = &try_block.error;
+                        out.code(quote! {
+                            azumi::Component::render(
+                                &azumi::error::catch(
+                                    azumi::from_fn_once(move |f| azumi::error::run(|| {
+                                        #body
+                                        Ok(())
+                                    })),
+                                    move |#error: azumi::RenderError| {
+                                        azumi::from_fn_once(move |f| azumi::error::run(|| {
+                                            #fallback
+                                            Ok(())
+                                        }))
+                                    },
+                                ),
+                                f,
+                            )?;
+                        });
+                    }
+                    token_parser::Block::Provide(provide_block) => {
+                        // Provided until the end of the block (also on `?`)
+                        let value = &provide_block.value;
+                        let body = generate_body_with_context(&provide_block.body, ctx);
+                        out.code(quote! {
+                            {
+                                let _azumi_provided = azumi::context::enter(#value);
+                                #body
+                            }
+                        });
+                    }
+                    token_parser::Block::Slot(slot_block) => {
+                        out.code(
+                            syn::Error::new(
+                                slot_block.span,
+                                "@slot is only valid directly inside a component call: @Card { @slot header { ... } }",
+                            )
+                            .to_compile_error(),
+                        );
+                    }
+                    token_parser::Block::Suspense(suspense_block) => {
+                        // Top-level @let run in the async block (they may .await);
+                        // the rest is rendered once they have resolved
+                        let mut lets = Vec::new();
+                        let mut rest = Vec::new();
+                        for node in &suspense_block.body {
+                            match node {
+                                token_parser::Node::Block(token_parser::Block::Let(let_block)) => {
+                                    let pat = &let_block.pattern;
+                                    let val = &let_block.value;
+                                    lets.push(quote! { let #pat = #val; });
+                                }
+                                other => rest.push(other.clone()),
+                            }
+                        }
+                        let fallback = &suspense_block.fallback;
+                        let body = generate_body_with_context(&rest, ctx);
+                        out.code(quote! {
+                            azumi::Component::render(
+                                &azumi::suspense::suspense(#fallback, async move {
+                                    #(#lets)*
+                                    azumi::from_fn_once(move |f| azumi::error::run(|| {
+                                        #body
+                                        Ok(())
+                                    }))
+                                }),
+                                f,
+                            )?;
+                        });
+                    }
                     // IMPORTANT: Fix for previous error - explicitly handle all variants or wildcard
                     // Since we have specific handlers for known types, wildcard is safe
                     _ => {}
@@ -1399,8 +1903,25 @@ fn generate_body_with_context(
             _ => {}
         }
     }
+}
 
-    quote! {
-        #(#instructions)*
+#[cfg(test)]
+mod tests {
+    use super::validate_swap;
+
+    #[test]
+    fn test_validate_swap() {
+        assert!(validate_swap("click call save").is_ok());
+        assert!(validate_swap("click call send -> #log beforeend").is_ok());
+        assert!(validate_swap("click call remove -> #row_3 delete").is_ok());
+        // TokenStream spacing from az-on={...}
+        assert!(validate_swap("click call send - > # log afterbegin").is_ok());
+        assert!(validate_swap("click call toggle -> # box").is_ok());
+
+        let err = validate_swap("click call send -> #log append").unwrap_err();
+        assert!(err.contains("Unknown swap mode `append`"));
+        assert!(err.contains("beforeend"));
+        assert!(validate_swap("click call send ->").is_err());
+        assert!(validate_swap("click call send -> #log inner now").is_err());
     }
 }
@@ -6,8 +6,9 @@
 //! 3. Generates prediction metadata for client-side optimistic updates
 //! 4. Auto-registers server action handlers
 
+use crate::middleware::RouteMiddleware;
 use proc_macro::TokenStream;
-use quote::{format_ident, quote};
+use quote::{format_ident, quote, quote_spanned};
 use syn::{
     parse_macro_input, BinOp, Expr, ExprAssign, ExprBinary, ExprField, ExprMethodCall, ExprPath,
     ExprUnary, Fields, ImplItem, ImplItemFn, ItemImpl, ItemStruct, Member, Stmt, UnOp,
@@ -249,25 +250,349 @@ fn is_self_field_mutation(mc: &ExprMethodCall) -> bool {
     }
 }
 
+/// Whether an attribute is `#[on_emit(..)]` / `#[azumi::on_emit(..)]`
+fn is_on_emit(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|seg| seg.ident == "on_emit")
+}
+
+/// Whether an attribute is `#[route(..)]` / `#[azumi::route(..)]`
+fn is_route(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|seg| seg.ident == "route")
+}
+
+/// Whether an attribute is a method-level `#[live(..)]` marker
+fn is_live_marker(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|seg| seg.ident == "live")
+}
+
+/// Whether a method is marked `#[live(emit)]`: its return value is sent to
+/// the parent component
+fn parse_live_emit(method: &ImplItemFn) -> syn::Result<bool> {
+    let mut emit = false;
+    for attr in method.attrs.iter().filter(|a| is_live_marker(a)) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("emit") {
+                emit = true;
+                Ok(())
+            } else {
+                Err(meta.error("Unknown #[live] method attribute. Expected: emit"))
+            }
+        })?;
+    }
+    if emit && matches!(method.sig.output, syn::ReturnType::Default) {
+        return Err(syn::Error::new_spanned(
+            &method.sig,
+            "#[live(emit)] methods must return the event to emit",
+        ));
+    }
+    Ok(emit)
+}
+
+/// Guards, layers and rate limit from `#[azumi::route(..)]` (several are merged)
+fn parse_route(method: &ImplItemFn) -> syn::Result<RouteMiddleware> {
+    let mut middleware = RouteMiddleware::default();
+    for attr in method.attrs.iter().filter(|a| is_route(a)) {
+        middleware.extend(RouteMiddleware::parse_attr(attr)?)?;
+    }
+    Ok(middleware)
+}
+
+/// Child struct named by `#[azumi::on_emit(Child)]`, if present
+fn parse_on_emit(method: &ImplItemFn) -> syn::Result<Option<syn::Ident>> {
+    let Some(attr) = method.attrs.iter().find(|a| is_on_emit(a)) else {
+        return Ok(None);
+    };
+    let path: syn::Path = attr.parse_args()?;
+    match path.segments.last() {
+        Some(seg) => Ok(Some(seg.ident.clone())),
+        None => Err(syn::Error::new_spanned(attr, "Expected #[on_emit(ChildStruct)]")),
+    }
+}
+
+/// Event type of an `#[on_emit]` method (`fn(&mut self, event: E)`).
+/// Returns the type to decode and the accessor applied to the decoded
+/// `Emitted<E>` (empty when the method takes `Emitted<E>` itself).
+fn emit_event_type(method: &ImplItemFn) -> syn::Result<(syn::Type, proc_macro2::TokenStream)> {
+    let mut inputs = method.sig.inputs.iter();
+    let event = match (inputs.next(), inputs.next(), inputs.next()) {
+        (Some(syn::FnArg::Receiver(_)), Some(syn::FnArg::Typed(event)), None) => event,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &method.sig,
+                "#[on_emit] methods take exactly one event argument: fn(&mut self, event: E)",
+            ))
+        }
+    };
+    if let syn::Type::Path(type_path) = &*event.ty {
+        if let Some(seg) = type_path.path.segments.last() {
+            if seg.ident == "Emitted" {
+                if let syn::PathArguments::AngleBracketed(args) = &seg.arguments {
+                    if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                        return Ok((inner.clone(), quote! {}));
+                    }
+                }
+            }
+        }
+    }
+    Ok(((*event.ty).clone(), quote! { .event }))
+}
+
 /// Main macro expansion for #[azumi::live]
-pub fn expand_live(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(item as ItemStruct);
-    let struct_name = &input.ident;
-    let struct_vis = &input.vis;
-    let struct_generics = &input.generics;
-    let struct_fields = &input.fields;
+pub fn expand_live(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(item as ItemStruct);
+    let args = parse_macro_input!(attr with syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated);
+
+    // #[azumi::live(hydrate)] - the user implements LiveHydrate themselves
+    // #[azumi::live(history = 20, history_storage = "server")] - undo/redo
+    let mut custom_hydrate = false;
+    let mut history: Option<syn::LitInt> = None;
+    let mut history_storage: Option<syn::LitStr> = None;
+    for arg in &args {
+        match arg {
+            syn::Meta::Path(path) if path.is_ident("hydrate") => custom_hydrate = true,
+            syn::Meta::NameValue(nv) if nv.path.is_ident("history") => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(lit),
+                    ..
+                }) if lit.base10_parse::<usize>().is_ok_and(|n| n > 0) => {
+                    history = Some(lit.clone());
+                }
+                other => {
+                    return syn::Error::new_spanned(
+                        other,
+                        "`history` expects a positive number of states, e.g. history = 20",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+            },
+            syn::Meta::NameValue(nv) if nv.path.is_ident("history_storage") => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) if lit.value() == "client" || lit.value() == "server" => {
+                    history_storage = Some(lit.clone());
+                }
+                other => {
+                    return syn::Error::new_spanned(
+                        other,
+                        "`history_storage` must be \"client\" or \"server\"",
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+            },
+            other => {
+                return syn::Error::new_spanned(
+                    other,
+                    "Unknown #[azumi::live] argument. Expected: hydrate, history = N, history_storage = \"client\" | \"server\"",
+                )
+                .to_compile_error()
+                .into();
+            }
+        }
+    }
+    if let (None, Some(storage)) = (&history, &history_storage) {
+        return syn::Error::new_spanned(storage, "`history_storage` requires `history = N`")
+            .to_compile_error()
+            .into();
+    }
 
     // Validate that struct has named fields
-    if !matches!(struct_fields, Fields::Named(_)) {
+    let Fields::Named(named) = &mut input.fields else {
         return syn::Error::new_spanned(
             &input,
             "#[azumi::live] only supports structs with named fields",
         )
         .to_compile_error()
         .into();
+    };
+
+    // #[live(server)] fields are skipped by serde so they never reach az-scope.
+    // They start as LiveServerField::initial() (a clear error for types
+    // without Default) and are reloaded by LiveHydrate::hydrate in every
+    // generated handler.
+    // A #[live(id)] field becomes the component's explicit az-id.
+    let mut has_server_fields = false;
+    let mut id_field: Option<syn::Ident> = None;
+    for field in named.named.iter_mut() {
+        let mut is_server = false;
+        let mut is_id = false;
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("live")) {
+            if let Err(e) = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("server") {
+                    is_server = true;
+                    Ok(())
+                } else if meta.path.is_ident("id") {
+                    is_id = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("Unknown #[live] field attribute. Expected: server, id"))
+                }
+            }) {
+                return e.to_compile_error().into();
+            }
+        }
+        field.attrs.retain(|a| !a.path().is_ident("live"));
+        if is_id {
+            if is_server {
+                return syn::Error::new_spanned(
+                    &field.ident,
+                    "#[live(id)] field must be part of the client state, not #[live(server)]",
+                )
+                .to_compile_error()
+                .into();
+            }
+            if id_field.is_some() {
+                return syn::Error::new_spanned(
+                    &field.ident,
+                    "Only one field can be marked #[live(id)]",
+                )
+                .to_compile_error()
+                .into();
+            }
+            id_field = field.ident.clone();
+        }
+        if is_server {
+            has_server_fields = true;
+            // Spanned on the field type so the missing-Default error points at it
+            let default_fn = syn::LitStr::new(
+                "azumi::__live_server_field",
+                syn::spanned::Spanned::span(&field.ty),
+            );
+            field
+                .attrs
+                .push(syn::parse_quote!(#[serde(skip, default = #default_fn)]));
+        }
     }
 
+    let struct_name = &input.ident;
+    let struct_vis = &input.vis;
+    let struct_generics = &input.generics;
+    let struct_fields = &input.fields;
     let struct_attrs = &input.attrs;
+    let (impl_generics, ty_generics, where_clause) = struct_generics.split_for_impl();
+
+    // Structs with server fields must say how to reload them
+    let hydrate_impl = if has_server_fields || custom_hydrate {
+        quote! {}
+    } else {
+        quote! {
+            impl #impl_generics azumi::LiveHydrate for #struct_name #ty_generics #where_clause {}
+        }
+    };
+
+    let history_items = match &history {
+        Some(capacity) => {
+            let storage = match history_storage.as_ref().map(|s| s.value()).as_deref() {
+                Some("server") => quote! { azumi::history::HistoryStorage::Server },
+                _ => quote! { azumi::history::HistoryStorage::Client },
+            };
+            let struct_name_str = struct_name.to_string();
+            let mod_name =
+                format_ident!("__azumi_history_handlers_{}", struct_name_str.to_lowercase());
+            let undo_path = format!("/_azumi/action/{}/undo", struct_name_str);
+            let redo_path = format!("/_azumi/action/{}/redo", struct_name_str);
+            quote! {
+                impl #impl_generics azumi::history::LiveHistory for #struct_name #ty_generics #where_clause {
+                    const CAPACITY: usize = #capacity;
+                    const STORAGE: azumi::history::HistoryStorage = #storage;
+                }
+
+                #[allow(non_snake_case)]
+                mod #mod_name {
+                    use super::*;
+
+                    async fn restore(
+                        parts: azumi::axum::http::request::Parts,
+                        body: String,
+                        step: azumi::history::Step,
+                        method: &'static str,
+                    ) -> azumi::axum::response::Response {
+                        let signed = match azumi::history::step::<#struct_name>(&parts.headers, body, step) {
+                            Ok(signed) => signed,
+                            Err(azumi::history::StepError::Empty) => return azumi::axum::response::IntoResponse::into_response((azumi::axum::http::StatusCode::CONFLICT, format!("Nothing to {}", method))),
+                            Err(azumi::history::StepError::Rejected(e)) => return azumi::__stale_state(azumi::axum::http::StatusCode::BAD_REQUEST, format!("Security Error: {}", e)),
+                        };
+                        let json = match azumi::security::verify_state(&signed) {
+                            Ok(j) => j,
+                            Err(e) => return azumi::__stale_state(azumi::axum::http::StatusCode::BAD_REQUEST, format!("Security Error: {}", e)),
+                        };
+                        let mut state: #struct_name = match serde_json::from_str(&json) {
+                            Ok(s) => s,
+                            Err(e) => return azumi::__stale_state(azumi::axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("State Deserialization Error: {}", e)),
+                        };
+                        let ctx = azumi::LiveContext::new(parts);
+                        if let Err(response) = azumi::LiveRender::before_action(&mut state, method, &ctx).await {
+                            return response;
+                        }
+                        azumi::LiveRender::render_live(&state, &ctx)
+                    }
+
+                    pub async fn undo_handler(
+                        parts: azumi::axum::http::request::Parts,
+                        body: String
+                    ) -> azumi::axum::response::Response {
+                        restore(parts, body, azumi::history::Step::Undo, "undo").await
+                    }
+
+                    pub async fn redo_handler(
+                        parts: azumi::axum::http::request::Parts,
+                        body: String
+                    ) -> azumi::axum::response::Response {
+                        restore(parts, body, azumi::history::Step::Redo, "redo").await
+                    }
+
+                    pub fn undo_router() -> azumi::axum::routing::MethodRouter<()> {
+                        azumi::axum::routing::post(undo_handler)
+                    }
+
+                    pub fn redo_router() -> azumi::axum::routing::MethodRouter<()> {
+                        azumi::axum::routing::post(redo_handler)
+                    }
+
+                    azumi::inventory::submit! {
+                        azumi::action::ActionEntry::new(#undo_path, undo_router)
+                            .kind(azumi::action::ActionKind::LiveHistory)
+                            .http_method("POST")
+                            .name("undo")
+                            .live_struct(#struct_name_str)
+                            .payload_type(#struct_name_str)
+                            .content_type("text/plain")
+                            .location(file!(), line!())
+                    }
+
+                    azumi::inventory::submit! {
+                        azumi::action::ActionEntry::new(#redo_path, redo_router)
+                            .kind(azumi::action::ActionKind::LiveHistory)
+                            .http_method("POST")
+                            .name("redo")
+                            .live_struct(#struct_name_str)
+                            .payload_type(#struct_name_str)
+                            .content_type("text/plain")
+                            .location(file!(), line!())
+                    }
+                }
+            }
+        }
+        None => quote! {
+            impl #impl_generics azumi::history::LiveHistory for #struct_name #ty_generics #where_clause {}
+        },
+    };
+
+    let live_id = match &id_field {
+        Some(field) => quote! { Some(self.#field.to_string()) },
+        None => quote! { None },
+    };
 
     // Generate the struct with derives
     let expanded = quote! {
@@ -275,13 +600,21 @@ pub fn expand_live(_attr: TokenStream, item: TokenStream) -> TokenStream {
         #(#struct_attrs)*
         #struct_vis struct #struct_name #struct_generics #struct_fields
 
-        impl #struct_generics #struct_name #struct_generics {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
             /// Serialize state for az-scope attribute
             pub fn to_scope(&self) -> String {
                 let json = serde_json::to_string(self).unwrap_or_default();
                 azumi::security::sign_state(&json)
             }
+
+            /// Explicit component id for the az-id attribute
+            pub fn live_id(&self) -> Option<String> {
+                #live_id
+            }
         }
+
+        #hydrate_impl
+        #history_items
     };
 
     TokenStream::from(expanded)
@@ -297,32 +630,66 @@ pub fn expand_live_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Clean up struct name (remove spaces)
     let struct_name_str = struct_name_str.replace(" ", "");
 
-    // Parse attributes to find component="name"
+    // Parse attributes to find component="name" and mount="/path"
     let args = parse_macro_input!(attr with syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated);
     let mut component_name = None;
+    let mut mount_path: Option<syn::LitStr> = None;
 
     for arg in args {
         if let syn::Meta::NameValue(nv) = arg {
-            if nv.path.is_ident("component") {
-                if let syn::Expr::Lit(syn::ExprLit {
-                    lit: syn::Lit::Str(lit),
-                    ..
-                }) = nv.value
-                {
+            if let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(lit),
+                ..
+            }) = nv.value
+            {
+                if nv.path.is_ident("component") {
                     component_name = Some(lit.value());
+                } else if nv.path.is_ident("mount") {
+                    mount_path = Some(lit);
                 }
             }
         }
     }
 
+    if let (Some(path), None) = (&mount_path, &component_name) {
+        return syn::Error::new_spanned(
+            path,
+            "`mount` requires `component = \"...\"` so the mounted state can be rendered",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let is_mounted = mount_path.is_some();
+
     let mut method_handlers = Vec::new();
     let mut original_methods = Vec::new();
 
+    // Registry metadata: live handlers answer with the component's HTML,
+    // or the state as JSON without a component
+    let (response_type, response_content_type) = match &component_name {
+        Some(comp_name) => (quote! { .response_type(#comp_name) }, "text/html"),
+        None => (quote! { .response_type(#struct_name_str) }, "application/json"),
+    };
+
     let mut predictions_entries = Vec::new();
+    let mut emitted_sources = std::collections::HashSet::new();
+    let mut history_conflicts = Vec::new();
 
     for item in &input.items {
         if let ImplItem::Fn(method) = item {
             let analysis = analyze_method(method);
+            let emit_source = match parse_on_emit(method) {
+                Ok(source) => source,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            let middleware = match parse_route(method) {
+                Ok(middleware) => middleware,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            let emits = match parse_live_emit(method) {
+                Ok(emits) => emits,
+                Err(e) => return e.to_compile_error().into(),
+            };
 
             // Generate prediction string
             let prediction_dsl: String = analysis
@@ -335,79 +702,203 @@ pub fn expand_live_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             let method_name = &method.sig.ident;
             let method_name_str = method_name.to_string();
 
-            if !prediction_dsl.is_empty() {
+            // Emit handlers are called by the client runtime, never from a button
+            if !prediction_dsl.is_empty() && emit_source.is_none() {
                 predictions_entries.push(quote! {
                     (#method_name_str, #prediction_dsl)
                 });
             }
 
+            // `undo`/`redo` would share the route and prediction that
+            // #[azumi::live(history = N)] registers. The history setting lives
+            // on the struct, so the check runs when the constant is evaluated.
+            if method_name_str == "undo" || method_name_str == "redo" {
+                let message = format!(
+                    "`{}::{}` collides with the `{}` that #[azumi::live(history = N)] generates; rename the method or remove `history`",
+                    struct_name_str, method_name_str, method_name_str
+                );
+                history_conflicts.push(quote_spanned! { method_name.span()=>
+                    const _: () = if <#struct_name as azumi::history::LiveHistory>::CAPACITY > 0 {
+                        panic!(#message)
+                    };
+                });
+            }
+
             let handler_name = format_ident!("{}_handler", method_name);
             let router_name = format_ident!("{}_router", method_name);
 
-            // Keep original method
-            original_methods.push(quote! { #method });
+            // Keep original method (minus the #[on_emit] / #[route] / #[live] markers)
+            let mut original = method.clone();
+            original
+                .attrs
+                .retain(|a| !is_on_emit(a) && !is_route(a) && !is_live_marker(a));
+            original_methods.push(quote! { #original });
+
+            // #[on_emit(Child)] methods receive the event from a JSON envelope
+            // that also carries this component's signed state.
+            let (read_body, decode_event, call_args) = if let Some(source) = &emit_source {
+                if !emitted_sources.insert(source.to_string()) {
+                    return syn::Error::new_spanned(
+                        source,
+                        format!("Duplicate #[on_emit({})] handler", source),
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+                let (event_ty, unwrap_event) = match emit_event_type(method) {
+                    Ok(ty) => ty,
+                    Err(e) => return e.to_compile_error().into(),
+                };
+                let source_str = source.to_string();
+                (
+                    quote! {
+                        let envelope: azumi::emit::EmitEnvelope = match serde_json::from_str(&body) {
+                            Ok(envelope) => envelope,
+                            Err(e) => return azumi::axum::response::IntoResponse::into_response((azumi::axum::http::StatusCode::BAD_REQUEST, format!("Emit Envelope Error: {}", e))),
+                        };
+                        let body = envelope.state.clone();
+                    },
+                    quote! {
+                        let emitted = match envelope.decode::<#event_ty>(#source_str, #struct_name_str) {
+                            Ok(emitted) => emitted #unwrap_event,
+                            Err(e) => return azumi::axum::response::IntoResponse::into_response((azumi::axum::http::StatusCode::BAD_REQUEST, format!("Emit Event Error: {}", e))),
+                        };
+                    },
+                    quote! { emitted },
+                )
+            } else {
+                (quote! {}, quote! {}, quote! {})
+            };
 
+            // #[live(emit)] methods send their return value to the parent component
             let is_async = method.sig.asyncness.is_some();
-            let method_call = if is_async {
-                quote! { state.#method_name().await; }
+            let call = if is_async {
+                quote! { state.#method_name(#call_args).await }
             } else {
-                quote! { state.#method_name(); }
+                quote! { state.#method_name(#call_args) }
+            };
+            let (method_call, response_binding, attach_event) = if emits {
+                (
+                    quote! { let event = #call; },
+                    quote! { let mut response },
+                    quote! { azumi::emit::attach(&mut response, ctx.headers(), #struct_name_str, azumi::LiveState::live_id(&state), &event); },
+                )
+            } else {
+                (quote! { #call; }, quote! { let response }, quote! {})
             };
 
+            // Reload #[live(server)] fields before the method runs
+            let mut hydrate_call = quote! {
+                let ctx = azumi::LiveContext::new(parts);
+                if let Err(response) = azumi::LiveHydrate::hydrate(&mut state, &ctx).await {
+                    return response;
+                }
+            };
+            // Mounted components get the LiveComponent lifecycle hooks and
+            // keep the az-mount URL the client sent
+            let (html_binding, render_html, after_render) = if is_mounted {
+                hydrate_call.extend(quote! {
+                    if let Err(response) = azumi::LiveComponent::on_action(&mut state, #method_name_str, &ctx).await {
+                        return response;
+                    }
+                });
+                (
+                    quote! { let mut html },
+                    quote! { azumi::render_mounted(&component, ctx.mount_url()) },
+                    quote! { azumi::LiveComponent::after_render(&state, &mut html, &ctx); },
+                )
+            } else {
+                (
+                    quote! { let html },
+                    quote! { azumi::render_to_string(&component) },
+                    quote! {},
+                )
+            };
+
+            // /_azumi/action/{StructName}/{MethodName}
+            // Emit handlers: /_azumi/action/{StructName}/__emit/{ChildStruct}
+            let action_path = match &emit_source {
+                Some(source) => format!("/_azumi/action/{}/__emit/{}", struct_name_str, source),
+                None => format!("/_azumi/action/{}/{}", struct_name_str, method_name),
+            };
+            let router_body = middleware.wrap(
+                quote! { azumi::axum::routing::post(#handler_name) },
+                quote! { #action_path },
+            );
+            let state_rate_limit = middleware.state_rate_limit(&action_path);
+
             // Generate Axum handler
             let handler = if let Some(comp_name) = &component_name {
                 let comp_mod = syn::Ident::new(comp_name, proc_macro2::Span::call_site());
                 quote! {
                     pub async fn #handler_name(
+                        parts: azumi::axum::http::request::Parts,
                         body: String
-                    ) -> axum::response::Response {
+                    ) -> azumi::axum::response::Response {
+                        #read_body
                         let json = match azumi::security::verify_state(&body) {
                             Ok(j) => j,
-                            Err(e) => return axum::response::IntoResponse::into_response((axum::http::StatusCode::BAD_REQUEST, format!("Security Error: {}", e))),
+                            Err(e) => return azumi::__stale_state(azumi::axum::http::StatusCode::BAD_REQUEST, format!("Security Error: {}", e)),
                         };
                         let mut state: #struct_name = match serde_json::from_str(&json) {
                             Ok(s) => s,
-                            Err(e) => return axum::response::IntoResponse::into_response((axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("State Deserialization Error: {}", e))),
+                            Err(e) => return azumi::__stale_state(azumi::axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("State Deserialization Error: {}", e)),
                         };
+                        #state_rate_limit
+                        #hydrate_call
+                        #decode_event
                         #method_call
+                        azumi::history::record(ctx.headers(), &body, &json, &state);
 
                         let props = match #comp_mod::Props::builder()
                             .state(&state)
                             .build()
                         {
                             Ok(props) => props,
-                            Err(e) => return axum::response::IntoResponse::into_response((axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("Component Build Error: {}", e))),
+                            Err(e) => return azumi::axum::response::IntoResponse::into_response((azumi::axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("Component Build Error: {}", e))),
                         };
-                        let html = azumi::render_to_string(&#comp_mod::render(props));
+                        let component = #comp_mod::render(props);
+                        #html_binding = #render_html;
+                        #after_render
 
-                        axum::response::IntoResponse::into_response(axum::response::Html(html))
+                        #response_binding = azumi::axum::response::IntoResponse::into_response(azumi::axum::response::Html(html));
+                        #attach_event
+                        response
                     }
 
                     #[allow(non_snake_case)]
-                    pub fn #router_name() -> axum::routing::MethodRouter<()> {
-                        axum::routing::post(#handler_name)
+                    pub fn #router_name() -> azumi::axum::routing::MethodRouter<()> {
+                        #router_body
                     }
                 }
             } else {
                 quote! {
                     pub async fn #handler_name(
+                        parts: azumi::axum::http::request::Parts,
                         body: String
-                    ) -> axum::response::Response {
+                    ) -> azumi::axum::response::Response {
+                        #read_body
                         let json = match azumi::security::verify_state(&body) {
                             Ok(j) => j,
-                            Err(e) => return axum::response::IntoResponse::into_response((axum::http::StatusCode::BAD_REQUEST, format!("Security Error: {}", e))),
+                            Err(e) => return azumi::__stale_state(azumi::axum::http::StatusCode::BAD_REQUEST, format!("Security Error: {}", e)),
                         };
                         let mut state: #struct_name = match serde_json::from_str(&json) {
                             Ok(s) => s,
-                            Err(e) => return axum::response::IntoResponse::into_response((axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("State Deserialization Error: {}", e))),
+                            Err(e) => return azumi::__stale_state(azumi::axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("State Deserialization Error: {}", e)),
                         };
+                        #state_rate_limit
+                        #hydrate_call
+                        #decode_event
                         #method_call
-                        axum::response::IntoResponse::into_response(axum::response::Json(state))
+                        azumi::history::record(ctx.headers(), &body, &json, &state);
+                        #response_binding = azumi::axum::response::IntoResponse::into_response(azumi::axum::response::Json(state));
+                        #attach_event
+                        response
                     }
 
                     #[allow(non_snake_case)]
-                    pub fn #router_name() -> axum::routing::MethodRouter<()> {
-                        axum::routing::post(#handler_name)
+                    pub fn #router_name() -> azumi::axum::routing::MethodRouter<()> {
+                        #router_body
                     }
                 }
             };
@@ -415,20 +906,152 @@ pub fn expand_live_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             method_handlers.push(handler);
 
             // Generate inventory registration with NAMESPACED path
-            // /_azumi/action/{StructName}/{MethodName}
-            let action_path = format!("/_azumi/action/{}/{}", struct_name_str, method_name);
+            let (kind, entry_name, payload_type, content_type) = match &emit_source {
+                Some(source) => {
+                    let event_ty = match emit_event_type(method) {
+                        Ok((ty, _)) => ty,
+                        Err(e) => return e.to_compile_error().into(),
+                    };
+                    (
+                        quote! { LiveEmit },
+                        source.to_string(),
+                        quote! { stringify!(#event_ty) },
+                        "application/json",
+                    )
+                }
+                None => (
+                    quote! { LiveMethod },
+                    method_name_str.clone(),
+                    quote! { #struct_name_str },
+                    "text/plain",
+                ),
+            };
+            let line = quote_spanned! { method_name.span()=> line!() };
             let registration = quote! {
                 azumi::inventory::submit! {
-                    azumi::action::ActionEntry {
-                        path: #action_path,
-                        handler: #router_name,
-                    }
+                    azumi::action::ActionEntry::new(#action_path, #router_name)
+                        .kind(azumi::action::ActionKind::#kind)
+                        .http_method("POST")
+                        .name(#entry_name)
+                        .live_struct(#struct_name_str)
+                        .payload_type(#payload_type)
+                        .content_type(#content_type)
+                        #response_type
+                        .response_content_type(#response_content_type)
+                        .location(file!(), #line)
                 }
             };
             method_handlers.push(registration);
         }
     }
 
+    // GET route that mounts the component at a URL (LiveComponent::mount)
+    if let (Some(path), Some(comp_name)) = (&mount_path, &component_name) {
+        let comp_mod = syn::Ident::new(comp_name, proc_macro2::Span::call_site());
+        let path_value = path.value();
+        // Path params feed LiveComponent::Params; paths without params use the query string
+        let params_extractor = if path_value.contains(':') || path_value.contains('*') {
+            quote! { azumi::axum::extract::Path }
+        } else {
+            quote! { azumi::axum::extract::Query }
+        };
+        method_handlers.push(quote! {
+            pub async fn __azumi_mount_handler(
+                mut parts: azumi::axum::http::request::Parts,
+            ) -> azumi::axum::response::Response {
+                let params = match <#params_extractor<<#struct_name as azumi::LiveComponent>::Params> as azumi::axum::extract::FromRequestParts<()>>::from_request_parts(&mut parts, &()).await {
+                    Ok(#params_extractor(params)) => params,
+                    Err(rejection) => return azumi::axum::response::IntoResponse::into_response(rejection),
+                };
+                let url = parts.uri.to_string();
+                let ctx = azumi::LiveContext::new(parts);
+                let state = match <#struct_name as azumi::LiveComponent>::mount(params, &ctx).await {
+                    Ok(state) => state,
+                    Err(response) => return response,
+                };
+
+                let props = match #comp_mod::Props::builder()
+                    .state(&state)
+                    .build()
+                {
+                    Ok(props) => props,
+                    Err(e) => return azumi::axum::response::IntoResponse::into_response((azumi::axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("Component Build Error: {}", e))),
+                };
+                let mut html = azumi::render_mounted(&#comp_mod::render(props), Some(&url));
+                azumi::LiveComponent::after_render(&state, &mut html, &ctx);
+
+                azumi::axum::response::IntoResponse::into_response(azumi::axum::response::Html(html))
+            }
+
+            pub fn __azumi_mount_router() -> azumi::axum::routing::MethodRouter<()> {
+                azumi::axum::routing::get(__azumi_mount_handler)
+            }
+
+            azumi::inventory::submit! {
+                azumi::action::ActionEntry::new(#path, __azumi_mount_router)
+                    .kind(azumi::action::ActionKind::LiveMount)
+                    .name("mount")
+                    .live_struct(#struct_name_str)
+                    .response_type(#comp_name)
+                    .location(file!(), line!())
+            }
+        });
+    }
+
+    // Hooks + rendering shared with handlers generated outside this impl (undo/redo)
+    let on_action = if is_mounted {
+        quote! { azumi::LiveComponent::on_action(self, method, ctx).await?; }
+    } else {
+        quote! { let _ = method; }
+    };
+    let render_live = if let Some(comp_name) = &component_name {
+        let comp_mod = syn::Ident::new(comp_name, proc_macro2::Span::call_site());
+        let (render_html, after_render) = if is_mounted {
+            (
+                quote! { azumi::render_mounted(&#comp_mod::render(props), ctx.mount_url()) },
+                quote! { azumi::LiveComponent::after_render(self, &mut html, ctx); },
+            )
+        } else {
+            (
+                quote! { azumi::render_to_string(&#comp_mod::render(props)) },
+                quote! { let _ = ctx; },
+            )
+        };
+        quote! {
+            let props = match #comp_mod::Props::builder()
+                .state(self)
+                .build()
+            {
+                Ok(props) => props,
+                Err(e) => return azumi::axum::response::IntoResponse::into_response((azumi::axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("Component Build Error: {}", e))),
+            };
+            #[allow(unused_mut)]
+            let mut html = #render_html;
+            #after_render
+            azumi::axum::response::IntoResponse::into_response(azumi::axum::response::Html(html))
+        }
+    } else {
+        quote! {
+            let _ = ctx;
+            azumi::axum::response::IntoResponse::into_response(azumi::axum::response::Json(self))
+        }
+    };
+    let live_render_items = quote! {
+        async fn before_action(
+            &mut self,
+            method: &'static str,
+            ctx: &azumi::LiveContext,
+        ) -> Result<(), azumi::axum::response::Response> {
+            azumi::LiveHydrate::hydrate(self, ctx).await?;
+            #on_action
+            Ok(())
+        }
+
+        fn render_live(&self, ctx: &azumi::LiveContext) -> azumi::axum::response::Response {
+            #render_live
+        }
+    };
+
     let handler_mod_name =
         format_ident!("__azumi_live_handlers_{}", struct_name_str.to_lowercase());
     let expanded = quote! {
@@ -436,11 +1059,22 @@ pub fn expand_live_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             #(#original_methods)*
         }
 
+        #(#history_conflicts)*
+
         impl azumi::LiveStateMetadata for #struct_name {
             fn predictions() -> &'static [(&'static str, &'static str)] {
-                &[
-                    #(#predictions_entries),*
-                ]
+                // #[azumi::live(history = N)] adds undo/redo, which restore the prior state
+                if <Self as azumi::history::LiveHistory>::CAPACITY > 0 {
+                    &[
+                        #(#predictions_entries,)*
+                        ("undo", "@undo"),
+                        ("redo", "@redo")
+                    ]
+                } else {
+                    &[
+                        #(#predictions_entries),*
+                    ]
+                }
             }
             fn struct_name() -> &'static str {
                 #struct_name_str
@@ -451,6 +1085,13 @@ pub fn expand_live_impl(attr: TokenStream, item: TokenStream) -> TokenStream {
             fn to_scope(&self) -> String {
                 self.to_scope()
             }
+            fn live_id(&self) -> Option<String> {
+                self.live_id()
+            }
+        }
+
+        impl azumi::LiveRender for #struct_name {
+            #live_render_items
         }
 
         #[allow(non_snake_case)]
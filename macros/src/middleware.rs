@@ -0,0 +1,217 @@
+//! `guard = ...` / `layer = ...` / `rate_limit(..)` arguments shared by
+//! `#[azumi::action(..)]` and `#[azumi::route(..)]` on live methods.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+
+/// `rate_limit(N, per_minute[, key = ip | user | state])`
+struct RateLimitArgs {
+    capacity: syn::LitInt,
+    period_secs: u64,
+    key: syn::Ident,
+}
+
+impl Parse for RateLimitArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let capacity: syn::LitInt = input.parse()?;
+        if capacity.base10_parse::<u32>()? == 0 {
+            return Err(syn::Error::new_spanned(
+                &capacity,
+                "rate_limit capacity must be positive",
+            ));
+        }
+        input.parse::<syn::Token![,]>()?;
+        let period: syn::Ident = input.parse()?;
+        let period_secs = match period.to_string().as_str() {
+            "per_second" => 1,
+            "per_minute" => 60,
+            "per_hour" => 3600,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &period,
+                    "Expected per_second, per_minute or per_hour",
+                ))
+            }
+        };
+        let mut key = syn::Ident::new("Ip", proc_macro2::Span::call_site());
+        if input.parse::<Option<syn::Token![,]>>()?.is_some() && !input.is_empty() {
+            let name: syn::Ident = input.parse()?;
+            if name != "key" {
+                return Err(syn::Error::new_spanned(
+                    &name,
+                    "Expected `key = ip | user | state`",
+                ));
+            }
+            input.parse::<syn::Token![=]>()?;
+            let value: syn::Ident = input.parse()?;
+            key = match value.to_string().as_str() {
+                "ip" => syn::Ident::new("Ip", value.span()),
+                "user" => syn::Ident::new("User", value.span()),
+                "state" => syn::Ident::new("State", value.span()),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &value,
+                        "Expected ip, user or state",
+                    ))
+                }
+            };
+        }
+        Ok(Self {
+            capacity,
+            period_secs,
+            key,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct RouteMiddleware {
+    guards: Vec<syn::Expr>,
+    layers: Vec<syn::Expr>,
+    rate_limit: Option<RateLimitArgs>,
+}
+
+impl RouteMiddleware {
+    /// Consumes `guard = f` / `layer = expr` / `rate_limit(..)`;
+    /// returns `Ok(false)` for other arguments
+    pub fn parse_arg(&mut self, arg: &syn::Meta) -> syn::Result<bool> {
+        match arg {
+            syn::Meta::NameValue(nv) if nv.path.is_ident("guard") => {
+                self.guards.push(nv.value.clone());
+            }
+            syn::Meta::NameValue(nv) if nv.path.is_ident("layer") => {
+                self.layers.push(nv.value.clone());
+            }
+            syn::Meta::List(list) if list.path.is_ident("rate_limit") => {
+                if self.rate_limit.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        arg,
+                        "Only one rate_limit per route",
+                    ));
+                }
+                self.rate_limit = Some(list.parse_args()?);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub fn extend(&mut self, other: RouteMiddleware) -> syn::Result<()> {
+        self.guards.extend(other.guards);
+        self.layers.extend(other.layers);
+        if let Some(limit) = other.rate_limit {
+            if self.rate_limit.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &limit.capacity,
+                    "Only one rate_limit per route",
+                ));
+            }
+            self.rate_limit = Some(limit);
+        }
+        Ok(())
+    }
+
+    /// Arguments of `#[azumi::route(guard = .., layer = .., rate_limit(..))]`
+    pub fn parse_attr(attr: &syn::Attribute) -> syn::Result<Self> {
+        let args = attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated,
+        )?;
+        let mut middleware = Self::default();
+        for arg in &args {
+            if !middleware.parse_arg(arg)? {
+                return Err(syn::Error::new_spanned(
+                    arg,
+                    "Unknown #[azumi::route] argument. Expected: guard = fn, layer = expr, rate_limit(..)",
+                ));
+            }
+        }
+        Ok(middleware)
+    }
+
+    /// `key = state`, which needs the deserialized live state
+    pub fn limits_by_state(&self) -> bool {
+        self.rate_limit
+            .as_ref()
+            .is_some_and(|limit| limit.key == "State")
+    }
+
+    /// `azumi::rate_limit::RateLimit` constant expression
+    fn rate_limit_expr(limit: &RateLimitArgs) -> TokenStream {
+        let capacity = &limit.capacity;
+        let period_secs = limit.period_secs;
+        let key = &limit.key;
+        quote! {
+            azumi::rate_limit::RateLimit::new(
+                #capacity,
+                std::time::Duration::from_secs(#period_secs),
+                azumi::rate_limit::RateLimitKey::#key,
+            )
+        }
+    }
+
+    /// Statements for a live handler (after the state is deserialized into
+    /// `state`, before `parts` is consumed) enforcing `key = state`
+    pub fn state_rate_limit(&self, route: &str) -> TokenStream {
+        match &self.rate_limit {
+            Some(limit) if limit.key == "State" => {
+                let limit = Self::rate_limit_expr(limit);
+                quote! {
+                    const RATE_LIMIT: azumi::rate_limit::RateLimit = #limit;
+                    let rate_key = match azumi::LiveState::live_id(&state) {
+                        Some(id) => format!("state:{}", id),
+                        None => azumi::rate_limit::ip_key(&parts),
+                    };
+                    if let Err(limited) = RATE_LIMIT.check(#route, &rate_key) {
+                        return azumi::axum::response::IntoResponse::into_response(limited);
+                    }
+                }
+            }
+            _ => quote! {},
+        }
+    }
+
+    /// Body of a `fn() -> MethodRouter<()>` helper wrapping `method_router`.
+    /// Layers are listed outermost first; the rate limit and then the guards
+    /// run inside all layers, in order, before the body is read.
+    pub fn wrap(&self, method_router: TokenStream, route: TokenStream) -> TokenStream {
+        let guards = &self.guards;
+        let rate_limit = match &self.rate_limit {
+            Some(limit) if limit.key != "State" => {
+                let limit = Self::rate_limit_expr(limit);
+                quote! {
+                    const RATE_LIMIT: azumi::rate_limit::RateLimit = #limit;
+                    if let Err(limited) = RATE_LIMIT.check(#route, &RATE_LIMIT.request_key(&parts)) {
+                        return azumi::axum::response::IntoResponse::into_response(limited);
+                    }
+                }
+            }
+            _ => quote! {},
+        };
+        let checks = if guards.is_empty() && rate_limit.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                let router = router.layer(azumi::axum::middleware::from_fn(
+                    |request: azumi::axum::extract::Request, next: azumi::axum::middleware::Next| async move {
+                        let (parts, body) = request.into_parts();
+                        #rate_limit
+                        #(
+                            if let Err(response) = azumi::action::GuardResult::into_guard_result((#guards)(&parts).await) {
+                                return response;
+                            }
+                        )*
+                        next.run(azumi::axum::extract::Request::from_parts(parts, body)).await
+                    },
+                ));
+            }
+        };
+        let layers = self.layers.iter().rev();
+        quote! {
+            let router = #method_router;
+            #checks
+            #(let router = router.layer(#layers);)*
+            router
+        }
+    }
+}
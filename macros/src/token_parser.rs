@@ -95,6 +95,12 @@ pub enum Block {
     Component(ComponentBlock),
     Let(LetBlock),
     Style(StyleBlock),
+    Flush(FlushBlock),
+    Suspense(SuspenseBlock),
+    Slot(SlotBlock),
+    Provide(ProvideBlock),
+    Try(TryBlock),
+    Cache(CacheBlock),
 }
 
 #[derive(Debug, Clone)]
@@ -146,6 +152,54 @@ pub struct ComponentBlock {
     pub span: Span,
 }
 
+/// `@flush`: send what has been rendered so far (streaming rendering)
+#[derive(Debug, Clone)]
+pub struct FlushBlock {
+    pub span: Span,
+}
+
+/// `@suspense(fallback) { ... }`: rendered once its `@let ... .await` resolve
+#[derive(Debug, Clone)]
+pub struct SuspenseBlock {
+    pub fallback: TokenStream,
+    pub body: Vec<Node>,
+    pub span: Span,
+}
+
+/// `@slot name { ... }`: content for a named slot, inside a component call
+#[derive(Debug, Clone)]
+pub struct SlotBlock {
+    pub name: syn::Ident,
+    pub body: Vec<Node>,
+    pub span: Span,
+}
+
+/// `@provide(value) { ... }`: `value` is available to `use_context` in the block
+#[derive(Debug, Clone)]
+pub struct ProvideBlock {
+    pub value: TokenStream,
+    pub body: Vec<Node>,
+    pub span: Span,
+}
+
+/// `@try { ... } @catch(err) { ... }`: the fallback replaces a failed body
+#[derive(Debug, Clone)]
+pub struct TryBlock {
+    pub body: Vec<Node>,
+    pub error: syn::Pat,
+    pub fallback: Vec<Node>,
+    pub span: Span,
+}
+
+/// `@cache(key = ..., ttl = ...) { ... }`: the body's HTML is reused under `key`
+#[derive(Debug, Clone)]
+pub struct CacheBlock {
+    pub key: TokenStream,
+    pub ttl: Option<TokenStream>,
+    pub body: Vec<Node>,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone)]
 pub struct LetBlock {
     pub pattern: TokenStream, // Variable pattern (e.g., `name`, `(x, y)`, etc.)
@@ -719,6 +773,133 @@ impl Parse for Block {
             Ok(Block::Match(input.parse()?))
         } else if lookahead.peek(Token![let]) {
             Ok(Block::Let(input.parse()?))
+        } else if input.peek(Token![try]) {
+            let keyword = input.parse::<Token![try]>()?;
+            let body;
+            syn::braced!(body in input);
+            let body = parse_nodes(&body)?;
+            let fork = input.fork();
+            if !(fork.parse::<Token![@]>().is_ok() && peek_ident(&fork, "catch")) {
+                return Err(syn::Error::new(
+                    keyword.span,
+                    "@try needs a fallback: @try { ... } @catch(err) { ... }",
+                ));
+            }
+            input.parse::<Token![@]>()?;
+            input.parse::<syn::Ident>()?;
+            let error;
+            syn::parenthesized!(error in input);
+            let error = syn::Pat::parse_single(&error)?;
+            let fallback;
+            syn::braced!(fallback in input);
+            Ok(Block::Try(TryBlock {
+                body,
+                error,
+                fallback: parse_nodes(&fallback)?,
+                span: keyword.span,
+            }))
+        } else if input.peek(syn::Ident) && input.peek2(Paren) && peek_ident(input, "catch") {
+            Err(syn::Error::new(
+                input.span(),
+                "@catch must follow a @try block: @try { ... } @catch(err) { ... }",
+            ))
+        } else if input.peek(syn::Ident) && input.peek2(Paren) && peek_ident(input, "suspense") {
+            let keyword: syn::Ident = input.parse()?;
+            let fallback;
+            syn::parenthesized!(fallback in input);
+            let fallback: TokenStream = fallback.parse()?;
+            if !input.peek(Brace) {
+                return Err(syn::Error::new(
+                    keyword.span(),
+                    "@suspense(fallback) needs a body: @suspense(fallback) { ... }",
+                ));
+            }
+            let body;
+            syn::braced!(body in input);
+            Ok(Block::Suspense(SuspenseBlock {
+                fallback,
+                body: parse_nodes(&body)?,
+                span: keyword.span(),
+            }))
+        } else if input.peek(syn::Ident) && input.peek2(Paren) && peek_ident(input, "cache") {
+            let keyword: syn::Ident = input.parse()?;
+            let args;
+            syn::parenthesized!(args in input);
+            let args =
+                syn::punctuated::Punctuated::<syn::MetaNameValue, Token![,]>::parse_terminated(
+                    &args,
+                )?;
+            let mut key = None;
+            let mut ttl = None;
+            for arg in args {
+                if arg.path.is_ident("key") {
+                    key = Some(quote::ToTokens::into_token_stream(arg.value));
+                } else if arg.path.is_ident("ttl") {
+                    ttl = Some(quote::ToTokens::into_token_stream(arg.value));
+                } else {
+                    return Err(syn::Error::new_spanned(
+                        arg.path,
+                        "Unknown @cache argument. Supported: key, ttl",
+                    ));
+                }
+            }
+            let Some(key) = key else {
+                return Err(syn::Error::new(
+                    keyword.span(),
+                    "@cache needs a key: @cache(key = ..., ttl = ...) { ... }",
+                ));
+            };
+            if !input.peek(Brace) {
+                return Err(syn::Error::new(
+                    keyword.span(),
+                    "@cache(key = ...) needs a body: @cache(key = ...) { ... }",
+                ));
+            }
+            let body;
+            syn::braced!(body in input);
+            Ok(Block::Cache(CacheBlock {
+                key,
+                ttl,
+                body: parse_nodes(&body)?,
+                span: keyword.span(),
+            }))
+        } else if input.peek(syn::Ident) && input.peek2(Paren) && peek_ident(input, "provide") {
+            let keyword: syn::Ident = input.parse()?;
+            let value;
+            syn::parenthesized!(value in input);
+            let value: TokenStream = value.parse()?;
+            if !input.peek(Brace) {
+                return Err(syn::Error::new(
+                    keyword.span(),
+                    "@provide(value) needs a body: @provide(value) { ... }",
+                ));
+            }
+            let body;
+            syn::braced!(body in input);
+            Ok(Block::Provide(ProvideBlock {
+                value,
+                body: parse_nodes(&body)?,
+                span: keyword.span(),
+            }))
+        } else if input.peek(syn::Ident) && input.peek2(syn::Ident) && peek_ident(input, "slot") {
+            let keyword: syn::Ident = input.parse()?;
+            let name: syn::Ident = input.parse()?;
+            if !input.peek(Brace) {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("@slot {} needs a body: @slot {} {{ ... }}", name, name),
+                ));
+            }
+            let body;
+            syn::braced!(body in input);
+            Ok(Block::Slot(SlotBlock {
+                name,
+                body: parse_nodes(&body)?,
+                span: keyword.span(),
+            }))
+        } else if let Some(span) = peek_keyword(input, "flush") {
+            input.parse::<syn::Ident>()?;
+            Ok(Block::Flush(FlushBlock { span }))
         } else {
             // Component or Call
             // Check if it's a path
@@ -772,6 +953,21 @@ impl Parse for Block {
 
 // Helpers
 
+fn peek_ident(input: ParseStream, keyword: &str) -> bool {
+    input.fork().parse::<syn::Ident>().is_ok_and(|ident| ident == keyword)
+}
+
+/// Span of a bare `@keyword` (not `@keyword(...)`, `@keyword { ... }` or a
+/// path starting with it, which stay component calls)
+fn peek_keyword(input: ParseStream, keyword: &str) -> Option<Span> {
+    let fork = input.fork();
+    let ident = fork.parse::<syn::Ident>().ok()?;
+    if ident != keyword || fork.peek(Paren) || fork.peek(Brace) || fork.peek(Token![::]) {
+        return None;
+    }
+    Some(ident.span())
+}
+
 fn parse_html_name(input: ParseStream, allow_double_dash: bool) -> Result<(String, Span)> {
     let mut name = String::new();
     let mut full_span = input.span();
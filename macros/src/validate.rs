@@ -0,0 +1,263 @@
+//! `#[derive(azumi::Validate)]`
+//!
+//! Generates `azumi::validate::Validate` from `#[validate(...)]` field rules:
+//! runtime checks producing `FieldErrors`, and the HTML5 constraint
+//! attributes `html!` adds to inputs of `<form bind={Struct}>`.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+#[derive(Default)]
+struct FieldRules {
+    required: bool,
+    email: bool,
+    nested: bool,
+    min_length: Option<syn::LitInt>,
+    max_length: Option<syn::LitInt>,
+    min: Option<syn::Expr>,
+    max: Option<syn::Expr>,
+    regex: Option<syn::LitStr>,
+    custom: Vec<syn::Path>,
+}
+
+/// `min = a, max = b` inside `length(...)` / `range(...)`
+fn parse_bounds<T: syn::parse::Parse>(
+    meta: &syn::meta::ParseNestedMeta,
+    rule: &str,
+) -> syn::Result<(Option<T>, Option<T>)> {
+    let mut min = None;
+    let mut max = None;
+    meta.parse_nested_meta(|bound| {
+        if bound.path.is_ident("min") {
+            min = Some(bound.value()?.parse()?);
+            Ok(())
+        } else if bound.path.is_ident("max") {
+            max = Some(bound.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(bound.error(format!("Unknown {} bound. Expected: min, max", rule)))
+        }
+    })?;
+    if min.is_none() && max.is_none() {
+        return Err(meta.error(format!("{}(...) needs `min` and/or `max`", rule)));
+    }
+    Ok((min, max))
+}
+
+fn parse_rules(field: &syn::Field) -> syn::Result<FieldRules> {
+    let mut rules = FieldRules::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("validate")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("required") {
+                rules.required = true;
+            } else if meta.path.is_ident("email") {
+                rules.email = true;
+            } else if meta.path.is_ident("nested") {
+                rules.nested = true;
+            } else if meta.path.is_ident("length") {
+                (rules.min_length, rules.max_length) = parse_bounds(&meta, "length")?;
+            } else if meta.path.is_ident("range") {
+                (rules.min, rules.max) = parse_bounds(&meta, "range")?;
+            } else if meta.path.is_ident("regex") {
+                let lit: syn::LitStr = meta.value()?.parse()?;
+                if let Err(e) = regex::Regex::new(&lit.value()) {
+                    return Err(syn::Error::new_spanned(&lit, format!("Invalid regex: {}", e)));
+                }
+                rules.regex = Some(lit);
+            } else if meta.path.is_ident("custom") {
+                let value = meta.value()?;
+                let path = if value.peek(syn::LitStr) {
+                    value.parse::<syn::LitStr>()?.parse()?
+                } else {
+                    value.parse()?
+                };
+                rules.custom.push(path);
+            } else {
+                return Err(meta.error(
+                    "Unknown validation rule. Expected: required, length(..), range(..), email, regex = \"..\", custom = fn, nested",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(rules)
+}
+
+/// Literal text of a bound for HTML attributes (`- 5` -> `-5`)
+fn bound_text(expr: &syn::Expr) -> String {
+    quote!(#expr).to_string().replace(' ', "")
+}
+
+pub fn derive_validate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let Data::Struct(data) = &input.data else {
+        return syn::Error::new_spanned(&input, "#[derive(Validate)] only supports structs")
+            .to_compile_error()
+            .into();
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return syn::Error::new_spanned(
+            &input,
+            "#[derive(Validate)] only supports structs with named fields",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let mut checks = Vec::new();
+    let mut constraint_arms = Vec::new();
+
+    for field in &fields.named {
+        let rules = match parse_rules(field) {
+            Ok(rules) => rules,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let Some(ident) = &field.ident else { continue };
+        let ty = &field.ty;
+        let field_name = ident.to_string();
+
+        // Rules that run on a present value
+        let mut value_checks = Vec::new();
+        let mut constraints = Vec::new();
+
+        if rules.required {
+            constraints.push(quote! { ("required", None) });
+        }
+        if let Some(min) = &rules.min_length {
+            let message = format!("Length must be at least {}", min.base10_digits());
+            value_checks.push(quote! {
+                if let Some(len) = azumi::validate::ValidateValue::length(&self.#ident) {
+                    if len > 0 && len < #min {
+                        errors.add(#field_name, "length", #message);
+                    }
+                }
+            });
+            let text = min.base10_digits().to_string();
+            constraints.push(quote! { ("minlength", Some(#text)) });
+        }
+        if let Some(max) = &rules.max_length {
+            let message = format!("Length must be at most {}", max.base10_digits());
+            value_checks.push(quote! {
+                if let Some(len) = azumi::validate::ValidateValue::length(&self.#ident) {
+                    if len > #max {
+                        errors.add(#field_name, "length", #message);
+                    }
+                }
+            });
+            let text = max.base10_digits().to_string();
+            constraints.push(quote! { ("maxlength", Some(#text)) });
+        }
+        if let Some(min) = &rules.min {
+            let text = bound_text(min);
+            let message = format!("Must be at least {}", text);
+            value_checks.push(quote! {
+                if let Some(value) = azumi::validate::ValidateRange::range_value(&self.#ident) {
+                    if value < (#min) as f64 {
+                        errors.add(#field_name, "range", #message);
+                    }
+                }
+            });
+            constraints.push(quote! { ("min", Some(#text)) });
+        }
+        if let Some(max) = &rules.max {
+            let text = bound_text(max);
+            let message = format!("Must be at most {}", text);
+            value_checks.push(quote! {
+                if let Some(value) = azumi::validate::ValidateRange::range_value(&self.#ident) {
+                    if value > (#max) as f64 {
+                        errors.add(#field_name, "range", #message);
+                    }
+                }
+            });
+            constraints.push(quote! { ("max", Some(#text)) });
+        }
+        if rules.email {
+            value_checks.push(quote! {
+                if let Some(text) = azumi::validate::ValidateValue::text(&self.#ident) {
+                    if !text.is_empty() && !azumi::validate::is_email(text) {
+                        errors.add(#field_name, "email", "Must be a valid email address");
+                    }
+                }
+            });
+            constraints.push(quote! { ("type", Some("email")) });
+        }
+        if let Some(pattern) = &rules.regex {
+            // Anchored like the HTML `pattern` attribute
+            let anchored = format!("^(?:{})$", pattern.value());
+            value_checks.push(quote! {
+                if let Some(text) = azumi::validate::ValidateValue::text(&self.#ident) {
+                    static RE: std::sync::OnceLock<azumi::validate::Regex> = std::sync::OnceLock::new();
+                    let re = RE.get_or_init(|| {
+                        azumi::validate::Regex::new(#anchored).expect("regex checked at compile time")
+                    });
+                    if !text.is_empty() && !re.is_match(text) {
+                        errors.add(#field_name, "regex", "Invalid format");
+                    }
+                }
+            });
+            constraints.push(quote! { ("pattern", Some(#pattern)) });
+        }
+        for custom in &rules.custom {
+            value_checks.push(quote! {
+                if let Err(message) = #custom(&self.#ident) {
+                    errors.add(#field_name, "custom", message);
+                }
+            });
+        }
+        if rules.nested {
+            value_checks.push(quote! {
+                if let Err(nested) = azumi::validate::Validate::validate(&self.#ident) {
+                    errors.merge_nested(#field_name, nested);
+                }
+            });
+            let prefix = format!("{}.", field_name);
+            constraint_arms.push(quote! {
+                f if f.starts_with(#prefix) => {
+                    <#ty as azumi::validate::Validate>::constraints(&f[#prefix.len()..])
+                }
+            });
+        }
+
+        // A missing required value reports only "required"
+        if rules.required {
+            checks.push(quote! {
+                if !azumi::validate::ValidateRequired::is_present(&self.#ident) {
+                    errors.add(#field_name, "required", "This field is required");
+                } else {
+                    #(#value_checks)*
+                }
+            });
+        } else {
+            checks.extend(value_checks);
+        }
+
+        if !constraints.is_empty() {
+            constraint_arms.push(quote! {
+                #field_name => &[#(#constraints),*],
+            });
+        }
+    }
+
+    let expanded = quote! {
+        impl #impl_generics azumi::validate::Validate for #name #ty_generics #where_clause {
+            fn validate(&self) -> Result<(), azumi::validate::FieldErrors> {
+                let mut errors = azumi::validate::FieldErrors::new();
+                #(#checks)*
+                errors.into_result()
+            }
+
+            fn constraints(field: &str) -> &'static [(&'static str, Option<&'static str>)] {
+                match field {
+                    #(#constraint_arms)*
+                    _ => &[],
+                }
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
@@ -1,21 +1,86 @@
 //! # Azumi Actions Module
 //!
-//! ## CSRF Protection for Actions
+//! ## Payloads
+//!
+//! The last argument of an `#[azumi::action]` function is the payload. How the
+//! request body is decoded follows the payload type, or an explicit mode:
+//!
+//! | Payload | Body |
+//! |---------|------|
+//! | `T` / `Json<T>` / `#[azumi::action(json)]` | `application/json` (default) |
+//! | `Form<T>` / `#[azumi::action(form)] fn f(p: T)` | `application/x-www-form-urlencoded` |
+//! | `Multipart` | `multipart/form-data`, streamed field by field |
+//!
+//! `#[azumi::action(validate)]` runs the payload's
+//! [`crate::validate::Validate`] rules first and answers `422` with the
+//! [`crate::validate::FieldErrors`] when they fail.
+//!
+//! Form payloads let a plain `<form method="post" action="/_azumi/action/name">`
+//! work without JavaScript. Multipart payloads receive uploads without a
+//! hand-written handler:
+//!
+//! ```rust,ignore
+//! #[azumi::action]
+//! pub async fn upload(mut files: azumi::action::Multipart) -> impl Component {
+//!     let mut names = Vec::new();
+//!     while let Ok(Some(field)) = files.next_field().await {
+//!         names.push(field.file_name().unwrap_or("blob").to_string());
+//!     }
+//!     html! { <p>{names.join(", ")}</p> }
+//! }
+//! ```
+//!
+//! ## Responses
 //!
-//! `#[azumi::action]` handlers are plain JSON API endpoints. CSRF protection
-//! is NOT built into Azumi because:
+//! An action returns a component, rendered as HTML into its target, or an
+//! [`ActionResponse`]: [`crate::fragments::Fragments`] to update several
+//! targets at once, or a [`Reply`] ([`redirect`], [`refresh`], [`flash`]) to
+//! navigate or show messages.
 //!
-//! 1. **Azumi doesn't own auth** - Authentication is application responsibility
-//! 2. **Actions are JSON APIs** - They're designed to be called from the JS client
+//! ## Guards and Layers
 //!
-//! ### CSRF Protection Options
+//! Authorization and middleware are declared on the action itself:
+//!
+//! ```rust,ignore
+//! async fn require_admin(parts: &Parts) -> Result<(), StatusCode> {
+//!     match parts.headers.get("x-role") {
+//!         Some(role) if role == "admin" => Ok(()),
+//!         _ => Err(StatusCode::FORBIDDEN),
+//!     }
+//! }
+//!
+//! #[azumi::action(guard = require_admin, layer = TimeoutLayer::new(Duration::from_secs(5)))]
+//! pub async fn delete_user(data: DeleteUser) -> impl Component { ... }
+//! ```
+//!
+//! Live methods take the same arguments through `#[azumi::route(..)]`:
+//!
+//! ```rust,ignore
+//! #[azumi::live_impl]
+//! impl Admin {
+//!     #[azumi::route(guard = require_admin)]
+//!     pub fn purge(&mut self) { ... }
+//! }
+//! ```
+//!
+//! A guard is an async function over the request [`Parts`] returning
+//! `Result<(), E: IntoResponse>` (see [`GuardResult`]); an `Err` is sent as
+//! the response and the handler never runs. Guards run in the order listed,
+//! before the body is read. `layer = expr` applies any tower layer to the
+//! route; layers are listed outermost first and wrap the guards.
+//! `rate_limit(N, per_minute)` adds a token-bucket limit (see
+//! [`crate::rate_limit`]).
+//!
+//! ## CSRF Protection for Actions
 //!
-//! For applications using cookie-based authentication, add CSRF protection at the
-//! Axum middleware layer (not in Azumi). Common approaches:
+//! `#[azumi::action]` handlers are plain endpoints and are not protected by
+//! default. Applications using cookie-based authentication should opt in to
+//! [`crate::csrf::CsrfLayer`] (double-submit token or Origin /
+//! `Sec-Fetch-Site` verification), e.g. through [`register_actions_with_csrf`].
+//! Pages render the token with [`crate::csrf::csrf_meta`]; the client runtime
+//! attaches it to every action request.
 //!
-//! 1. **SameSite cookies** - Set `SameSite=Strict` or `SameSite=Lax` on session cookies
-//! 2. **Double Submit Cookie** - Check a CSRF token in both cookie and header
-//! 3. **Custom Axum middleware** - Verify Origin/Referer headers
+//! `SameSite=Strict`/`Lax` session cookies remain a good complement.
 //!
 //! ### LiveView State Protection
 //!
@@ -24,11 +89,150 @@
 //! cannot forge valid state without the secret key.
 
 use crate::Component;
-use axum::response::IntoResponse;
+use axum::response::{IntoResponse, Response};
 use axum::routing::get;
 
 use std::future::Future;
 
+pub use axum::extract::{Form, Json, Multipart};
+pub use axum::http::request::Parts;
+
+/// Outcome of a route guard (`guard = f` in `#[azumi::action]` / `#[azumi::route]`)
+pub trait GuardResult {
+    #[allow(clippy::result_large_err)]
+    fn into_guard_result(self) -> Result<(), Response>;
+}
+
+impl<E: IntoResponse> GuardResult for Result<(), E> {
+    fn into_guard_result(self) -> Result<(), Response> {
+        self.map_err(IntoResponse::into_response)
+    }
+}
+
+/// Responses an action may return instead of a component (e.g.
+/// [`crate::fragments::Fragments`]). Components are rendered as HTML.
+pub trait ActionResponse {
+    fn into_action_response(self) -> Response;
+}
+
+/// Used by `#[azumi::action]` to turn the function output into a response
+pub struct ActionOutput<T>(pub T);
+
+impl<T: ActionResponse> ActionOutput<T> {
+    // Takes precedence over RenderActionOutput when T: ActionResponse
+    pub fn into_response(self) -> Response {
+        self.0.into_action_response()
+    }
+}
+
+pub trait RenderActionOutput {
+    fn into_response(self) -> Response;
+}
+
+impl<T: Component> RenderActionOutput for ActionOutput<T> {
+    fn into_response(self) -> Response {
+        crate::Html::new(self.0).into_response()
+    }
+}
+
+/// Response header telling the client runtime to navigate to its value
+pub const REDIRECT_HEADER: &str = "x-azumi-redirect";
+/// Response header telling the client runtime to reload the page
+pub const REFRESH_HEADER: &str = "x-azumi-refresh";
+/// Request header the client runtime sends with every action request
+pub const CLIENT_HEADER: &str = "x-azumi-request";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Navigation {
+    Redirect(String),
+    Refresh,
+}
+
+/// What the client does after an action instead of swapping HTML: navigate
+/// ([`redirect`]), reload ([`refresh`]) and/or show [`flash`] messages
+/// (see [`crate::flash`]).
+#[derive(Clone, Debug, Default)]
+pub struct Reply {
+    navigation: Option<Navigation>,
+    flashes: Vec<crate::flash::Flash>,
+}
+
+/// Navigate to `url`. The client runtime follows the [`REDIRECT_HEADER`];
+/// forms posted without JavaScript get `303 See Other` with `Location` from
+/// the routes of [`register_actions`] (a `<meta http-equiv="refresh">` page
+/// elsewhere).
+pub fn redirect(url: impl Into<String>) -> Reply {
+    Reply {
+        navigation: Some(Navigation::Redirect(url.into())),
+        flashes: Vec::new(),
+    }
+}
+
+/// Reload the current page
+pub fn refresh() -> Reply {
+    Reply {
+        navigation: Some(Navigation::Refresh),
+        flashes: Vec::new(),
+    }
+}
+
+/// Show a message without navigating
+pub fn flash(level: crate::flash::Level, message: impl Into<String>) -> Reply {
+    Reply::default().flash(level, message)
+}
+
+impl Reply {
+    /// Add a message, shown after the navigation if there is one
+    pub fn flash(mut self, level: crate::flash::Level, message: impl Into<String>) -> Self {
+        self.flashes.push(crate::flash::Flash::new(level, message));
+        self
+    }
+}
+
+impl IntoResponse for Reply {
+    fn into_response(self) -> Response {
+        let mut response = match &self.navigation {
+            Some(Navigation::Redirect(url)) => axum::response::Html(format!(
+                "<meta http-equiv=\"refresh\" content=\"0; url={}\">",
+                crate::Escaped(url)
+            ))
+            .into_response(),
+            _ => axum::response::Html("").into_response(),
+        };
+        let headers = response.headers_mut();
+        match &self.navigation {
+            Some(Navigation::Redirect(url)) => match axum::http::HeaderValue::from_str(url) {
+                Ok(value) => {
+                    headers.insert(REDIRECT_HEADER, value);
+                }
+                Err(_) => eprintln!("⚠️  Azumi: invalid redirect URL {:?}", url),
+            },
+            Some(Navigation::Refresh) => {
+                headers.insert(REFRESH_HEADER, axum::http::HeaderValue::from_static("true"));
+            }
+            None => {}
+        }
+        if !self.flashes.is_empty() {
+            let flashes = if self.navigation.is_some() {
+                crate::flash::store_cookie(&self.flashes).map(|v| (axum::http::header::SET_COOKIE, v))
+            } else {
+                crate::flash::header_value(&self.flashes)
+                    .map(|v| (axum::http::HeaderName::from_static(crate::flash::FLASH_HEADER), v))
+            };
+            if let Some((name, value)) = flashes {
+                headers.append(name, value);
+            }
+        }
+        response
+    }
+}
+
+impl ActionResponse for Reply {
+    fn into_action_response(self) -> Response {
+        self.into_response()
+    }
+}
+
 /// Trait for Azumi Actions
 /// This is implemented automatically by the `#[azumi::action]` macro
 #[allow(dead_code)]
@@ -38,32 +242,331 @@ pub trait Action<Input, Output> {
 
 use axum::routing::MethodRouter;
 
-/// Registry entry for an action
+/// What generated (or registered) a route
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionKind {
+    /// `#[azumi::action]` function
+    Action,
+    /// Method of a `#[azumi::live_impl]` block
+    LiveMethod,
+    /// `#[azumi::on_emit(Child)]` method
+    LiveEmit,
+    /// `undo` / `redo` of `#[azumi::live(history = N)]`
+    LiveHistory,
+    /// `mount = "/path"` GET route of a live component
+    LiveMount,
+    /// Registered by hand with [`ActionEntry::new`]
+    Custom,
+}
+
+impl ActionKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ActionKind::Action => "action",
+            ActionKind::LiveMethod => "live_method",
+            ActionKind::LiveEmit => "live_emit",
+            ActionKind::LiveHistory => "live_history",
+            ActionKind::LiveMount => "live_mount",
+            ActionKind::Custom => "custom",
+        }
+    }
+}
+
+/// Registry entry for an action.
+///
+/// Submitted through `inventory` by the macros; the metadata feeds
+/// [`registry`] and [`crate::openapi`]. Built with [`ActionEntry::new`] and
+/// the `const` setters below, so new metadata doesn't break registrations:
+///
+/// ```rust,ignore
+/// azumi::inventory::submit! {
+///     azumi::action::ActionEntry::new("/report", report_router)
+///         .http_method("POST")
+///         .location(file!(), line!())
+/// }
+/// ```
+#[non_exhaustive]
 pub struct ActionEntry {
     pub path: &'static str,
     pub handler: fn() -> MethodRouter<()>,
+    pub kind: ActionKind,
+    /// `POST` for actions and live methods, `GET` for mount routes
+    pub http_method: &'static str,
+    /// Function or method name
+    pub name: &'static str,
+    /// Live struct the route belongs to
+    pub live_struct: Option<&'static str>,
+    /// Payload type as written in the source (`Signup`, `Form<Signup>`)
+    pub payload_type: Option<&'static str>,
+    /// Request body content type
+    pub content_type: Option<&'static str>,
+    /// Return type as written in the source
+    pub response_type: Option<&'static str>,
+    /// Response content type
+    pub response_content_type: &'static str,
+    /// JSON schema of the payload, when it implements
+    /// [`crate::openapi::ApiSchema`]
+    pub payload_schema: fn(&mut crate::openapi::Components) -> Option<serde_json::Value>,
+    /// Source location of the declaration
+    pub file: &'static str,
+    pub line: u32,
+}
+
+impl ActionEntry {
+    /// Entry for a hand-registered route, without metadata (listed as a
+    /// `GET` route named after its path)
+    pub const fn new(path: &'static str, handler: fn() -> MethodRouter<()>) -> Self {
+        Self {
+            path,
+            handler,
+            kind: ActionKind::Custom,
+            http_method: "GET",
+            name: path,
+            live_struct: None,
+            payload_type: None,
+            content_type: None,
+            response_type: None,
+            response_content_type: "text/html",
+            payload_schema: |_| None,
+            file: "",
+            line: 0,
+        }
+    }
+
+    pub const fn kind(mut self, kind: ActionKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub const fn http_method(mut self, http_method: &'static str) -> Self {
+        self.http_method = http_method;
+        self
+    }
+
+    pub const fn name(mut self, name: &'static str) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub const fn live_struct(mut self, live_struct: &'static str) -> Self {
+        self.live_struct = Some(live_struct);
+        self
+    }
+
+    pub const fn payload_type(mut self, payload_type: &'static str) -> Self {
+        self.payload_type = Some(payload_type);
+        self
+    }
+
+    pub const fn content_type(mut self, content_type: &'static str) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    pub const fn response_type(mut self, response_type: &'static str) -> Self {
+        self.response_type = Some(response_type);
+        self
+    }
+
+    pub const fn response_content_type(mut self, response_content_type: &'static str) -> Self {
+        self.response_content_type = response_content_type;
+        self
+    }
+
+    pub const fn payload_schema(
+        mut self,
+        payload_schema: fn(&mut crate::openapi::Components) -> Option<serde_json::Value>,
+    ) -> Self {
+        self.payload_schema = payload_schema;
+        self
+    }
+
+    /// Source location of the declaration (`file!()`, `line!()`)
+    pub const fn location(mut self, file: &'static str, line: u32) -> Self {
+        self.file = file;
+        self.line = line;
+        self
+    }
 }
 
 inventory::collect!(ActionEntry);
 
+/// All registered routes, sorted by path
+pub fn registry() -> Vec<&'static ActionEntry> {
+    let mut entries: Vec<_> = inventory::iter::<ActionEntry>.into_iter().collect();
+    entries.sort_by(|a, b| a.path.cmp(b.path).then(a.http_method.cmp(b.http_method)));
+    entries
+}
+
 /// Register all collected actions into the router.
-/// Also registers the `/azumi.js` route to serve the client runtime.
-pub fn register_actions(mut router: axum::Router) -> axum::Router {
-    for entry in inventory::iter::<ActionEntry> {
-        router = router.route(entry.path, (entry.handler)());
+/// Also registers the `/azumi.js` route to serve the client runtime and the
+/// `/_azumi/telemetry` route that receives prediction mismatch reports
+/// (see [`crate::telemetry`]).
+///
+/// Equivalent to `azumi::routes().register(router)`; use [`routes`] to mount
+/// the Azumi routes under a prefix.
+pub fn register_actions(router: axum::Router) -> axum::Router {
+    routes().register(router)
+}
+
+/// [`register_actions`] with a CSRF layer installed on the resulting router.
+///
+/// The layer checks unsafe requests to `/_azumi/action/*` and, in
+/// double-submit mode, issues the token cookie on every other response of
+/// the router (the pages rendering [`crate::csrf::csrf_meta`]).
+pub fn register_actions_with_csrf(router: axum::Router, csrf: crate::csrf::CsrfLayer) -> axum::Router {
+    routes().csrf(csrf).register(router)
+}
+
+/// Builder for the routes Azumi serves: actions, live methods, the client
+/// script, telemetry and (with the `devtools` feature) hot reload.
+///
+/// ```rust,ignore
+/// let app = azumi::routes()
+///     .prefix("/app")
+///     .csrf(CsrfLayer::double_submit())
+///     .register(Router::new().route("/app", get(home)));
+/// // POST /app/_azumi/action/Counter/increment, GET /app/azumi.js, ...
+/// ```
+///
+/// The prefix is injected into the served `{prefix}/azumi.js` so that the
+/// URLs the client runtime builds match. Pages inlining the runtime render
+/// [`AzumiRoutes::script`] (or [`crate::azumi_script_at`]) instead of
+/// [`crate::azumi_script`], which assumes the root. Routes registered with [`ActionEntry::new`] are prefixed
+/// too; mount URLs already come from the request.
+#[derive(Clone, Default)]
+pub struct AzumiRoutes {
+    prefix: String,
+    csrf: Option<crate::csrf::CsrfLayer>,
+    #[cfg(feature = "devtools")]
+    devtools: bool,
+}
+
+/// Start an [`AzumiRoutes`] builder (no prefix)
+pub fn routes() -> AzumiRoutes {
+    AzumiRoutes::default()
+}
+
+impl AzumiRoutes {
+    /// Mount everything under `prefix` (`"/app"`, `"app/"` and `"/app/"`
+    /// are equivalent; `""` and `"/"` mean the root)
+    pub fn prefix(mut self, prefix: impl AsRef<str>) -> Self {
+        let trimmed = prefix.as_ref().trim_matches('/');
+        self.prefix = if trimmed.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", trimmed)
+        };
+        self
+    }
+
+    /// Install a CSRF layer; its protected path prefix is moved under the
+    /// mount prefix
+    pub fn csrf(mut self, layer: crate::csrf::CsrfLayer) -> Self {
+        self.csrf = Some(layer);
+        self
+    }
+
+    /// Also mount the hot reload endpoints (see [`crate::hot_reload::router`]).
+    /// The file watcher of [`crate::devtools::auto_reload`] reads the prefix
+    /// from the `AZUMI_PREFIX` environment variable.
+    #[cfg(feature = "devtools")]
+    pub fn devtools(mut self) -> Self {
+        self.devtools = true;
+        self
+    }
+
+    /// The normalized prefix (`""` or `/segment...`)
+    pub fn mount_prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// The inline client runtime for pages served with these routes (same
+    /// as [`crate::azumi_script_at`] with the mount prefix)
+    pub fn script(&self) -> crate::AzumiScript {
+        crate::azumi_script_at(&self.prefix)
+    }
+
+    /// Add the Azumi routes to `router`
+    pub fn register(self, mut router: axum::Router) -> axum::Router {
+        let at = |path: &str| format!("{}{}", self.prefix, path);
+
+        for entry in inventory::iter::<ActionEntry> {
+            let route = (entry.handler)().layer(axum::middleware::from_fn(see_other));
+            router = router.route(&at(entry.path), route);
+        }
+        let script = axum::body::Bytes::from(crate::script::client_js(&self.prefix));
+        router = router
+            .route(
+                &at("/azumi.js"),
+                get(move || {
+                    let script = script.clone();
+                    async move {
+                        (
+                            [(axum::http::header::CONTENT_TYPE, "application/javascript")],
+                            script,
+                        )
+                    }
+                }),
+            )
+            .route(
+                &at("/_azumi/telemetry"),
+                axum::routing::post(crate::telemetry::telemetry_handler),
+            );
+
+        #[cfg(feature = "devtools")]
+        if self.devtools {
+            router = router.merge(crate::hot_reload::router_at(&self.prefix));
+        }
+
+        match self.csrf {
+            Some(csrf) => router.layer(csrf.under_prefix(&self.prefix)),
+            None => router,
+        }
+    }
+
+    /// A new router with only the Azumi routes
+    pub fn build(self) -> axum::Router {
+        self.register(axum::Router::new())
+    }
+}
+
+/// Turns a [`redirect`] answering a request not sent by the client runtime
+/// (a plain `<form>` post) into `303 See Other`, so that reloading the
+/// target page or going back does not submit the form again
+async fn see_other(request: axum::extract::Request, next: axum::middleware::Next) -> Response {
+    let from_client = request.headers().contains_key(CLIENT_HEADER);
+    let mut response = next.run(request).await;
+    if from_client {
+        return response;
+    }
+    if let Some(location) = response.headers_mut().remove(REDIRECT_HEADER) {
+        *response.status_mut() = axum::http::StatusCode::SEE_OTHER;
+        *response.body_mut() = axum::body::Body::empty();
+        let headers = response.headers_mut();
+        headers.remove(axum::http::header::CONTENT_TYPE);
+        headers.remove(axum::http::header::CONTENT_LENGTH);
+        headers.insert(axum::http::header::LOCATION, location);
     }
-    router.route("/azumi.js", get(azumi_js_handler))
+    response
 }
 
-/// Handler that serves the embedded Azumi client JavaScript
-async fn azumi_js_handler() -> impl IntoResponse {
-    (
-        [(axum::http::header::CONTENT_TYPE, "application/javascript")],
-        crate::AZUMI_JS,
-    )
+/// Paths (axum syntax) of the routes [`AzumiRoutes::register`] adds under
+/// `prefix`, apart from the hot reload endpoints
+#[cfg(feature = "actix-web")]
+pub(crate) fn served_paths(prefix: &str) -> Vec<String> {
+    let mut paths: Vec<String> = inventory::iter::<ActionEntry>
+        .into_iter()
+        .map(|entry| format!("{}{}", prefix, entry.path))
+        .collect();
+    paths.push(format!("{}/azumi.js", prefix));
+    paths.push(format!("{}/_azumi/telemetry", prefix));
+    paths.sort();
+    paths.dedup();
+    paths
 }
 
 /// Helper to wrap an action result into an Axum response with correct Content-Type
 pub async fn handle_action_result<C: Component + ?Sized>(component: &C) -> impl IntoResponse {
-    axum::response::Html(crate::render_to_string(component))
+    crate::Html::new(component).into_response()
 }
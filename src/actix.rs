@@ -0,0 +1,196 @@
+//! # actix-web
+//!
+//! Mount the Azumi routes (actions, live components, `/azumi.js`,
+//! telemetry) in an actix-web app:
+//!
+//! ```rust,ignore
+//! use actix_web::{web, App, HttpServer};
+//!
+//! async fn home() -> azumi::Html<impl azumi::Component> {
+//!     azumi::Html::new(home_page())
+//! }
+//!
+//! HttpServer::new(|| {
+//!     App::new()
+//!         .configure(azumi::actix::register_actions)
+//!         .route("/", web::get().to(home))
+//! })
+//! ```
+//!
+//! This is a bridge to the axum integration, not a separate one: the macros
+//! generate axum handlers, the routes are the axum router built by
+//! [`crate::routes`], and each Azumi path is registered with actix and
+//! forwarded to it. The feature therefore compiles axum, tokio and tower
+//! too. In exchange, handlers written for axum (extractors, `Extension`s, tower layers such as
+//! [`crate::csrf::CsrfLayer`]) keep working unchanged. Configure them on the
+//! router and pass it to [`mount`] with its prefix:
+//!
+//! ```rust,ignore
+//! let routes = azumi::routes()
+//!     .prefix("/app")
+//!     .csrf(azumi::csrf::CsrfLayer::origin());
+//! let router = routes.clone().build().layer(azumi::axum::Extension(db.clone()));
+//! App::new().configure(azumi::actix::mount(routes.mount_prefix(), router))
+//! ```
+//!
+//! Notes:
+//!
+//! - Request bodies are read by actix first, so its `PayloadConfig` limit
+//!   (256 KiB by default) applies to actions and uploads.
+//! - Layers on the router only see Azumi requests. Use
+//!   [`CsrfLayer::origin`](crate::csrf::CsrfLayer::origin); the
+//!   double-submit cookie has to be set on page responses.
+//! - The hot reload endpoints (`devtools`) use a WebSocket and are not
+//!   forwarded.
+//!
+//! [`Html`](crate::Html) (and `html!` results) implement `Responder`, with
+//! the same status, headers, `ETag` and error handling as in axum.
+
+use crate::Component;
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse, HttpResponseBuilder, Responder};
+use axum::body::Body;
+
+/// Mount the routes of [`crate::routes`] (see the [module docs](self))
+pub fn register_actions(cfg: &mut web::ServiceConfig) {
+    mount("", crate::routes().build())(cfg);
+}
+
+/// Mount a router built with [`crate::AzumiRoutes::build`] under the prefix
+/// it was built with
+pub fn mount(prefix: &str, router: axum::Router) -> impl FnOnce(&mut web::ServiceConfig) {
+    let prefix = crate::routes().prefix(prefix).mount_prefix().to_string();
+    move |cfg| {
+        for path in crate::action::served_paths(&prefix) {
+            let router = router.clone();
+            cfg.route(
+                &actix_path(&path),
+                web::route().to(move |req: HttpRequest, body: web::Bytes| {
+                    forward(router.clone(), req, body)
+                }),
+            );
+        }
+    }
+}
+
+/// `/todos/:id/*rest` -> `/todos/{id}/{rest:.*}`
+fn actix_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            if let Some(name) = segment.strip_prefix(':') {
+                format!("{{{}}}", name)
+            } else if let Some(name) = segment.strip_prefix('*') {
+                format!("{{{}:.*}}", name)
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Run an actix request through the axum router
+async fn forward(mut router: axum::Router, req: HttpRequest, body: web::Bytes) -> HttpResponse {
+    let mut request = axum::http::Request::new(Body::from(body));
+    *request.method_mut() = match axum::http::Method::from_bytes(req.method().as_str().as_bytes()) {
+        Ok(method) => method,
+        Err(_) => return HttpResponse::MethodNotAllowed().finish(),
+    };
+    *request.uri_mut() = match req.uri().to_string().parse() {
+        Ok(uri) => uri,
+        Err(_) => return HttpResponse::BadRequest().finish(),
+    };
+    for (name, value) in req.headers() {
+        if let (Ok(name), Ok(value)) = (
+            axum::http::HeaderName::from_bytes(name.as_str().as_bytes()),
+            axum::http::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            request.headers_mut().append(name, value);
+        }
+    }
+    if let Some(addr) = req.peer_addr() {
+        // For `rate_limit(key = ip)`
+        request
+            .extensions_mut()
+            .insert(axum::extract::ConnectInfo(addr));
+    }
+
+    if let Err(infallible) = std::future::poll_fn(|cx| {
+        tower_service::Service::<axum::extract::Request>::poll_ready(&mut router, cx)
+    })
+    .await
+    {
+        match infallible {}
+    }
+    let response = match tower_service::Service::call(&mut router, request).await {
+        Ok(response) => response,
+        Err(infallible) => match infallible {},
+    };
+    let (parts, body) = response.into_parts();
+    let mut builder = builder(parts.status.as_u16(), &parts.headers);
+    if parts
+        .headers
+        .contains_key(axum::http::header::CONTENT_LENGTH)
+    {
+        match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => builder.body(bytes),
+            Err(_) => HttpResponse::InternalServerError().finish(),
+        }
+    } else {
+        // Streamed pages (`azumi::stream`) stay streamed
+        builder.streaming(body.into_data_stream())
+    }
+}
+
+/// Response builder with an axum status and headers. `Content-Length` is
+/// left to actix.
+fn builder(status: u16, headers: &axum::http::HeaderMap) -> HttpResponseBuilder {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut builder = HttpResponse::build(status);
+    for (name, value) in headers {
+        if name == axum::http::header::CONTENT_LENGTH {
+            continue;
+        }
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_str().as_bytes()),
+            HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            builder.append_header((name, value));
+        }
+    }
+    builder
+}
+
+impl<C: Component> Responder for crate::Html<C> {
+    type Body = actix_web::body::BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse {
+        match self.into_parts() {
+            Some((status, headers, body)) => builder(status.as_u16(), &headers).body(body),
+            None => HttpResponse::InternalServerError().finish(),
+        }
+    }
+}
+
+impl<F> Responder for crate::FnOnceComponent<F>
+where
+    F: FnOnce(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+{
+    type Body = actix_web::body::BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        crate::Html::new(self).respond_to(req)
+    }
+}
+
+impl<F> Responder for crate::FnComponent<F>
+where
+    F: Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+{
+    type Body = actix_web::body::BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse {
+        crate::Html::new(self).respond_to(req)
+    }
+}
@@ -0,0 +1,228 @@
+//! # Fragment Caching
+//!
+//! A `@cache` block renders its body once and reuses the HTML until the
+//! entry expires or is invalidated:
+//!
+//! ```rust,ignore
+//! html! {
+//!     @cache(key = format!("nav:{}", locale), ttl = Duration::from_secs(300)) {
+//!         <nav>@for item in load_menu(locale) { <a href={item.href}>{item.label}</a> }</nav>
+//!     }
+//! }
+//! ```
+//!
+//! The key must include everything the body depends on (user, locale,
+//! provided values...). Without `ttl` an entry lives until it is evicted or
+//! invalidated. Rendering is skipped entirely on a hit; a body that fails
+//! (see [`crate::error`]) is not cached.
+//!
+//! Entries are stored in the [`RenderCache`] set with [`init_cache`], by
+//! default a [`MemoryCache`] of [`DEFAULT_CAPACITY`] entries. Invalidate
+//! them by key prefix, e.g. after an update:
+//!
+//! ```rust,ignore
+//! azumi::cache::invalidate_prefix("nav:");
+//! ```
+//!
+//! `@suspense` blocks can't be cached and are rejected in `@cache`; `@flush`
+//! inside `@cache` has no effect.
+
+use crate::Component;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Storage for cached fragments: rendered HTML by key
+pub trait RenderCache: Send + Sync {
+    /// The HTML stored under `key`, unless missing or expired
+    fn get(&self, key: &str) -> Option<String>;
+
+    /// Store `html` under `key`, for `ttl` if given
+    fn insert(&self, key: &str, html: String, ttl: Option<Duration>);
+
+    /// Remove every entry whose key starts with `prefix`
+    fn invalidate_prefix(&self, prefix: &str);
+}
+
+/// Entries kept by the default cache
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+struct Entry {
+    html: String,
+    expires: Option<Instant>,
+    last_access: u64,
+}
+
+struct Entries {
+    map: HashMap<String, Entry>,
+    next_access_id: u64,
+}
+
+/// In-memory LRU [`RenderCache`]
+pub struct MemoryCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl MemoryCache {
+    /// A cache of at most `capacity` entries; the least recently used are
+    /// evicted first
+    pub fn new(capacity: usize) -> Self {
+        MemoryCache {
+            capacity: capacity.max(1),
+            entries: Mutex::new(Entries {
+                map: HashMap::new(),
+                next_access_id: 0,
+            }),
+        }
+    }
+
+    /// Number of entries, including expired ones not yet removed
+    pub fn len(&self) -> usize {
+        self.entries.lock().map_or(0, |entries| entries.map.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        MemoryCache::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl Entries {
+    fn access_id(&mut self) -> u64 {
+        let id = self.next_access_id;
+        self.next_access_id = self.next_access_id.wrapping_add(1);
+        id
+    }
+
+    /// Make room for one more entry: drop expired entries, then the least
+    /// recently used tenth
+    fn evict(&mut self, capacity: usize) {
+        let now = Instant::now();
+        self.map
+            .retain(|_, entry| entry.expires.is_none_or(|expires| expires > now));
+        if self.map.len() < capacity {
+            return;
+        }
+        let remove = (capacity / 10).max(self.map.len() + 1 - capacity);
+        // O(n) partition: the `remove` oldest entries come first
+        let mut by_age: Vec<(u64, &String)> = self
+            .map
+            .iter()
+            .map(|(key, entry)| (entry.last_access, key))
+            .collect();
+        by_age.select_nth_unstable(remove - 1);
+        let keys: Vec<String> = by_age[..remove]
+            .iter()
+            .map(|(_, key)| (*key).clone())
+            .collect();
+        for key in keys {
+            self.map.remove(&key);
+        }
+    }
+}
+
+impl RenderCache for MemoryCache {
+    fn get(&self, key: &str) -> Option<String> {
+        let mut entries = self.entries.lock().ok()?;
+        let access_id = entries.access_id();
+        let entry = entries.map.get_mut(key)?;
+        if entry
+            .expires
+            .is_some_and(|expires| expires <= Instant::now())
+        {
+            entries.map.remove(key);
+            return None;
+        }
+        entry.last_access = access_id;
+        Some(entry.html.clone())
+    }
+
+    fn insert(&self, key: &str, html: String, ttl: Option<Duration>) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if !entries.map.contains_key(key) && entries.map.len() >= self.capacity {
+            entries.evict(self.capacity);
+        }
+        let last_access = entries.access_id();
+        entries.map.insert(
+            key.to_string(),
+            Entry {
+                html,
+                expires: ttl.map(|ttl| Instant::now() + ttl),
+                last_access,
+            },
+        );
+    }
+
+    fn invalidate_prefix(&self, prefix: &str) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.map.retain(|key, _| !key.starts_with(prefix));
+        }
+    }
+}
+
+static CACHE: OnceLock<Box<dyn RenderCache>> = OnceLock::new();
+
+/// Set the cache used by `@cache` blocks.
+/// This should be called once at application startup, before the first
+/// render that uses `@cache`.
+pub fn init_cache(cache: impl RenderCache + 'static) {
+    if CACHE.set(Box::new(cache)).is_err() {
+        eprintln!(
+            "WARNING: init_cache() called after the cache was in use - keeping the first cache"
+        );
+    }
+}
+
+/// The cache used by `@cache` blocks
+pub fn cache() -> &'static dyn RenderCache {
+    CACHE
+        .get_or_init(|| Box::new(MemoryCache::default()))
+        .as_ref()
+}
+
+/// Remove every cached fragment whose key starts with `prefix`
+pub fn invalidate_prefix(prefix: &str) {
+    cache().invalidate_prefix(prefix);
+}
+
+/// A `@cache` block
+pub struct Cached<C> {
+    key: String,
+    ttl: Option<Duration>,
+    content: C,
+}
+
+/// Render `content` through the cache under `key` (`@cache` in Rust code)
+pub fn cached<C: Component>(
+    key: impl Into<String>,
+    ttl: impl Into<Option<Duration>>,
+    content: C,
+) -> Cached<C> {
+    Cached {
+        key: key.into(),
+        ttl: ttl.into(),
+        content,
+    }
+}
+
+impl<C: Component> Component for Cached<C> {
+    fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cache = cache();
+        if let Some(html) = cache.get(&self.key) {
+            return f.write_str(&html);
+        }
+        let html =
+            crate::error::try_render_to_string(&self.content).map_err(crate::error::raise)?;
+        f.write_str(&html)?;
+        cache.insert(&self.key, html, self.ttl);
+        Ok(())
+    }
+}
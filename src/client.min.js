@@ -859,6 +859,8 @@ var Idiomorph = (function () {
 class Azumi {
     constructor() {
         this.scopes = new WeakMap(); // Element -> state cache
+        this.telemetryEnabled = true; // Disabled after the first failed report
+        this.prefix = window.AZUMI_PREFIX || ""; // Mount prefix of the Azumi routes
         this.delegate();
         this.connectHotReload();
     }
@@ -866,7 +868,7 @@ class Azumi {
     // Hot Reload Logic
     connectHotReload() {
         const protocol = window.location.protocol === "https:" ? "wss:" : "ws:";
-        const wsUrl = `${protocol}//${window.location.host}/_azumi/live_reload`;
+        const wsUrl = `${protocol}//${window.location.host}${this.prefix}/_azumi/live_reload`;
 
         try {
             const ws = new WebSocket(wsUrl);
@@ -987,7 +989,7 @@ class Azumi {
                 }
             }
 
-            let url = `/_azumi/action${namespace}/${actionName}`;
+            let url = `${this.prefix}/_azumi/action${namespace}/${actionName}`;
             let targetSelector = null;
             let swap = "morph";
 
@@ -1047,16 +1049,7 @@ class Azumi {
         if (!scopeAttr) return null;
 
         try {
-            // Handle signed state: "{json}|{signature}"
-            let jsonStr = scopeAttr;
-            if (scopeAttr.includes("|")) {
-                const parts = scopeAttr.split("|");
-                // JSON is the part before the last pipe (to handle pipes in JSON strings, though rare)
-                // Actually, Azumi security uses "last pipe" logic.
-                const lastPipe = scopeAttr.lastIndexOf("|");
-                jsonStr = scopeAttr.substring(0, lastPipe);
-            }
-
+            const jsonStr = this.scopeJson(scopeAttr);
             const state = JSON.parse(jsonStr);
             const originalState = JSON.parse(jsonStr); // Keep copy for rollback
 
@@ -1186,6 +1179,95 @@ class Azumi {
         });
     }
 
+    /**
+     * Extract the JSON part of a signed az-scope value.
+     * Signed format: "{json}|{timestamp}|{signature}"
+     */
+    scopeJson(scopeAttr) {
+        const signed = scopeAttr.match(/^([\s\S]*)\|\d+\|[A-Za-z0-9+/=]+$/);
+        return signed ? signed[1] : scopeAttr;
+    }
+
+    /**
+     * Read the state the server returned for a live action.
+     * Component handlers return HTML with a root [az-scope]; plain live
+     * handlers return the state as JSON.
+     */
+    serverState(body, contentType) {
+        try {
+            if (contentType && contentType.includes("application/json")) {
+                return JSON.parse(body);
+            }
+            const tpl = document.createElement("template");
+            tpl.innerHTML = body;
+            const scopeEl = tpl.content.querySelector("[az-scope]");
+            if (!scopeEl) return null;
+            return JSON.parse(this.scopeJson(scopeEl.getAttribute("az-scope") || ""));
+        } catch (err) {
+            return null;
+        }
+    }
+
+    /**
+     * Field-level differences between two states ("a.b" paths)
+     */
+    diffStates(predicted, actual, path = "", out = []) {
+        const isObj = (v) => v !== null && typeof v === "object" && !Array.isArray(v);
+        if (isObj(predicted) && isObj(actual)) {
+            const keys = new Set([...Object.keys(predicted), ...Object.keys(actual)]);
+            for (const key of [...keys].sort()) {
+                const child = path ? `${path}.${key}` : key;
+                this.diffStates(
+                    key in predicted ? predicted[key] : null,
+                    key in actual ? actual[key] : null,
+                    child,
+                    out
+                );
+            }
+        } else if (JSON.stringify(predicted) !== JSON.stringify(actual)) {
+            out.push({ path, predicted, actual });
+        }
+        return out;
+    }
+
+    /**
+     * Report a wrong or missing prediction to {prefix}/_azumi/telemetry.
+     * Fire-and-forget; disabled for the page after the first failure
+     * (the server answers 404 until a reporter is installed).
+     */
+    reportPrediction(scopeElement, action, prediction, predicted, actual) {
+        if (!this.telemetryEnabled || !scopeElement || actual == null) return;
+        const structName = scopeElement.getAttribute("az-struct");
+        if (!structName) return;
+
+        const diff = this.diffStates(predicted, actual);
+        if (diff.length === 0) return;
+
+        const report = {
+            struct_name: structName,
+            method: action.actionName,
+            kind: prediction ? "wrong" : "missing",
+            prediction: prediction || null,
+            predicted,
+            actual,
+            diff,
+        };
+        console.warn("[Azumi] Prediction mismatch:", report);
+
+        fetch(`${this.prefix}/_azumi/telemetry`, {
+            method: "POST",
+            headers: { "Content-Type": "application/json" },
+            body: JSON.stringify(report),
+            keepalive: true,
+        })
+            .then((res) => {
+                if (!res.ok) this.telemetryEnabled = false;
+            })
+            .catch(() => {
+                this.telemetryEnabled = false;
+            });
+    }
+
     /**
      * Rollback prediction if server response differs
      */
@@ -1237,7 +1319,23 @@ class Azumi {
         // We must send the original, signed state to the server.
         // If we predict first, we might dirty the state or invalidly sign it.
         let body = null;
-        if (element.tagName === "FORM") {
+        let contentType = "application/json";
+        const enctype = element.tagName === "FORM" ? element.getAttribute("enctype") : null;
+        if (enctype === "multipart/form-data" || enctype === "application/x-www-form-urlencoded") {
+            // Form/Multipart action payloads: send the form as the browser would
+            // (file inputs included), plus the scope's signed state.
+            const data = new FormData(element);
+            if (scopeElement) {
+                data.append("_azumi_scope", scopeElement.getAttribute("az-scope") || "");
+            }
+            if (enctype === "multipart/form-data") {
+                body = data;
+                contentType = null; // the browser adds the multipart boundary
+            } else {
+                body = new URLSearchParams(data);
+                contentType = enctype;
+            }
+        } else if (element.tagName === "FORM") {
             // For forms, we send the form data alongside the parent scope's signed state.
             // This allows the server to verify the request context.
             body = new FormData(element);
@@ -1256,15 +1354,59 @@ class Azumi {
             }
         }
 
+        // Undo/redo history (#[azumi::live(history = N)])
+        const history = this.historyFor(scopeElement);
+        const historyStep =
+            history && (action.actionName === "undo" || action.actionName === "redo")
+                ? action.actionName
+                : null;
+        const signedBefore = scopeElement ? scopeElement.getAttribute("az-scope") : null;
+        const headers = this.requestHeaders(contentType ? { "Content-Type": contentType } : {});
+        // Mounted components: the server renders az-mount on the new root
+        const mountUrl = scopeElement && scopeElement.getAttribute("az-mount");
+        if (mountUrl) headers["X-Azumi-Mount"] = mountUrl;
+        // Ancestors an emitted event may be delivered to (signed into it)
+        const parents = this.parentStructs(scopeElement);
+        if (parents) headers["X-Azumi-Parents"] = parents;
+        if (history) {
+            const stack = historyStep === "undo" ? history.past : history.future;
+            if (historyStep && stack.length === 0) {
+                console.log(`[Azumi] Nothing to ${historyStep}`);
+                scopeElement._azumi_pending = false;
+                return;
+            }
+            if (history.storage === "server") {
+                headers["X-Azumi-History"] = history.id;
+            } else if (historyStep) {
+                // Client storage: the state to restore is the request body
+                body = stack[stack.length - 1];
+            }
+        }
+
         // Check for prediction attribute (Azumi Live)
         const prediction = element.getAttribute("data-predict");
         let predictionResult = null;
 
+        // Pre-action state, used to detect methods that change state without a prediction
+        let stateBefore = null;
+        if (scopeElement) {
+            try {
+                stateBefore = JSON.parse(
+                    this.scopeJson(scopeElement.getAttribute("az-scope") || "")
+                );
+            } catch (err) {
+                stateBefore = null;
+            }
+        }
+
         if (prediction && scopeElement) {
             console.log("[Azumi] Executing Optimistic Prediction:", prediction);
             // Execute prediction. This updates the DOM optimistically.
             // But we already captured 'body' (original state) above, so we are safe!
-            predictionResult = this.executePrediction(scopeElement, prediction);
+            predictionResult =
+                prediction === "@undo" || prediction === "@redo"
+                    ? this.predictHistoryStep(scopeElement, history, historyStep)
+                    : this.executePrediction(scopeElement, prediction);
         }
 
         try {
@@ -1276,27 +1418,74 @@ class Azumi {
             );
             const res = await fetch(action.url, {
                 method: "POST",
-                headers: {
-                    "Content-Type": "application/json",
-                },
+                headers,
                 body, // Sends the ORIGINAL, validly signed state
             });
 
             console.log("[Azumi] Server Response Status:", res.status);
 
+            // Signed state rejected (bad signature, expired, or no longer
+            // matching the struct after a deploy): re-mount from the server
+            // when the component knows its URL, instead of leaving stale state.
+            if (res.headers.get("X-Azumi-Stale-State") !== null && mountUrl) {
+                await this.remount(scopeElement, mountUrl);
+                return;
+            }
+
+            if (res.status === 429) {
+                this.rateLimited(scopeElement || element, action, res.headers.get("Retry-After"));
+                throw new Error("Action rate limited");
+            }
+
             if (!res.ok) throw new Error(`Action failed: ${res.status}`);
 
+            // azumi::action::Reply: navigate, reload or show flash messages
+            if (this.followReply(res.headers)) return;
+
             const html = await res.text();
             console.log("[Azumi] Received HTML length:", html.length);
 
-            // OPTIMIZATION: Check if server state matches prediction
-            // If prediction was correct, skip morphing to prevent flicker
-            /* 
-            // DISABLED: This optimization prevents structural updates (e.g. @if blocks) from rendering
-            if (predictionResult && scopeElement) {
-                // ... (omitted for brevity)
+            // Out-of-band fragments (azumi::fragments::Fragments): several
+            // targets updated from one response
+            if (res.headers.get("X-Azumi-Fragments") !== null) {
+                this.applyFragments(html);
+                return;
             }
-            */
+
+            // Compare the predicted state with server truth and report mismatches.
+            // We still morph below: skipping it would drop structural updates
+            // (e.g. @if blocks) that a state-only prediction cannot express.
+            const actualState = scopeElement
+                ? this.serverState(html, res.headers.get("Content-Type"))
+                : null;
+            if (history) {
+                this.recordHistory(history, historyStep, signedBefore, stateBefore, actualState);
+            }
+
+            if (scopeElement && stateBefore) {
+                const predictedState = predictionResult
+                    ? predictionResult.newState
+                    : stateBefore;
+                this.reportPrediction(
+                    scopeElement,
+                    action,
+                    predictionResult ? prediction : null,
+                    predictedState,
+                    actualState
+                );
+            }
+
+            // Events emitted by a child method go to the enclosing components.
+            // Capture the ancestor before morphing in case the node is replaced.
+            const emitted = this.readEmit(res.headers);
+            const emitSource = scopeElement && emitted !== undefined
+                ? {
+                    parent: scopeElement.parentElement
+                        ? scopeElement.parentElement.closest("[az-scope]")
+                        : null,
+                    source: scopeElement.getAttribute("az-struct"),
+                }
+                : null;
 
             // FIXED: Default target to scopeElement (component root), then element
             let target = scopeElement || element;
@@ -1304,17 +1493,13 @@ class Azumi {
                 target = document.querySelector(action.target);
             }
 
-            if (target && window.Idiomorph) {
-                // Morph will reconcile prediction with server truth
-                // Use outerHTML to replace component wrapper
-                window.Idiomorph.morph(target, html, {
-                    morphStyle: "outerHTML",
-                });
-            } else if (target) {
-                console.warn(
-                    "Idiomorph not loaded, falling back to outerHTML replacement"
-                );
-                target.outerHTML = html;
+            if (target) {
+                // The default morph reconciles the prediction with server truth
+                this.swapContent(target, html, action.swap);
+            }
+
+            if (emitSource && emitSource.parent) {
+                await this.emitToParent(emitSource.parent, emitSource.source, emitted);
             }
         } catch (err) {
             console.error("Action Call Error:", err);
@@ -1333,6 +1518,343 @@ class Azumi {
         }
     }
 
+    /**
+     * Handle the headers of an azumi::action::Reply. Returns true when the
+     * response replaces the usual swap (navigation or flash-only reply).
+     */
+    followReply(headers) {
+        const redirect = headers.get("X-Azumi-Redirect");
+        if (redirect !== null) {
+            window.location.assign(redirect);
+            return true;
+        }
+        if (headers.get("X-Azumi-Refresh") !== null) {
+            window.location.reload();
+            return true;
+        }
+        const encoded = headers.get("X-Azumi-Flash");
+        if (!encoded) return false;
+        try {
+            const bytes = Uint8Array.from(atob(encoded), (c) => c.charCodeAt(0));
+            this.showFlashes(JSON.parse(new TextDecoder().decode(bytes)));
+        } catch (err) {
+            console.warn("[Azumi] Invalid flash messages:", err);
+        }
+        return true;
+    }
+
+    /**
+     * Show flash messages in the [data-azumi-flashes] container (created at
+     * the top of <body> when the page has none). Each message fires the
+     * cancelable azumi:flash event first.
+     */
+    showFlashes(flashes) {
+        let container = document.querySelector("[data-azumi-flashes]");
+        if (!container) {
+            container = document.createElement("div");
+            container.setAttribute("data-azumi-flashes", "");
+            document.body.prepend(container);
+        }
+        for (const flash of flashes) {
+            const event = new CustomEvent("azumi:flash", {
+                bubbles: true,
+                cancelable: true,
+                detail: flash,
+            });
+            if (!container.dispatchEvent(event)) continue;
+            const message = document.createElement("div");
+            message.className = `azumi-flash azumi-flash-${flash.level}`;
+            message.setAttribute(
+                "role",
+                flash.level === "warning" || flash.level === "error" ? "alert" : "status"
+            );
+            message.textContent = flash.message;
+            container.appendChild(message);
+        }
+    }
+
+    /**
+     * Apply a Fragments response: each <template az-fragment="selector"
+     * az-swap="mode"> is swapped into every element matching its selector.
+     */
+    applyFragments(html) {
+        const container = document.createElement("template");
+        container.innerHTML = html;
+        for (const fragment of Array.from(container.content.children)) {
+            if (fragment.tagName !== "TEMPLATE" || !fragment.hasAttribute("az-fragment")) continue;
+            const selector = fragment.getAttribute("az-fragment");
+            const swap = fragment.getAttribute("az-swap") || "morph";
+            let targets = [];
+            try {
+                targets = Array.from(document.querySelectorAll(selector));
+            } catch (err) {
+                console.warn("[Azumi] Invalid fragment selector:", selector);
+            }
+            if (targets.length === 0) {
+                console.warn("[Azumi] No element matches fragment target:", selector);
+            }
+            for (const target of targets) {
+                this.swapContent(target, fragment.innerHTML, swap);
+            }
+        }
+    }
+
+    /**
+     * Put server HTML into the page:
+     *   morph        morph the element (outerHTML) with Idiomorph (default)
+     *   inner        replace the children
+     *   outer        replace the element
+     *   beforebegin / afterbegin / beforeend / afterend
+     *                insert next to or inside the element
+     *   delete       remove the element, ignoring the response
+     *   none         leave the page unchanged
+     */
+    swapContent(target, html, swap) {
+        switch (swap) {
+            case "none":
+                break;
+            case "delete":
+                target.remove();
+                break;
+            case "beforebegin":
+            case "afterbegin":
+            case "beforeend":
+            case "afterend":
+                target.insertAdjacentHTML(swap, html);
+                break;
+            case "inner":
+                if (window.Idiomorph) {
+                    window.Idiomorph.morph(target, html, { morphStyle: "innerHTML" });
+                } else {
+                    target.innerHTML = html;
+                }
+                break;
+            case "outer":
+                target.outerHTML = html;
+                break;
+            default:
+                if (swap && swap !== "morph") {
+                    console.warn(`[Azumi] Unknown swap mode "${swap}", morphing instead`);
+                }
+                if (window.Idiomorph) {
+                    window.Idiomorph.morph(target, html, { morphStyle: "outerHTML" });
+                } else {
+                    console.warn("Idiomorph not loaded, falling back to outerHTML replacement");
+                    target.outerHTML = html;
+                }
+        }
+    }
+
+    /**
+     * 429 from a rate-limited route: fire azumi:rate-limited and, unless a
+     * listener cancels it, show a message next to the element until the
+     * Retry-After delay has passed. The caller rolls back the prediction.
+     */
+    rateLimited(element, action, retryAfterHeader) {
+        const retryAfter = Math.max(parseInt(retryAfterHeader || "1", 10) || 1, 1);
+        const event = new CustomEvent("azumi:rate-limited", {
+            bubbles: true,
+            cancelable: true,
+            detail: { action: action.actionName, retryAfter },
+        });
+        if (!element.dispatchEvent(event)) return;
+
+        const existing = element.parentElement
+            ? element.parentElement.querySelector(":scope > .azumi-rate-limited")
+            : null;
+        if (existing) existing.remove();
+        const message = document.createElement("span");
+        message.className = "azumi-rate-limited";
+        message.setAttribute("role", "status");
+        message.textContent = `Too many requests. Try again in ${retryAfter}s.`;
+        element.insertAdjacentElement("afterend", message);
+        setTimeout(() => message.remove(), retryAfter * 1000);
+    }
+
+    /**
+     * History stacks of a scope element with az-history, or null.
+     * Stacks hold previous signed az-scope values and survive morphs because
+     * Idiomorph keeps the root element.
+     */
+    historyFor(scopeElement) {
+        if (!scopeElement) return null;
+        const capacity = parseInt(scopeElement.getAttribute("az-history") || "0", 10);
+        if (!capacity) return null;
+        if (!scopeElement._azumi_history) {
+            scopeElement._azumi_history = {
+                capacity,
+                storage: scopeElement.getAttribute("az-history-storage") || "client",
+                id: window.crypto && crypto.randomUUID
+                    ? crypto.randomUUID()
+                    : Math.random().toString(36).slice(2) + Date.now().toString(36),
+                past: [],
+                future: [],
+            };
+        }
+        return scopeElement._azumi_history;
+    }
+
+    /**
+     * @undo / @redo prediction: restore the prior state immediately.
+     * Returns a result compatible with rollbackPrediction.
+     */
+    predictHistoryStep(scopeElement, history, step) {
+        if (!history || !step) return null;
+        const stack = step === "undo" ? history.past : history.future;
+        const target = stack[stack.length - 1];
+        const originalScopeAttr = scopeElement.getAttribute("az-scope");
+        try {
+            const originalState = JSON.parse(this.scopeJson(originalScopeAttr || ""));
+            const newState = JSON.parse(this.scopeJson(target));
+            scopeElement.setAttribute("az-scope", target);
+            this.updateBindings(scopeElement, newState);
+            console.log(`⏪ Prediction executed: @${step}`, newState);
+            return { originalState, newState, originalScopeAttr };
+        } catch (err) {
+            console.warn("History prediction failed:", err);
+            return null;
+        }
+    }
+
+    /**
+     * Update the history stacks after a successful action
+     */
+    recordHistory(history, step, signedBefore, stateBefore, actualState) {
+        if (step === "undo") {
+            history.past.pop();
+            history.future.push(signedBefore);
+        } else if (step === "redo") {
+            history.future.pop();
+            history.past.push(signedBefore);
+        } else if (
+            stateBefore &&
+            actualState &&
+            JSON.stringify(stateBefore) !== JSON.stringify(actualState)
+        ) {
+            history.past.push(signedBefore);
+            history.future = [];
+        }
+        while (history.past.length > history.capacity) {
+            history.past.shift();
+        }
+    }
+
+    /**
+     * Mark the request as sent by the runtime (a redirect then comes back as
+     * X-Azumi-Redirect instead of 303) and add the CSRF token
+     */
+    requestHeaders(headers) {
+        return this.csrfHeaders({ ...headers, "X-Azumi-Request": "1" });
+    }
+
+    /**
+     * Add the CSRF token rendered by azumi::csrf::csrf_meta() (if any)
+     */
+    csrfHeaders(headers) {
+        const meta = document.querySelector('meta[name="azumi-csrf"]');
+        if (meta) headers["X-Azumi-CSRF"] = meta.getAttribute("content");
+        return headers;
+    }
+
+    /**
+     * The signed event carried in the X-Azumi-Emit response header, passed
+     * to the ancestor unchanged. Returns undefined when nothing was emitted.
+     */
+    readEmit(headers) {
+        return headers.get("X-Azumi-Emit") || undefined;
+    }
+
+    /**
+     * az-struct names of the live components enclosing `element`, nearest
+     * first and comma-separated (X-Azumi-Parents). Empty when there are none.
+     */
+    parentStructs(element) {
+        const names = [];
+        let parent = element && element.parentElement
+            ? element.parentElement.closest("[az-scope]")
+            : null;
+        while (parent) {
+            const name = parent.getAttribute("az-struct");
+            if (name) names.push(name);
+            parent = parent.parentElement ? parent.parentElement.closest("[az-scope]") : null;
+        }
+        return names.join(",");
+    }
+
+    /**
+     * Deliver a child event to the nearest ancestor with an #[on_emit] handler
+     * for the child struct, and morph that ancestor with its re-rendered HTML.
+     * Ancestors without a handler answer 404 and the event keeps bubbling.
+     */
+    async emitToParent(parent, source, event) {
+        while (parent) {
+            const struct = parent.getAttribute("az-struct");
+            const headers = this.requestHeaders({ "Content-Type": "application/json" });
+            const mountUrl = parent.getAttribute("az-mount");
+            if (mountUrl) headers["X-Azumi-Mount"] = mountUrl;
+            const parents = this.parentStructs(parent);
+            if (parents) headers["X-Azumi-Parents"] = parents;
+            const res = await fetch(`${this.prefix}/_azumi/action/${struct}/__emit/${source}`, {
+                method: "POST",
+                headers,
+                body: JSON.stringify({
+                    state: parent.getAttribute("az-scope") || "",
+                    event,
+                }),
+            });
+
+            if (res.status === 404) {
+                parent = parent.parentElement
+                    ? parent.parentElement.closest("[az-scope]")
+                    : null;
+                continue;
+            }
+            if (!res.ok) throw new Error(`Emit to ${struct} failed: ${res.status}`);
+
+            const html = await res.text();
+
+            // A handler may emit in turn; continue from this ancestor
+            const next = this.readEmit(res.headers);
+            const grandparent = parent.parentElement
+                ? parent.parentElement.closest("[az-scope]")
+                : null;
+
+            if (window.Idiomorph) {
+                window.Idiomorph.morph(parent, html, { morphStyle: "outerHTML" });
+            } else {
+                parent.outerHTML = html;
+            }
+
+            if (next !== undefined && grandparent) {
+                await this.emitToParent(grandparent, struct, next);
+            }
+            return;
+        }
+    }
+
+    /**
+     * Re-mount a live component from its az-mount URL (fresh signed state)
+     */
+    async remount(scopeElement, mountUrl) {
+        console.warn("[Azumi] State rejected, re-mounting from", mountUrl);
+        try {
+            const res = await fetch(mountUrl, {
+                headers: { "X-Azumi-Remount": "1" },
+            });
+            if (!res.ok) throw new Error(`Re-mount failed: ${res.status}`);
+            const html = await res.text();
+            if (window.Idiomorph) {
+                window.Idiomorph.morph(scopeElement, html, {
+                    morphStyle: "outerHTML",
+                });
+            } else {
+                scopeElement.outerHTML = html;
+            }
+        } catch (err) {
+            console.error("Re-mount Error:", err);
+        }
+    }
+
     // Local state change (no server roundtrip)
     setState(action, element) {
         const scopeElement = element.closest("[az-scope]");
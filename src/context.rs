@@ -1,141 +1,276 @@
+use crate::Component;
+use futures_util::future::BoxFuture;
+use std::any::{Any, TypeId};
 use std::cell::RefCell;
 use std::future::Future;
-use std::rc::Rc;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 
+#[cfg(feature = "axum")]
 tokio::task_local! {
     static CURRENT_PATH: String;
 }
 
 /// Run a future with the current request path derived from context
+#[cfg(feature = "axum")]
 pub async fn with_path<F: Future>(path: String, f: F) -> F::Output {
     CURRENT_PATH.scope(path, f).await
 }
 
 /// Get the current request path if available in the task context
+#[cfg(feature = "axum")]
 pub fn get_current_path() -> Option<String> {
     CURRENT_PATH.try_with(|p| p.clone()).ok()
 }
 
+/// Without a web integration there is no request path
+#[cfg(not(feature = "axum"))]
+pub fn get_current_path() -> Option<String> {
+    None
+}
+
 // ============================================================================
-// Sync Context for Rendering (Thread Local)
+// Provided Values
 // ============================================================================
+//
+// `@provide(value) { ... }` makes `value` available to everything rendered in
+// the block, through `use_context::<T>()`:
+//
+// ```rust,ignore
+// #[azumi::component]
+// pub fn ThemedButton(label: &str) -> impl Component {
+//     let theme = azumi::use_context::<Theme>().unwrap_or_default();
+//     html! { <button class={theme.button_class()}>{label}</button> }
+// }
+//
+// html! {
+//     @provide(Theme::Dark) {
+//         @ThemedButton(label = "Save")
+//     }
+// }
+// ```
+//
+// Values are looked up by type; the innermost `@provide` of a type wins.
+// They are visible while the block renders: component bodies called from it
+// (`@ThemedButton(...)`) and `@suspense` blocks in it, which keep the values
+// provided where they were rendered even when they resolve on another task.
 
-#[derive(Clone, Default, Debug)]
-pub struct PageMeta {
-    pub title: Option<String>,
-    pub description: Option<String>,
-    pub image: Option<String>,
-}
+type Provided = Vec<(TypeId, Arc<dyn Any + Send + Sync>)>;
 
 thread_local! {
-    static PAGE_META: RefCell<PageMeta> = RefCell::new(PageMeta::default());
+    static PROVIDED: RefCell<Provided> = const { RefCell::new(Vec::new()) };
 }
 
-#[derive(Clone)]
-#[allow(dead_code)]
-struct PageMetaState {
-    refcount: Rc<std::sync::atomic::AtomicU32>,
+/// The innermost value of type `T` provided with `@provide` (or [`provide`])
+/// around the current render, if any
+pub fn use_context<T: Clone + Send + Sync + 'static>() -> Option<T> {
+    PROVIDED.with(|provided| {
+        provided
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(type_id, _)| *type_id == TypeId::of::<T>())
+            .and_then(|(_, value)| value.downcast_ref::<T>().cloned())
+    })
+}
+
+/// Provides a value until dropped. Returned by [`enter`].
+pub struct ProvideGuard {
+    depth: usize,
+    // Bound to the thread whose stack it pushed to
+    _not_send: PhantomData<*const ()>,
 }
 
-impl PageMetaState {
-    fn new() -> Self {
-        Self {
-            refcount: Rc::new(std::sync::atomic::AtomicU32::new(1)),
+/// Provide `value` to [`use_context`] until the guard is dropped (used by
+/// `@provide`)
+pub fn enter<T: Send + Sync + 'static>(value: T) -> ProvideGuard {
+    PROVIDED.with(|provided| {
+        let mut provided = provided.borrow_mut();
+        let depth = provided.len();
+        provided.push((TypeId::of::<T>(), Arc::new(value)));
+        ProvideGuard {
+            depth,
+            _not_send: PhantomData,
         }
+    })
+}
+
+impl Drop for ProvideGuard {
+    fn drop(&mut self) {
+        PROVIDED.with(|provided| provided.borrow_mut().truncate(self.depth));
     }
+}
 
-    fn clone(&self) -> Self {
-        self.refcount.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-        Self {
-            refcount: Rc::clone(&self.refcount),
-        }
+/// `content` rendered with `value` provided (`@provide` in Rust code)
+pub struct Provide<T, C> {
+    value: T,
+    content: C,
+}
+
+/// Render `content` with `value` available to [`use_context`]
+pub fn provide<T, C>(value: T, content: C) -> Provide<T, C>
+where
+    T: Clone + Send + Sync + 'static,
+    C: Component,
+{
+    Provide { value, content }
+}
+
+impl<T, C> Component for Provide<T, C>
+where
+    T: Clone + Send + Sync + 'static,
+    C: Component,
+{
+    fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let _guard = enter(self.value.clone());
+        self.content.render(f)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.content.size_hint()
     }
 }
 
-impl Drop for PageMetaState {
-    fn drop(&mut self) {
-        if self.refcount.fetch_sub(1, std::sync::atomic::Ordering::SeqCst) == 1 {
-            PAGE_META.with(|params| *params.borrow_mut() = PageMeta::default());
-        }
+/// `future` polled with the values provided where it was created
+pub(crate) fn scoped<T: 'static>(future: BoxFuture<'static, T>) -> BoxFuture<'static, T> {
+    let provided = PROVIDED.with(|provided| provided.borrow().clone());
+    if provided.is_empty() {
+        return future;
     }
+    Box::pin(Scoped { provided, future })
 }
 
-thread_local! {
-    static PAGE_META_STATE: PageMetaState = PageMetaState::new();
+struct Scoped<T> {
+    provided: Provided,
+    future: BoxFuture<'static, T>,
 }
 
-/// RAII guard that resets PAGE_META to default when all guards are dropped.
-/// Ensures metadata from one request cannot leak into another.
-///
-/// # Thread Safety
-///
-/// `PageMetaGuard` uses atomic reference counting and is `Sync`.
-/// However, guards must stay on the thread where they were created because
-/// `PAGE_META` is a `thread_local!` `RefCell` which is not thread-safe.
-///
-/// **Key limitation**: Guards must not cross thread boundaries.
-///
-/// This is intentional: each thread has its own `PAGE_META`, so developers must ensure
-/// guards do not cross thread boundaries.
-#[derive(Clone)]
-#[allow(dead_code)]
-pub struct PageMetaGuard(PageMetaState);
+impl<T> Future for Scoped<T> {
+    type Output = T;
 
-impl PageMetaGuard {
-    fn new() -> Self {
-        Self(PAGE_META_STATE.with(|s| s.clone()))
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let this = &mut *self;
+        let previous = PROVIDED.with(|provided| provided.replace(this.provided.clone()));
+        let result = this.future.as_mut().poll(cx);
+        PROVIDED.with(|provided| *provided.borrow_mut() = previous);
+        result
     }
 }
 
-/// Set the metadata for the current page render and return a guard.
-/// The guard ensures metadata is reset when all copies of the guard are dropped.
-///
-/// # Safety / TOCTOU Warning
-///
-/// This function uses a `thread_local!` `RefCell` which is **not async-safe** when
-/// the guarded value is accessed across `.await` points. Specifically:
-///
-/// - `set_page_meta()` writes to `PAGE_META`
-/// - If the async code that reads `PAGE_META` (via `get_page_meta()`) yields at an `.await`,
-///   another task on the same thread can overwrite `PAGE_META`
-/// - When the guard drops, it resets `PAGE_META` — but by this point the original
-///   async task may have resumed with the wrong metadata
-///
-/// **Rule**: Do not pass `PageMetaGuard` across `.await` points. The guard should
-/// be dropped before any async operation that might yield.
-///
-/// Example of UNSAFE usage:
-/// ```ignore
-/// let guard = set_page_meta(...);
-/// some_async_operation().await; // BAD: another task could overwrite PAGE_META here
-/// let meta = get_page_meta();   // May read corrupted/wrong metadata
-/// drop(guard);                  // Resets at wrong time
-/// ```
+// ============================================================================
+// Page Metadata
+// ============================================================================
+//
+// Title, description and image of the page being rendered, read by
+// `seo::generate_head` (`head!`, `seo::render_automatic_seo`). Set by
+// `#[azumi::page]` for the duration of its render, or by a handler for a
+// whole request with `with_page_meta`. Neither outlives its scope, so
+// metadata of one request is never seen by another.
+
+#[derive(Clone, Default, Debug)]
+pub struct PageMeta {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+}
+
+#[cfg(feature = "axum")]
+tokio::task_local! {
+    static PAGE_META: PageMeta;
+}
+
+/// Run a future with `meta` as the metadata of every page it renders (unless
+/// the page sets its own, e.g. with `#[azumi::page]`)
+#[cfg(feature = "axum")]
+pub async fn with_page_meta<F: Future>(meta: PageMeta, f: F) -> F::Output {
+    PAGE_META.scope(meta, f).await
+}
+
+/// Provides page metadata until dropped. Returned by [`set_page_meta`].
 ///
-/// Example of SAFE usage:
-/// ```ignore
-/// let guard = set_page_meta(...);
-/// let meta = get_page_meta();   // Read immediately before any await
-/// drop(guard);                  // Reset immediately
-/// some_async_operation().await; // Now safe to await
-/// ```
+/// The guard is not `Send`: a handler future holding it across an `.await`
+/// does not compile. Use [`with_page_meta`] for a whole request instead.
+pub struct PageMetaGuard(#[allow(dead_code)] ProvideGuard);
+
+/// Set the metadata of the page being rendered until the guard is dropped.
+/// Used by `#[azumi::page]` inside its render.
 pub fn set_page_meta(
     title: Option<String>,
     description: Option<String>,
     image: Option<String>,
 ) -> PageMetaGuard {
-    PAGE_META.with(|params| {
-        *params.borrow_mut() = PageMeta {
-            title,
-            description,
-            image,
-        };
-    });
-    PageMetaGuard::new()
+    PageMetaGuard(enter(PageMeta {
+        title,
+        description,
+        image,
+    }))
 }
 
-/// Get the current page metadata.
+/// Get the current page metadata: the innermost [`set_page_meta`] of the
+/// current render, then [`with_page_meta`] of the current task, then empty.
 /// This is used by the `head!` macro or layout components.
 pub fn get_page_meta() -> PageMeta {
-    PAGE_META.with(|params| params.borrow().clone())
+    use_context::<PageMeta>()
+        .or_else(task_page_meta)
+        .unwrap_or_default()
+}
+
+#[cfg(feature = "axum")]
+fn task_page_meta() -> Option<PageMeta> {
+    PAGE_META.try_with(|meta| meta.clone()).ok()
+}
+
+#[cfg(not(feature = "axum"))]
+fn task_page_meta() -> Option<PageMeta> {
+    None
+}
+
+/// Request path, page metadata, CSRF token and flash messages of a task,
+/// carried to a render on another thread (`stream::stream` renders on a
+/// blocking thread)
+#[cfg(feature = "axum")]
+#[derive(Clone)]
+pub(crate) struct RequestScope {
+    path: Option<String>,
+    meta: Option<PageMeta>,
+    csrf_token: Option<String>,
+    flashes: Option<Vec<crate::flash::Flash>>,
+}
+
+#[cfg(feature = "axum")]
+impl RequestScope {
+    pub(crate) fn current() -> Self {
+        RequestScope {
+            path: get_current_path(),
+            meta: task_page_meta(),
+            csrf_token: crate::csrf::current_token(),
+            flashes: crate::flash::current(),
+        }
+    }
+
+    pub(crate) fn run<R>(self, f: impl FnOnce() -> R) -> R {
+        // As a provided value, the metadata also reaches @suspense blocks
+        let _meta = self.meta.map(enter);
+        let f = move || {
+            crate::csrf::sync_scope(self.csrf_token, || {
+                crate::flash::sync_scope(self.flashes, f)
+            })
+        };
+        match self.path {
+            Some(path) => CURRENT_PATH.sync_scope(path, f),
+            None => f(),
+        }
+    }
+
+    /// [`RequestScope::run`] for a future, without the page metadata (which
+    /// `@suspense` blocks already carry as a provided value)
+    pub(crate) async fn scope<F: Future>(self, f: F) -> F::Output {
+        let f = crate::csrf::scope(self.csrf_token, crate::flash::scope(self.flashes, f));
+        match self.path {
+            Some(path) => CURRENT_PATH.scope(path, f).await,
+            None => f.await,
+        }
+    }
 }
@@ -0,0 +1,453 @@
+//! # CSRF Protection
+//!
+//! Opt-in tower layer protecting `/_azumi/action/*` (live handlers and
+//! `#[azumi::action]` endpoints) against cross-site request forgery.
+//!
+//! ```rust,ignore
+//! let app = azumi::action::register_actions_with_csrf(
+//!     Router::new().route("/", get(index)),
+//!     azumi::csrf::CsrfLayer::double_submit(),
+//! );
+//! ```
+//!
+//! Two modes are available:
+//!
+//! - [`CsrfLayer::double_submit`]: every response that passes through the
+//!   layer carries a signed token in the `azumi_csrf` cookie. Pages render it
+//!   with [`csrf_meta`] and the client runtime sends it back in the
+//!   `X-Azumi-CSRF` header; forms posted without JavaScript send it through
+//!   [`csrf_input`]. Protected requests are rejected unless header (or form
+//!   field) and cookie hold the same validly signed token. Form fields are
+//!   read from urlencoded and `multipart/form-data` bodies; in a multipart
+//!   form, place the input before any file fields, as only the start of the
+//!   body is read.
+//! - [`CsrfLayer::origin`]: no token. Protected requests are rejected when
+//!   `Sec-Fetch-Site` says `cross-site`/`same-site`, or when `Origin` does not
+//!   match the `Host` (or an origin allowed with [`CsrfLayer::allow_origin`]).
+//!   Requests without either header (non-browser clients) are allowed.
+//!
+//! Only unsafe methods (`POST`, `PUT`, `PATCH`, `DELETE`) under the protected
+//! prefix are checked. Apply the layer to the whole app so that page
+//! responses also receive the cookie.
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, Method, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Header the client runtime sends the token in
+pub const CSRF_HEADER: &str = "x-azumi-csrf";
+/// Form field read when the header is missing (`<form>` without JavaScript)
+pub const CSRF_FIELD: &str = "_azumi_csrf";
+/// Default cookie holding the token
+pub const CSRF_COOKIE: &str = "azumi_csrf";
+
+/// Largest urlencoded body (or multipart prefix) buffered to look for
+/// [`CSRF_FIELD`]
+const MAX_FORM_BYTES: usize = 64 * 1024;
+
+tokio::task_local! {
+    static CURRENT_TOKEN: String;
+}
+
+/// The CSRF token of the current request, if the double-submit layer is active
+pub fn current_token() -> Option<String> {
+    CURRENT_TOKEN.try_with(|t| t.clone()).ok()
+}
+
+/// Run `f` with `token` as the current token (a render on another thread)
+pub(crate) fn sync_scope<R>(token: Option<String>, f: impl FnOnce() -> R) -> R {
+    match token {
+        Some(token) => CURRENT_TOKEN.sync_scope(token, f),
+        None => f(),
+    }
+}
+
+/// [`sync_scope`] for a future (a render on another task)
+pub(crate) async fn scope<F: Future>(token: Option<String>, f: F) -> F::Output {
+    match token {
+        Some(token) => CURRENT_TOKEN.scope(token, f).await,
+        None => f.await,
+    }
+}
+
+/// `<meta name="azumi-csrf" content="...">` for the page `<head>`.
+/// Renders nothing outside the double-submit layer. In `multipart/form-data`
+/// forms, place it before any file inputs.
+pub fn csrf_meta() -> impl crate::Component {
+    let token = current_token();
+    crate::from_fn(move |f| match &token {
+        Some(token) => write!(
+            f,
+            "<meta name=\"azumi-csrf\" content=\"{}\">",
+            crate::Escaped(token)
+        ),
+        None => Ok(()),
+    })
+}
+
+/// Hidden `<input name="_azumi_csrf">` for forms posted without JavaScript.
+/// Renders nothing outside the double-submit layer. In `multipart/form-data`
+/// forms, place it before any file inputs.
+pub fn csrf_input() -> impl crate::Component {
+    let token = current_token();
+    crate::from_fn(move |f| match &token {
+        Some(token) => write!(
+            f,
+            "<input type=\"hidden\" name=\"{}\" value=\"{}\">",
+            CSRF_FIELD,
+            crate::Escaped(token)
+        ),
+        None => Ok(()),
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsrfMode {
+    /// Signed token in a cookie, echoed in a header or form field
+    DoubleSubmit,
+    /// `Sec-Fetch-Site` / `Origin` verification
+    Origin,
+}
+
+#[derive(Clone, Debug)]
+struct CsrfConfig {
+    mode: CsrfMode,
+    prefix: String,
+    cookie_name: String,
+    secure: bool,
+    allowed_origins: Vec<String>,
+}
+
+/// Tower layer enforcing CSRF protection (see the module docs)
+#[derive(Clone, Debug)]
+pub struct CsrfLayer {
+    config: Arc<CsrfConfig>,
+}
+
+impl CsrfLayer {
+    fn new(mode: CsrfMode) -> Self {
+        Self {
+            config: Arc::new(CsrfConfig {
+                mode,
+                prefix: "/_azumi/action/".to_string(),
+                cookie_name: CSRF_COOKIE.to_string(),
+                secure: true,
+                allowed_origins: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn double_submit() -> Self {
+        Self::new(CsrfMode::DoubleSubmit)
+    }
+
+    pub fn origin() -> Self {
+        Self::new(CsrfMode::Origin)
+    }
+
+    /// Path prefix that is protected (default `/_azumi/action/`)
+    pub fn protect_prefix(mut self, prefix: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config).prefix = prefix.into();
+        self
+    }
+
+    /// Move the protected prefix under a mount prefix
+    /// (see [`crate::action::AzumiRoutes::prefix`])
+    pub(crate) fn under_prefix(mut self, mount: &str) -> Self {
+        if !mount.is_empty() {
+            let config = Arc::make_mut(&mut self.config);
+            config.prefix = format!("{}{}", mount, config.prefix);
+        }
+        self
+    }
+
+    /// Cookie holding the token (default `azumi_csrf`)
+    pub fn cookie_name(mut self, name: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config).cookie_name = name.into();
+        self
+    }
+
+    /// Whether the cookie is marked `Secure` (default `true`; browsers accept
+    /// secure cookies on `http://localhost`)
+    pub fn secure(mut self, secure: bool) -> Self {
+        Arc::make_mut(&mut self.config).secure = secure;
+        self
+    }
+
+    /// Extra origin (e.g. `https://app.example.com`) accepted in origin mode
+    pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config)
+            .allowed_origins
+            .push(origin.into());
+        self
+    }
+}
+
+impl<S> tower_layer::Layer<S> for CsrfLayer {
+    type Service = CsrfService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CsrfService {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// Service produced by [`CsrfLayer`]
+#[derive(Clone, Debug)]
+pub struct CsrfService<S> {
+    inner: S,
+    config: Arc<CsrfConfig>,
+}
+
+impl<S> tower_service::Service<Request<Body>> for CsrfService<S>
+where
+    S: tower_service::Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let config = self.config.clone();
+        // Take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let protected = is_unsafe(req.method()) && req.uri().path().starts_with(&config.prefix);
+
+            if config.mode == CsrfMode::Origin {
+                if protected && !origin_allowed(req.headers(), &config) {
+                    return Ok(reject("Cross-origin request blocked"));
+                }
+                return inner.call(req).await;
+            }
+
+            let cookie_token =
+                cookie_value(req.headers(), &config.cookie_name).filter(|t| token_is_valid(t));
+
+            let req = if protected {
+                let (req, sent) = submitted_token(req).await;
+                match (&cookie_token, sent) {
+                    (Some(cookie), Some(sent)) if constant_time_eq(cookie, &sent) => req,
+                    _ => return Ok(reject("CSRF token missing or invalid")),
+                }
+            } else {
+                req
+            };
+
+            match cookie_token {
+                Some(token) => CURRENT_TOKEN.scope(token, inner.call(req)).await,
+                None => {
+                    let token = new_token();
+                    let mut response = CURRENT_TOKEN.scope(token.clone(), inner.call(req)).await?;
+                    let mut cookie = format!(
+                        "{}={}; Path=/; HttpOnly; SameSite=Strict",
+                        config.cookie_name, token
+                    );
+                    if config.secure {
+                        cookie.push_str("; Secure");
+                    }
+                    if let Ok(value) = header::HeaderValue::from_str(&cookie) {
+                        response.headers_mut().append(header::SET_COOKIE, value);
+                    }
+                    Ok(response)
+                }
+            }
+        })
+    }
+}
+
+fn is_unsafe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    )
+}
+
+fn reject(message: &'static str) -> Response {
+    (StatusCode::FORBIDDEN, message).into_response()
+}
+
+/// MAC purpose tag of CSRF tokens
+const MAC_PURPOSE: &[u8] = b"azumi-csrf:";
+
+/// `{nonce}.{hmac("azumi-csrf:" + nonce)}`, both URL-safe base64
+fn new_token() -> String {
+    let mut nonce = [0u8; 32];
+    getrandom::getrandom(&mut nonce).expect("OS random number generator unavailable");
+    let nonce = URL_SAFE_NO_PAD.encode(nonce);
+    let tag = URL_SAFE_NO_PAD.encode(crate::security::mac_bytes(MAC_PURPOSE, nonce.as_bytes()));
+    format!("{}.{}", nonce, tag)
+}
+
+fn token_is_valid(token: &str) -> bool {
+    let Some((nonce, tag)) = token.split_once('.') else {
+        return false;
+    };
+    URL_SAFE_NO_PAD
+        .decode(tag)
+        .is_ok_and(|tag| crate::security::verify_mac(MAC_PURPOSE, nonce.as_bytes(), &tag))
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+fn cookie_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Token from the header, or from the `_azumi_csrf` field of an urlencoded body.
+/// The body is buffered and put back so the handler can still read it.
+async fn submitted_token(req: Request<Body>) -> (Request<Body>, Option<String>) {
+    if let Some(token) = req.headers().get(CSRF_HEADER).and_then(|v| v.to_str().ok()) {
+        let token = token.to_string();
+        return (req, Some(token));
+    }
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if let Some(boundary) = multipart_boundary(content_type) {
+        return multipart_token(req, &boundary).await;
+    }
+    if !content_type.starts_with("application/x-www-form-urlencoded") {
+        return (req, None);
+    }
+
+    let (parts, body) = req.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_FORM_BYTES).await else {
+        return (Request::from_parts(parts, Body::empty()), None);
+    };
+    let token = form_urlencoded_value(&bytes, CSRF_FIELD);
+    (Request::from_parts(parts, Body::from(bytes)), token)
+}
+
+/// Reads the body only until the [`CSRF_FIELD`] part is complete, so uploads
+/// are not buffered. The field must therefore come before large file parts
+/// (render [`csrf_input`] at the top of the form) and within the first
+/// `MAX_FORM_BYTES`. The consumed prefix is replayed in front of the rest of
+/// the stream.
+async fn multipart_token(req: Request<Body>, boundary: &str) -> (Request<Body>, Option<String>) {
+    use futures_util::StreamExt as _;
+
+    let (parts, body) = req.into_parts();
+    let mut stream = body.into_data_stream();
+    let mut buffered = Vec::new();
+    let mut token = None;
+    while token.is_none() && buffered.len() < MAX_FORM_BYTES {
+        match stream.next().await {
+            Some(Ok(chunk)) => {
+                buffered.extend_from_slice(&chunk);
+                token = multipart_value(&buffered, boundary, CSRF_FIELD);
+            }
+            Some(Err(_)) => return (Request::from_parts(parts, Body::empty()), None),
+            None => break,
+        }
+    }
+    let prefix =
+        futures_util::stream::once(
+            async move { Ok::<_, axum::Error>(bytes::Bytes::from(buffered)) },
+        );
+    (
+        Request::from_parts(parts, Body::from_stream(prefix.chain(stream))),
+        token,
+    )
+}
+
+fn multipart_boundary(content_type: &str) -> Option<String> {
+    let (mime, params) = content_type.split_once(';')?;
+    if !mime.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params
+        .split(';')
+        .filter_map(|param| param.trim().split_once('='))
+        .find(|(key, _)| key.eq_ignore_ascii_case("boundary"))
+        .map(|(_, value)| value.trim_matches('"').to_string())
+        .filter(|boundary| !boundary.is_empty())
+}
+
+/// Value of the first complete text part named `field`
+fn multipart_value(body: &[u8], boundary: &str, field: &str) -> Option<String> {
+    let delimiter = format!("--{}", boundary);
+    let part_end = format!("\r\n{}", delimiter);
+    let name = format!("; name=\"{}\"", field);
+
+    let mut rest = body;
+    while let Some(start) = find(rest, delimiter.as_bytes()) {
+        rest = &rest[start + delimiter.len()..];
+        let headers_end = find(rest, b"\r\n\r\n")?;
+        let content = &rest[headers_end + 4..];
+        let end = find(content, part_end.as_bytes())?;
+        if String::from_utf8_lossy(&rest[..headers_end]).contains(&name) {
+            return std::str::from_utf8(&content[..end])
+                .ok()
+                .map(str::to_string);
+        }
+        rest = &content[end..];
+    }
+    None
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn form_urlencoded_value(body: &[u8], field: &str) -> Option<String> {
+    let body = std::str::from_utf8(body).ok()?;
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == field)
+        .map(|(_, value)| value.to_string())
+}
+
+fn origin_allowed(headers: &HeaderMap, config: &CsrfConfig) -> bool {
+    let header_str = |name: header::HeaderName| headers.get(name).and_then(|v| v.to_str().ok());
+
+    let origin = header_str(header::ORIGIN);
+    if let Some(origin) = origin {
+        if config.allowed_origins.iter().any(|o| o == origin) {
+            return true;
+        }
+    }
+
+    match headers.get("sec-fetch-site").and_then(|v| v.to_str().ok()) {
+        Some("same-origin") | Some("none") => return true,
+        Some(_) => return false,
+        None => {}
+    }
+
+    match (origin, header_str(header::HOST)) {
+        (None, _) => true,
+        (Some(origin), Some(host)) => origin
+            .split_once("://")
+            .is_some_and(|(_, authority)| authority == host),
+        (Some(_), None) => false,
+    }
+}
@@ -202,10 +202,15 @@ fn try_hot_patch_internal(path: &Path, port: &str) -> Result<bool, Box<dyn std::
         return Ok(false);
     }
 
+    // Routes mounted with `azumi::routes().prefix(..)`
+    let prefix = std::env::var("AZUMI_PREFIX").unwrap_or_default();
+    let path = format!("/{}/_azumi/update_template", prefix.trim_matches('/'))
+        .replace("//", "/");
+
     let mut success = false;
     for (id, parts) in templates {
         let payload = serde_json::json!({ "id": id, "parts": parts }).to_string();
-        if send_raw_post(port, "/_azumi/update_template", &payload) {
+        if send_raw_post(port, &path, &payload) {
             success = true;
         } else {
             return Ok(false);
@@ -0,0 +1,249 @@
+//! # Parent/Child Messaging
+//!
+//! Live components nested inside each other keep isolated state: an action
+//! only ever sees the state of its nearest `az-scope`. To let a child update
+//! its parent (e.g. a row deleting itself from a list), a child live method
+//! marked `#[live(emit)]` *returns an event*:
+//!
+//! ```rust,ignore
+//! #[azumi::live_impl(component = "row_view")]
+//! impl Row {
+//!     #[live(emit)]
+//!     pub fn delete(&mut self) -> RowEvent {
+//!         RowEvent::Deleted(self.id)
+//!     }
+//! }
+//!
+//! #[azumi::live_impl(component = "list_view")]
+//! impl TodoList {
+//!     #[azumi::on_emit(Row)]
+//!     pub fn row_event(&mut self, event: RowEvent) {
+//!         match event {
+//!             RowEvent::Deleted(id) => self.rows.retain(|r| r.id != id),
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Methods without `#[live(emit)]` may still return values; nothing is sent
+//! to the parent for them.
+//!
+//! The child handler signs the event together with the emitting struct, its
+//! id and the structs enclosing it on the page (sent by the client in
+//! [`PARENTS_HEADER`]), and sends it back in the [`EMIT_HEADER`] response
+//! header. The client passes the signed event unchanged in an
+//! [`EmitEnvelope`] to the nearest ancestor component that handles events
+//! from that child (`/_azumi/action/{Parent}/__emit/{Child}`), and morphs the
+//! ancestor with the HTML it re-renders. The ancestor rejects events that
+//! were not signed by the server, come from another struct or were emitted
+//! under other parents, so a client cannot forge them. Ancestors without a
+//! handler answer 404 and the event bubbles further up. Handlers marked
+//! `#[live(emit)]` may return an event of their own to keep bubbling.
+//!
+//! Signed events are not single-use: until the signature expires (one hour,
+//! like live state) the same event can be sent again, to the same ancestor
+//! or another instance of its struct. Handlers should apply events
+//! idempotently (e.g. remove row 2 rather than the last row).
+//!
+//! Mark a field with `#[live(id)]` to give each child an explicit `az-id`; it
+//! is forwarded as [`Emitted::source_id`].
+
+use axum::http::HeaderValue;
+use base64::{engine::general_purpose, Engine as _};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Response header carrying an emitted event (base64-encoded signed event)
+pub const EMIT_HEADER: &str = "x-azumi-emit";
+
+/// Request header listing the `az-struct` names of the live components
+/// enclosing the one an action runs on, nearest first (`Board,Page`)
+pub const PARENTS_HEADER: &str = "x-azumi-parents";
+
+/// Prefix of the signed JSON, so an event never verifies as live state (and
+/// state never as an event). The `.` keeps it from reading as the user id of
+/// [`crate::security::sign_state_for_user`].
+const SIGNED_PREFIX: &str = "azumi.emit:";
+
+/// Request body sent by the client to an ancestor's emit handler
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EmitEnvelope {
+    /// Signed state of the ancestor component
+    pub state: String,
+    /// Signed event, copied from the [`EMIT_HEADER`] of the child response
+    pub event: String,
+}
+
+/// Why an emitted event was rejected
+#[derive(Debug)]
+pub enum EmitError {
+    /// Not signed by this server, tampered with or expired
+    Signature(String),
+    /// Emitted by another struct than the one the handler accepts
+    Source { expected: String, found: String },
+    /// Emitted by a component that was not inside the handler's struct
+    Parent {
+        parent: String,
+        parents: Vec<String>,
+    },
+    /// The event does not match the handler's event type
+    Event(serde_json::Error),
+}
+
+impl std::fmt::Display for EmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmitError::Signature(e) => write!(f, "invalid signature: {}", e),
+            EmitError::Source { expected, found } => {
+                write!(f, "event from {}, expected {}", found, expected)
+            }
+            EmitError::Parent { parent, parents } => {
+                write!(f, "event for [{}], not {}", parents.join(", "), parent)
+            }
+            EmitError::Event(e) => write!(f, "invalid event: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EmitError {}
+
+/// Event as signed by the child handler
+#[derive(Serialize, Deserialize)]
+struct SignedEvent {
+    source: String,
+    source_id: Option<String>,
+    parents: Vec<String>,
+    event: serde_json::Value,
+}
+
+impl EmitEnvelope {
+    /// Verify the signed event and decode it. `source` is the child struct
+    /// the handler accepts events from, `parent` the handler's own struct.
+    pub fn decode<E: DeserializeOwned>(
+        &self,
+        source: &str,
+        parent: &str,
+    ) -> Result<Emitted<E>, EmitError> {
+        verify(&self.event, source, parent)
+    }
+}
+
+/// An event together with the child that emitted it.
+///
+/// Take `Emitted<E>` instead of `E` in an `#[azumi::on_emit]` method to learn
+/// which child sent the event.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Emitted<E> {
+    pub source: String,
+    pub source_id: Option<String>,
+    pub event: E,
+}
+
+/// Sign an event emitted by `source` (the live struct name) with the
+/// component id `source_id`, for delivery to one of `parents`
+pub fn sign<E: Serialize>(
+    source: &str,
+    source_id: Option<String>,
+    parents: Vec<String>,
+    event: &E,
+) -> Result<String, serde_json::Error> {
+    let signed = SignedEvent {
+        source: source.to_string(),
+        source_id,
+        parents,
+        event: serde_json::to_value(event)?,
+    };
+    let json = format!("{}{}", SIGNED_PREFIX, serde_json::to_string(&signed)?);
+    Ok(general_purpose::STANDARD.encode(crate::security::sign_state(&json)))
+}
+
+/// Verify an event signed with [`sign`], emitted by `source` inside `parent`
+pub fn verify<E: DeserializeOwned>(
+    token: &str,
+    source: &str,
+    parent: &str,
+) -> Result<Emitted<E>, EmitError> {
+    let signed = open(token)?;
+    if signed.source != source {
+        return Err(EmitError::Source {
+            expected: source.to_string(),
+            found: signed.source,
+        });
+    }
+    if !signed.parents.iter().any(|p| p == parent) {
+        return Err(EmitError::Parent {
+            parent: parent.to_string(),
+            parents: signed.parents,
+        });
+    }
+    Ok(Emitted {
+        source: signed.source,
+        source_id: signed.source_id,
+        event: serde_json::from_value(signed.event).map_err(EmitError::Event)?,
+    })
+}
+
+/// Check the signature and domain of a token from [`sign`]
+fn open(token: &str) -> Result<SignedEvent, EmitError> {
+    let signed = general_purpose::STANDARD
+        .decode(token)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| EmitError::Signature("Invalid state".to_string()))?;
+    let json = crate::security::verify_state(&signed).map_err(EmitError::Signature)?;
+    let json = json
+        .strip_prefix(SIGNED_PREFIX)
+        .ok_or_else(|| EmitError::Signature("not an emitted event".to_string()))?;
+    serde_json::from_str(json).map_err(EmitError::Event)
+}
+
+/// Structs named in the [`PARENTS_HEADER`] of a request
+pub fn parents(headers: &axum::http::HeaderMap) -> Vec<String> {
+    headers
+        .get(PARENTS_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| {
+            v.split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Attach an event emitted by `source` to a live handler response.
+/// `request` holds the headers of the action request (for [`PARENTS_HEADER`]).
+///
+/// Events serializing to `null` (`()`, `None`) are not emitted.
+pub fn attach<E: Serialize>(
+    response: &mut axum::response::Response,
+    request: &axum::http::HeaderMap,
+    source: &str,
+    source_id: Option<String>,
+    event: &E,
+) {
+    let token = match serde_json::to_value(event) {
+        Ok(serde_json::Value::Null) => return,
+        Ok(_) => sign(source, source_id, parents(request), event),
+        Err(e) => Err(e),
+    };
+    match token {
+        Ok(token) => {
+            if let Ok(value) = HeaderValue::from_str(&token) {
+                response.headers_mut().insert(EMIT_HEADER, value);
+            }
+        }
+        Err(e) => eprintln!("⚠️  Azumi: failed to serialize emitted event: {}", e),
+    }
+}
+
+/// Read and verify the event emitted by `source` for `parent` in a live
+/// handler response, if any
+pub fn read<E: DeserializeOwned>(
+    headers: &axum::http::HeaderMap,
+    source: &str,
+    parent: &str,
+) -> Option<Emitted<E>> {
+    let token = headers.get(EMIT_HEADER)?.to_str().ok()?;
+    verify(token, source, parent).ok()
+}
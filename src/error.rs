@@ -0,0 +1,244 @@
+//! # Render Errors
+//!
+//! [`Component::render`] returns `fmt::Result`, which can't say what went
+//! wrong. Rendering that can fail for a reason of its own returns a
+//! [`RenderError`] instead, which keeps the typed error:
+//!
+//! - `?` works in `html!` expressions and `@let`: `{load_orders(&db)?}`
+//!   stops rendering with the `Err` of any `std::error::Error` type.
+//! - [`TryComponent`] is a component whose rendering can fail. It can be used
+//!   in `{...}` like any component.
+//!
+//! The error travels up to the nearest `@try` block, which renders its
+//! `@catch` fallback in place of everything the block rendered, and logs
+//! the error:
+//!
+//! ```rust,ignore
+//! html! {
+//!     <h1>"Orders"</h1>
+//!     @try {
+//!         <ul>
+//!             @for order in load_orders(&db)? { <li>{&order.title}</li> }
+//!         </ul>
+//!     } @catch(err) {
+//!         <p>"Orders are unavailable right now"</p>
+//!     }
+//! }
+//! ```
+//!
+//! `err` is the [`RenderError`]; [`RenderError::downcast_ref`] gets the typed
+//! error back. A panic in the block is caught the same way, as a [`Panic`].
+//! Without a `@try` block the error fails the whole render:
+//! [`try_render_to_string`] returns it, and an [`crate::Html`] response
+//! becomes a 500.
+
+use crate::Component;
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Why rendering failed: the typed error of a fallible render.
+///
+/// Any `std::error::Error` converts into it, so `?` works on them in
+/// `html!` and [`TryComponent::try_render`] (including `fmt::Error` from
+/// writes). It does not implement `Error` itself, which is what allows that.
+pub struct RenderError {
+    error: Box<dyn Error + Send + Sync>,
+    type_name: &'static str,
+}
+
+impl RenderError {
+    pub fn new<E: Error + Send + Sync + 'static>(error: E) -> Self {
+        RenderError {
+            error: Box::new(error),
+            type_name: std::any::type_name::<E>(),
+        }
+    }
+
+    /// Type name of the original error
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The original error, if it is an `E`
+    pub fn downcast_ref<E: Error + 'static>(&self) -> Option<&E> {
+        self.error.downcast_ref::<E>()
+    }
+
+    /// The original error
+    pub fn get_ref(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.error
+    }
+
+    /// Whether this is a plain `fmt::Error` (e.g. the writer failed)
+    fn is_fmt(&self) -> bool {
+        self.error.is::<fmt::Error>()
+    }
+}
+
+impl<E: Error + Send + Sync + 'static> From<E> for RenderError {
+    fn from(error: E) -> Self {
+        RenderError::new(error)
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl fmt::Debug for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RenderError({}: {:?})", self.type_name, self.error)
+    }
+}
+
+/// A panic while rendering, caught by a `@try` block
+#[derive(Debug)]
+pub struct Panic {
+    pub message: String,
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "panicked: {}", self.message)
+    }
+}
+
+impl Error for Panic {}
+
+/// Message of a panic payload
+pub(crate) fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// A component whose rendering can fail with a [`RenderError`]
+pub trait TryComponent {
+    fn try_render(&self, f: &mut fmt::Formatter<'_>) -> Result<(), RenderError>;
+}
+
+impl<T: TryComponent + ?Sized> TryComponent for &T {
+    fn try_render(&self, f: &mut fmt::Formatter<'_>) -> Result<(), RenderError> {
+        (**self).try_render(f)
+    }
+}
+
+thread_local! {
+    /// The error a failed render returned `fmt::Error` for
+    static PENDING: RefCell<Option<RenderError>> = const { RefCell::new(None) };
+}
+
+/// Hand `error` to whoever catches the render failure, as a `fmt::Error`
+pub(crate) fn raise(error: RenderError) -> fmt::Error {
+    if !error.is_fmt() {
+        PENDING.with(|pending| *pending.borrow_mut() = Some(error));
+    }
+    fmt::Error
+}
+
+/// The error behind the current render failure
+fn take() -> RenderError {
+    PENDING
+        .with(|pending| pending.borrow_mut().take())
+        .unwrap_or_else(|| RenderError::new(fmt::Error))
+}
+
+/// Run a render body that may use `?` (used by `html!`)
+#[doc(hidden)]
+pub fn run(body: impl FnOnce() -> Result<(), RenderError>) -> fmt::Result {
+    body().map_err(raise)
+}
+
+/// Render a [`TryComponent`] as part of a `Component` render (used by
+/// `{...}` in `html!`)
+pub fn render_fallible<C: TryComponent + ?Sized>(
+    component: &C,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    component.try_render(f).map_err(raise)
+}
+
+/// Render `component` into `buf`, returning the error that stopped it
+pub fn try_render_into<C, B>(component: &C, buf: &mut B) -> Result<(), RenderError>
+where
+    C: Component + ?Sized,
+    B: crate::render::RenderBuf,
+{
+    capture(|| crate::render::render_into(component, buf))
+}
+
+/// Run a render, returning the error behind its `fmt::Error`
+pub(crate) fn capture(render: impl FnOnce() -> fmt::Result) -> Result<(), RenderError> {
+    PENDING.with(|pending| pending.borrow_mut().take());
+    render().map_err(|_| take())
+}
+
+/// Render `component` to a `String`, returning the error that stopped it
+pub fn try_render_to_string<C: Component + ?Sized>(component: &C) -> Result<String, RenderError> {
+    let mut html = String::new();
+    try_render_into(component, &mut html)?;
+    Ok(html)
+}
+
+pub(crate) fn log(error: &RenderError, context: &str) {
+    eprintln!("⚠️  Azumi: {} ({}): {}", context, error.type_name(), error);
+}
+
+/// A `@try { ... } @catch(err) { ... }` block.
+///
+/// The handler is `FnOnce` (so that `@catch` can move values into its
+/// fallback), and is consumed by the first failed render. Rendering the
+/// same `Catch` again after a failure logs the error and renders nothing.
+/// `html!` views render once, so this only matters for a [`catch`] kept and
+/// rendered repeatedly; build a new one per render in that case.
+pub struct Catch<B, H> {
+    body: B,
+    handler: std::cell::Cell<Option<H>>,
+}
+
+/// Render `body`, or the component `handler` returns for its error if
+/// rendering `body` fails or panics (`@try`/`@catch` in Rust code). The
+/// handler runs at most once, see [`Catch`].
+pub fn catch<B, H, F>(body: B, handler: H) -> Catch<B, H>
+where
+    B: Component,
+    H: FnOnce(RenderError) -> F,
+    F: Component,
+{
+    Catch {
+        body,
+        handler: std::cell::Cell::new(Some(handler)),
+    }
+}
+
+impl<B, H, F> Component for Catch<B, H>
+where
+    B: Component,
+    H: FnOnce(RenderError) -> F,
+    F: Component,
+{
+    fn render(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Buffered, so that nothing of a failed body reaches the page
+        let rendered = catch_unwind(AssertUnwindSafe(|| try_render_to_string(&self.body)))
+            .unwrap_or_else(|payload| {
+                Err(RenderError::new(Panic {
+                    message: panic_message(payload),
+                }))
+            });
+        let error = match rendered {
+            Ok(html) => return f.write_str(&html),
+            Err(error) => error,
+        };
+        log(&error, "render error caught by @catch");
+        match self.handler.take() {
+            Some(handler) => handler(error).render(f),
+            None => Ok(()),
+        }
+    }
+}
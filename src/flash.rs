@@ -0,0 +1,273 @@
+//! # Flash Messages
+//!
+//! One-shot messages ("Order placed", "Could not save") shown after an
+//! action. Actions return them through [`crate::action::flash`], usually
+//! together with a navigation:
+//!
+//! ```rust,ignore
+//! use azumi::action::{flash, redirect};
+//! use azumi::flash::Level;
+//!
+//! #[azumi::action]
+//! pub async fn place_order(order: NewOrder) -> azumi::action::Reply {
+//!     let id = orders::create(order).await;
+//!     redirect(format!("/orders/{}", id)).flash(Level::Success, "Order placed")
+//! }
+//!
+//! #[azumi::action]
+//! pub async fn save_draft(draft: Draft) -> azumi::action::Reply {
+//!     drafts::save(draft).await;
+//!     flash(Level::Info, "Draft saved")
+//! }
+//! ```
+//!
+//! Without a navigation the messages travel in the [`FLASH_HEADER`] response
+//! header and the client runtime shows them at once. With a redirect or
+//! refresh they are stored in the signed `azumi_flash` cookie, which
+//! [`FlashLayer`] reads on the next page load (a `GET` accepting HTML outside
+//! `/_azumi/`) and then clears, so that [`flash_messages`] renders them on
+//! the new page:
+//!
+//! ```rust,ignore
+//! let app = azumi::action::register_actions(Router::new().route("/orders/:id", get(order)))
+//!     .layer(azumi::flash::FlashLayer);
+//!
+//! html! { <body>{azumi::flash::flash_messages()} ... </body> }
+//! ```
+//!
+//! Messages are rendered as `<div class="azumi-flash azumi-flash-{level}">`
+//! inside a `<div data-azumi-flashes>` container, by the server and by the
+//! client alike.
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, HeaderValue, Method, Request};
+use axum::response::Response;
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Response header carrying messages to show immediately (base64-encoded JSON)
+pub const FLASH_HEADER: &str = "x-azumi-flash";
+/// Cookie carrying messages across a redirect or refresh
+pub const FLASH_COOKIE: &str = "azumi_flash";
+
+/// Seconds a stored message waits for the next page
+const COOKIE_MAX_AGE: u32 = 300;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Success => "success",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Flash {
+    pub level: Level,
+    pub message: String,
+}
+
+impl Flash {
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Flash {
+            level,
+            message: message.into(),
+        }
+    }
+}
+
+tokio::task_local! {
+    static PENDING: Vec<Flash>;
+}
+
+/// Messages stored by the previous response, if [`FlashLayer`] is active
+pub fn pending() -> Vec<Flash> {
+    PENDING
+        .try_with(|flashes| flashes.clone())
+        .unwrap_or_default()
+}
+
+/// The pending messages, `None` outside [`FlashLayer`]
+pub(crate) fn current() -> Option<Vec<Flash>> {
+    PENDING.try_with(|flashes| flashes.clone()).ok()
+}
+
+/// Run `f` with `flashes` pending (a render on another thread)
+pub(crate) fn sync_scope<R>(flashes: Option<Vec<Flash>>, f: impl FnOnce() -> R) -> R {
+    match flashes {
+        Some(flashes) => PENDING.sync_scope(flashes, f),
+        None => f(),
+    }
+}
+
+/// [`sync_scope`] for a future (a render on another task)
+pub(crate) async fn scope<F: std::future::Future>(flashes: Option<Vec<Flash>>, f: F) -> F::Output {
+    match flashes {
+        Some(flashes) => PENDING.scope(flashes, f).await,
+        None => f.await,
+    }
+}
+
+/// The pending messages in a `<div data-azumi-flashes>` container. The
+/// container is always rendered so the client can add messages to it.
+pub fn flash_messages() -> impl crate::Component {
+    let flashes = pending();
+    crate::from_fn(move |f| {
+        write!(f, "<div data-azumi-flashes>")?;
+        for flash in &flashes {
+            let role = match flash.level {
+                Level::Warning | Level::Error => "alert",
+                Level::Info | Level::Success => "status",
+            };
+            write!(
+                f,
+                "<div class=\"azumi-flash azumi-flash-{}\" role=\"{}\">{}</div>",
+                flash.level.as_str(),
+                role,
+                crate::Escaped(&flash.message)
+            )?;
+        }
+        write!(f, "</div>")
+    })
+}
+
+/// `FLASH_HEADER` value for messages shown without navigation
+pub(crate) fn header_value(flashes: &[Flash]) -> Option<HeaderValue> {
+    let json = serde_json::to_string(flashes).ok()?;
+    HeaderValue::from_str(&STANDARD.encode(json)).ok()
+}
+
+/// MAC purpose tag of the flash cookie
+const MAC_PURPOSE: &[u8] = b"azumi-flash:";
+
+/// `Set-Cookie` storing messages for the next page load:
+/// `{payload}.{hmac("azumi-flash:" + payload)}`
+pub(crate) fn store_cookie(flashes: &[Flash]) -> Option<HeaderValue> {
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(flashes).ok()?);
+    let tag = URL_SAFE_NO_PAD.encode(crate::security::mac_bytes(MAC_PURPOSE, payload.as_bytes()));
+    HeaderValue::from_str(&format!(
+        "{}={}.{}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}",
+        FLASH_COOKIE, payload, tag, COOKIE_MAX_AGE
+    ))
+    .ok()
+}
+
+/// Messages from a signed `azumi_flash` cookie value
+fn read_cookie(value: &str) -> Option<Vec<Flash>> {
+    let (payload, tag) = value.split_once('.')?;
+    let tag = URL_SAFE_NO_PAD.decode(tag).ok()?;
+    if !crate::security::verify_mac(MAC_PURPOSE, payload.as_bytes(), &tag) {
+        return None;
+    }
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+}
+
+fn cookie_value(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == FLASH_COOKIE)
+        .map(|(_, value)| value.to_string())
+}
+
+fn sets_flash_cookie(response: &Response) -> bool {
+    response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.starts_with(FLASH_COOKIE) && v[FLASH_COOKIE.len()..].starts_with('='))
+}
+
+/// Whether `req` is a page load that can show the messages: a `GET`
+/// accepting HTML, outside the Azumi routes (actions, fetches from other
+/// tabs and asset requests leave the cookie alone)
+fn is_page_request(req: &Request<Body>) -> bool {
+    req.method() == Method::GET
+        && !req.uri().path().contains("/_azumi/")
+        && req
+            .headers()
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .any(|v| v.contains("text/html"))
+}
+
+/// Tower layer delivering stored messages to [`flash_messages`] and clearing
+/// the cookie once they have been handed to a page load
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FlashLayer;
+
+impl<S> tower_layer::Layer<S> for FlashLayer {
+    type Service = FlashService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FlashService { inner }
+    }
+}
+
+/// Service produced by [`FlashLayer`]
+#[derive(Clone, Debug)]
+pub struct FlashService<S> {
+    inner: S,
+}
+
+impl<S> tower_service::Service<Request<Body>> for FlashService<S>
+where
+    S: tower_service::Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // Take the service that was driven to readiness
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let cookie = cookie_value(req.headers()).filter(|_| is_page_request(&req));
+            let Some(cookie) = cookie else {
+                return inner.call(req).await;
+            };
+            let flashes = read_cookie(&cookie).unwrap_or_default();
+            let mut response = PENDING.scope(flashes, inner.call(req)).await?;
+            if !sets_flash_cookie(&response) {
+                let clear = format!(
+                    "{}=; Path=/; HttpOnly; SameSite=Lax; Max-Age=0",
+                    FLASH_COOKIE
+                );
+                if let Ok(value) = HeaderValue::from_str(&clear) {
+                    response.headers_mut().append(header::SET_COOKIE, value);
+                }
+            }
+            Ok(response)
+        })
+    }
+}
@@ -0,0 +1,198 @@
+//! # Out-of-Band Fragments
+//!
+//! An action normally replaces a single target: the `-> #id` of its
+//! `az-on` call, or the enclosing live component. Returning [`Fragments`]
+//! updates several parts of the page from one response instead:
+//!
+//! ```rust,ignore
+//! #[azumi::action]
+//! pub async fn add_to_cart(item: AddItem) -> Fragments {
+//!     let cart = cart::add(item.product_id).await;
+//!     Fragments::new()
+//!         .morph("#cart-badge", cart_badge(cart.len()))
+//!         .morph(format!("#product-{}", item.product_id), product_card(&cart, item.product_id))
+//! }
+//! ```
+//!
+//! The body is a sequence of `<template az-fragment="selector" az-swap="mode">`
+//! elements and the response carries the [`FRAGMENTS_HEADER`] header. The
+//! client applies each fragment to every element matching its selector, in
+//! order; selectors that match nothing are skipped.
+
+use crate::action::ActionResponse;
+use crate::Component;
+use axum::http::HeaderValue;
+use axum::response::{IntoResponse, Response};
+
+/// Response header marking a [`Fragments`] body (value: number of fragments)
+pub const FRAGMENTS_HEADER: &str = "x-azumi-fragments";
+
+/// How a fragment is applied to its target. The same modes are accepted
+/// after the target of an `az-on` call (`click call send -> #log beforeend`)
+/// and are checked by `html!` at compile time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Swap {
+    /// Morph the target (`outerHTML`) with Idiomorph, keeping focus and
+    /// unchanged nodes
+    #[default]
+    Morph,
+    /// Replace the children of the target
+    Inner,
+    /// Replace the target itself
+    Outer,
+    /// Insert before the target
+    BeforeBegin,
+    /// Insert before the first child of the target
+    AfterBegin,
+    /// Insert after the last child of the target
+    BeforeEnd,
+    /// Insert after the target
+    AfterEnd,
+    /// Remove the target; the HTML is ignored
+    Delete,
+    /// Leave the page unchanged
+    None,
+}
+
+impl Swap {
+    pub const ALL: [Swap; 9] = [
+        Swap::Morph,
+        Swap::Inner,
+        Swap::Outer,
+        Swap::BeforeBegin,
+        Swap::AfterBegin,
+        Swap::BeforeEnd,
+        Swap::AfterEnd,
+        Swap::Delete,
+        Swap::None,
+    ];
+
+    /// Name used in `az-swap` and `az-on`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Swap::Morph => "morph",
+            Swap::Inner => "inner",
+            Swap::Outer => "outer",
+            Swap::BeforeBegin => "beforebegin",
+            Swap::AfterBegin => "afterbegin",
+            Swap::BeforeEnd => "beforeend",
+            Swap::AfterEnd => "afterend",
+            Swap::Delete => "delete",
+            Swap::None => "none",
+        }
+    }
+}
+
+impl std::str::FromStr for Swap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Swap::ALL
+            .into_iter()
+            .find(|swap| swap.as_str() == s)
+            .ok_or_else(|| format!("unknown swap mode `{}`", s))
+    }
+}
+
+/// One rendered fragment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    pub target: String,
+    pub swap: Swap,
+    pub html: String,
+}
+
+/// Several fragments returned by one action
+#[derive(Debug, Clone, Default)]
+pub struct Fragments {
+    fragments: Vec<Fragment>,
+}
+
+impl Fragments {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Render `component` now and apply it to `target` (a CSS selector) with `swap`
+    pub fn add(mut self, target: impl Into<String>, swap: Swap, component: impl Component) -> Self {
+        self.fragments.push(Fragment {
+            target: target.into(),
+            swap,
+            html: crate::render_to_string(&component),
+        });
+        self
+    }
+
+    /// [`Swap::Morph`] `target` with `component`
+    pub fn morph(self, target: impl Into<String>, component: impl Component) -> Self {
+        self.add(target, Swap::Morph, component)
+    }
+
+    /// [`Swap::Inner`]: replace the children of `target`
+    pub fn inner(self, target: impl Into<String>, component: impl Component) -> Self {
+        self.add(target, Swap::Inner, component)
+    }
+
+    /// [`Swap::Outer`]: replace `target`
+    pub fn outer(self, target: impl Into<String>, component: impl Component) -> Self {
+        self.add(target, Swap::Outer, component)
+    }
+
+    /// [`Swap::BeforeEnd`]: append to the children of `target`
+    pub fn append(self, target: impl Into<String>, component: impl Component) -> Self {
+        self.add(target, Swap::BeforeEnd, component)
+    }
+
+    /// [`Swap::AfterBegin`]: prepend to the children of `target`
+    pub fn prepend(self, target: impl Into<String>, component: impl Component) -> Self {
+        self.add(target, Swap::AfterBegin, component)
+    }
+
+    /// [`Swap::Delete`]: remove `target`
+    pub fn delete(mut self, target: impl Into<String>) -> Self {
+        self.fragments.push(Fragment {
+            target: target.into(),
+            swap: Swap::Delete,
+            html: String::new(),
+        });
+        self
+    }
+
+    pub fn fragments(&self) -> &[Fragment] {
+        &self.fragments
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fragments.is_empty()
+    }
+
+    /// The response body: one `<template>` per fragment
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        for fragment in &self.fragments {
+            out.push_str(&format!(
+                r#"<template az-fragment="{}" az-swap="{}">{}</template>"#,
+                crate::Escaped(&fragment.target),
+                fragment.swap.as_str(),
+                fragment.html
+            ));
+        }
+        out
+    }
+}
+
+impl IntoResponse for Fragments {
+    fn into_response(self) -> Response {
+        let mut response = axum::response::Html(self.to_html()).into_response();
+        response
+            .headers_mut()
+            .insert(FRAGMENTS_HEADER, HeaderValue::from(self.fragments.len()));
+        response
+    }
+}
+
+impl ActionResponse for Fragments {
+    fn into_action_response(self) -> Response {
+        self.into_response()
+    }
+}
@@ -0,0 +1,312 @@
+//! # Undo/Redo History
+//!
+//! `#[azumi::live(history = 20)]` keeps a bounded stack of previous signed
+//! states for a live struct and registers two extra live methods, `undo` and
+//! `redo`, at `/_azumi/action/{Struct}/undo` and `/_azumi/action/{Struct}/redo`.
+//! Both carry the prediction `@undo` / `@redo`, which the client executes by
+//! restoring the prior state immediately and rolls back if the server refuses.
+//!
+//! ```rust,ignore
+//! #[azumi::live(history = 20)]
+//! pub struct Editor { pub text: String }
+//!
+//! // in the component:
+//! <button on:click={state.undo} data-predict={azumi::get_prediction(state, "undo")}>
+//!     "Undo"
+//! </button>
+//! ```
+//!
+//! Where the stack lives depends on the storage mode:
+//!
+//! - [`HistoryStorage::Client`] (default): the browser keeps the previous
+//!   signed states of each component and sends the one to restore as the body
+//!   of `undo`/`redo`. Nothing is stored on the server; the signature proves
+//!   the state was produced by the server.
+//! - [`HistoryStorage::Server`] (`history_storage = "server"`): the generated
+//!   handlers keep the stack in process memory, keyed by the struct name and
+//!   the per-component id the client sends in the [`HISTORY_HEADER`] header.
+//!   Only validly signed states up to [`MAX_SERVER_STATE_BYTES`] are stored,
+//!   and the least recently used histories are dropped once there are
+//!   [`MAX_SERVER_HISTORIES`] of them or they hold [`MAX_SERVER_BYTES`].
+//!
+//! Restored states go through the same signature and expiry checks as any
+//! other state, so entries older than the signing window can no longer be
+//! restored. The struct also needs `#[azumi::live_impl]`, which provides the
+//! rendering used by `undo`/`redo` ([`crate::LiveRender`]). Its own methods
+//! cannot be named `undo` or `redo` while history is on:
+//!
+//! ```compile_fail,E0080
+//! # use azumi::{html, Component};
+//! #[azumi::live(history = 10)]
+//! pub struct Editor {
+//!     pub text: String,
+//! }
+//!
+//! #[azumi::live_impl(component = "editor_view")]
+//! impl Editor {
+//!     // error: `Editor::undo` collides with the `undo` that #[azumi::live(history = N)] generates
+//!     pub fn undo(&mut self) {
+//!         self.text.pop();
+//!     }
+//! }
+//!
+//! #[azumi::component]
+//! pub fn editor_view<'a>(state: &'a Editor) -> impl Component + 'a {
+//!     html! { <p>{&state.text}</p> }
+//! }
+//! # fn main() {}
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, OnceLock};
+
+/// Request header carrying the client's history id (server storage)
+pub const HISTORY_HEADER: &str = "x-azumi-history";
+
+/// Maximum number of component histories kept by the server store.
+/// The least recently used history is dropped first.
+pub const MAX_SERVER_HISTORIES: usize = 10_000;
+
+/// Largest signed state the server store keeps; bigger states are not
+/// recorded, and undo/redo requests sending one are refused
+pub const MAX_SERVER_STATE_BYTES: usize = 16 * 1024;
+
+/// Total size of the states in the server store before the least recently
+/// used histories are dropped
+pub const MAX_SERVER_BYTES: usize = 64 * 1024 * 1024;
+
+/// Where undo/redo stacks are kept
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryStorage {
+    Client,
+    Server,
+}
+
+impl HistoryStorage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HistoryStorage::Client => "client",
+            HistoryStorage::Server => "server",
+        }
+    }
+}
+
+/// History configuration of a live struct.
+/// Implemented by `#[azumi::live]` (with `CAPACITY = 0` unless `history = N`).
+pub trait LiveHistory {
+    /// Number of previous states kept (0 disables history)
+    const CAPACITY: usize = 0;
+    const STORAGE: HistoryStorage = HistoryStorage::Client;
+}
+
+struct Stacks {
+    past: VecDeque<String>,
+    future: Vec<String>,
+    /// Value of [`ServerStore::uses`] when last used
+    used: u64,
+}
+
+impl Stacks {
+    fn bytes(&self) -> usize {
+        self.past.iter().chain(&self.future).map(String::len).sum()
+    }
+}
+
+#[derive(Default)]
+struct ServerStore {
+    stacks: HashMap<String, Stacks>,
+    /// Total length of the stored states
+    bytes: usize,
+    /// Number of accesses so far, ordering histories by use
+    uses: u64,
+}
+
+impl ServerStore {
+    /// Stacks of `key`, marked as used now
+    fn stacks(&mut self, key: &str) -> &mut Stacks {
+        if !self.stacks.contains_key(key) && self.stacks.len() >= MAX_SERVER_HISTORIES {
+            self.evict(key);
+        }
+        self.uses += 1;
+        let stacks = self
+            .stacks
+            .entry(key.to_string())
+            .or_insert_with(|| Stacks {
+                past: VecDeque::new(),
+                future: Vec::new(),
+                used: 0,
+            });
+        stacks.used = self.uses;
+        stacks
+    }
+
+    /// Update the total after the stacks of `key` changed from `before`
+    /// bytes, dropping other histories while over [`MAX_SERVER_BYTES`]
+    fn resize(&mut self, key: &str, before: usize) {
+        let after = self.stacks.get(key).map_or(0, Stacks::bytes);
+        self.bytes = self.bytes - before + after;
+        while self.bytes > MAX_SERVER_BYTES && self.evict(key) {}
+    }
+
+    /// Drop the least recently used history other than `keep`
+    fn evict(&mut self, keep: &str) -> bool {
+        let oldest = self
+            .stacks
+            .iter()
+            .filter(|(key, _)| key.as_str() != keep)
+            .min_by_key(|(_, stacks)| stacks.used)
+            .map(|(key, _)| key.clone());
+        let Some(stacks) = oldest.and_then(|key| self.stacks.remove(&key)) else {
+            return false;
+        };
+        self.bytes -= stacks.bytes();
+        true
+    }
+}
+
+static SERVER_STORE: OnceLock<Mutex<ServerStore>> = OnceLock::new();
+
+fn with_store<R>(f: impl FnOnce(&mut ServerStore) -> R) -> R {
+    let store = SERVER_STORE.get_or_init(|| Mutex::new(ServerStore::default()));
+    let mut guard = store.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut guard)
+}
+
+fn history_key<S: crate::LiveStateMetadata>(headers: &axum::http::HeaderMap) -> Option<String> {
+    let id = headers.get(HISTORY_HEADER)?.to_str().ok()?;
+    if id.is_empty() || id.len() > 128 {
+        return None;
+    }
+    Some(format!("{}:{}", S::struct_name(), id))
+}
+
+/// Record the state a live method started from (server storage only).
+///
+/// Called by the generated handlers after the method ran. `signed_before` is
+/// the signed state the client sent; it is pushed only when the method
+/// actually changed the state, and any redo entries are discarded.
+pub fn record<S>(headers: &axum::http::HeaderMap, signed_before: &str, json_before: &str, state: &S)
+where
+    S: LiveHistory + crate::LiveStateMetadata + serde::Serialize,
+{
+    if S::CAPACITY == 0 || S::STORAGE != HistoryStorage::Server {
+        return;
+    }
+    let Some(key) = history_key::<S>(headers) else {
+        return;
+    };
+    if signed_before.len() > MAX_SERVER_STATE_BYTES {
+        return;
+    }
+    if serde_json::to_string(state).is_ok_and(|after| after == json_before) {
+        return;
+    }
+    with_store(|store| {
+        let stacks = store.stacks(&key);
+        let before = stacks.bytes();
+        stacks.past.push_back(signed_before.to_string());
+        while stacks.past.len() > S::CAPACITY {
+            stacks.past.pop_front();
+        }
+        stacks.future.clear();
+        store.resize(&key, before);
+    });
+}
+
+/// Direction of a history step
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    Undo,
+    Redo,
+}
+
+/// Why a history step produced no state to restore
+#[derive(Debug, PartialEq, Eq)]
+pub enum StepError {
+    /// The requested stack is empty (or there is no history id)
+    Empty,
+    /// The current state sent with a server-storage step is not a validly
+    /// signed state, or is larger than [`MAX_SERVER_STATE_BYTES`]
+    Rejected(String),
+}
+
+/// Resolve the signed state to restore for `undo`/`redo`.
+///
+/// With client storage the body already *is* the state to restore. With
+/// server storage the body is the current state: it is checked like any
+/// other state, moved to the opposite stack, and the top of the requested
+/// stack is returned.
+pub fn step<S>(
+    headers: &axum::http::HeaderMap,
+    body: String,
+    step: Step,
+) -> Result<String, StepError>
+where
+    S: LiveHistory + crate::LiveStateMetadata,
+{
+    if S::STORAGE != HistoryStorage::Server {
+        return Ok(body);
+    }
+    let key = history_key::<S>(headers).ok_or(StepError::Empty)?;
+    if body.len() > MAX_SERVER_STATE_BYTES {
+        return Err(StepError::Rejected("State too large".to_string()));
+    }
+    crate::security::verify_state(&body).map_err(StepError::Rejected)?;
+    with_store(|store| {
+        let stacks = store.stacks(&key);
+        let before = stacks.bytes();
+        let restored = match step {
+            Step::Undo => {
+                let previous = stacks.past.pop_back().ok_or(StepError::Empty)?;
+                stacks.future.push(body);
+                previous
+            }
+            Step::Redo => {
+                let next = stacks.future.pop().ok_or(StepError::Empty)?;
+                stacks.past.push_back(body);
+                while stacks.past.len() > S::CAPACITY {
+                    stacks.past.pop_front();
+                }
+                next
+            }
+        };
+        store.resize(&key, before);
+        Ok(restored)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_evicts_least_recently_used() {
+        let mut store = ServerStore::default();
+        for i in 0..MAX_SERVER_HISTORIES {
+            store.stacks(&format!("Tally:{}", i));
+        }
+        // The first history is used again, so the second is the oldest
+        store.stacks("Tally:0");
+        store.stacks("Tally:new");
+        assert_eq!(store.stacks.len(), MAX_SERVER_HISTORIES);
+        assert!(store.stacks.contains_key("Tally:0"));
+        assert!(!store.stacks.contains_key("Tally:1"));
+    }
+
+    #[test]
+    fn test_store_stays_within_byte_budget() {
+        let mut store = ServerStore::default();
+        let state = "s".repeat(MAX_SERVER_STATE_BYTES);
+        let histories = MAX_SERVER_BYTES / MAX_SERVER_STATE_BYTES + 1;
+        for i in 0..histories {
+            let key = format!("Editor:{}", i);
+            store.stacks(&key).past.push_back(state.clone());
+            store.resize(&key, 0);
+        }
+        assert!(store.bytes <= MAX_SERVER_BYTES);
+        assert!(!store.stacks.contains_key("Editor:0"));
+        assert!(store
+            .stacks
+            .contains_key(&format!("Editor:{}", histories - 1)));
+    }
+}
@@ -173,12 +173,23 @@ pub fn push_style_update(scope_id: &str, css: &str) {
 ///
 /// If deploying to production with this enabled, ensure only localhost can access these routes.
 pub fn router<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    router_at("")
+}
+
+/// [`router`] with both endpoints under `prefix` (e.g. `/app/_azumi/live_reload`)
+pub fn router_at<S>(prefix: &str) -> Router<S>
 where
     S: Clone + Send + Sync + 'static,
 {
     Router::new()
-        .route("/_azumi/live_reload", get(ws_handler))
-        .route("/_azumi/update_template", post(update_template_handler))
+        .route(&format!("{}/_azumi/live_reload", prefix), get(ws_handler))
+        .route(
+            &format!("{}/_azumi/update_template", prefix),
+            post(update_template_handler),
+        )
         .layer(axum::middleware::from_fn(check_dev_token))
 }
 
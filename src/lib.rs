@@ -1,25 +1,68 @@
 pub mod prelude {
+    #[cfg(feature = "axum")]
     pub use crate::action::Action;
     pub use crate::{
         action, azumi_script, component, head, html, live, live_impl, page, predict,
-        session_cleanup_script, AzumiScript, Component, escape_css_string, from_fn, FnComponent,
-        TrustedHtml,
+        session_cleanup_script, use_context, AzumiScript, Component, escape_css_string, from_fn,
+        FnComponent, TrustedHtml,
     };
 }
 
-pub use azumi_macros::{action, component, head, html, live, live_impl, page, predict};
+pub use azumi_macros::{
+    action, component, head, html, live, live_impl, on_emit, page, predict, route,
+};
+#[cfg(feature = "axum")]
 pub mod action;
+#[cfg(feature = "actix-web")]
+pub mod actix;
+pub mod cache;
 pub mod context;
+#[cfg(feature = "axum")]
+pub mod csrf;
+#[cfg(feature = "axum")]
+pub mod emit;
+pub mod error;
+#[cfg(feature = "axum")]
+pub mod flash;
+#[cfg(feature = "axum")]
+pub mod fragments;
+#[cfg(feature = "axum")]
+pub mod history;
 #[cfg(feature = "devtools")]
 pub mod hot_reload;
+#[cfg(feature = "axum")]
+pub mod openapi;
+#[cfg(feature = "axum")]
+pub mod rate_limit;
+pub mod render;
+#[cfg(feature = "axum")]
+pub mod response;
 pub mod script;
 pub mod security;
+pub mod slot;
+pub mod stream;
+pub mod suspense;
+#[cfg(feature = "axum")]
+pub mod telemetry;
+pub mod validate;
+pub use azumi_macros::{ApiSchema, Validate};
 pub use inventory;
 #[cfg(feature = "devtools")]
 pub mod devtools;
 
+/// The axum version Azumi is built on. Generated handlers use this path, so
+/// applications don't need their own `axum` dependency.
+#[cfg(feature = "axum")]
+pub use axum;
+
 pub mod seo;
 pub use script::{AzumiScript, session_cleanup_script, TrustedHtml};
+pub use context::{provide, use_context};
+pub use error::{try_render_to_string, RenderError, TryComponent};
+#[cfg(feature = "axum")]
+pub use action::{routes, AzumiRoutes};
+#[cfg(feature = "axum")]
+pub use response::Html;
 
 #[cfg(feature = "test-utils")]
 pub mod test;
@@ -70,6 +113,21 @@ pub const AZUMI_RULES: &[&str] = &[
 
 pub trait Component {
     fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
+
+    /// Lower bound on the rendered length in bytes, used to size buffers
+    /// (see [`render::render_into`])
+    fn size_hint(&self) -> usize {
+        0
+    }
+
+    /// Wrap in [`Html`] to return from an axum handler
+    #[cfg(feature = "axum")]
+    fn into_html(self) -> Html<Self>
+    where
+        Self: Sized,
+    {
+        Html::new(self)
+    }
 }
 
 /// Metadata for live state (predictions and namespacing)
@@ -98,6 +156,237 @@ pub trait LiveState:
         };
         crate::security::sign_state(&json)
     }
+
+    /// Explicit component id rendered as `az-id` (from a `#[live(id)]` field)
+    fn live_id(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Request context handed to live state hooks such as [`LiveHydrate::hydrate`]
+#[cfg(feature = "axum")]
+pub struct LiveContext {
+    pub parts: axum::http::request::Parts,
+}
+
+#[cfg(feature = "axum")]
+impl LiveContext {
+    pub fn new(parts: axum::http::request::Parts) -> Self {
+        Self { parts }
+    }
+
+    /// Request headers (cookies, auth tokens, ...)
+    pub fn headers(&self) -> &axum::http::HeaderMap {
+        &self.parts.headers
+    }
+
+    /// Clone a value installed with `axum::Extension` (DB pools, current user, ...)
+    pub fn extension<T: Clone + Send + Sync + 'static>(&self) -> Option<T> {
+        self.parts.extensions.get::<T>().cloned()
+    }
+
+    /// `az-mount` URL of the component the request targets, sent by the
+    /// client in [`MOUNT_HEADER`]
+    pub fn mount_url(&self) -> Option<&str> {
+        self.parts.headers.get(MOUNT_HEADER)?.to_str().ok()
+    }
+}
+
+/// Reloads `#[live(server)]` fields after the signed state has been verified.
+///
+/// Server-only fields are never serialized into `az-scope`, so every live
+/// handler deserializes them with [`LiveServerField::initial`] (their
+/// `Default`) and then calls `hydrate` before running the method. Return
+/// `Err(response)` to reject the request (e.g. `403` when the user lost a
+/// permission).
+///
+/// `#[azumi::live]` implements this as a no-op for structs without server
+/// fields. Structs with server fields must implement it:
+///
+/// ```rust,ignore
+/// #[azumi::live]
+/// pub struct Board {
+///     pub cards: Vec<String>,
+///     #[live(server)]
+///     pub can_edit: bool,
+/// }
+///
+/// impl azumi::LiveHydrate for Board {
+///     async fn hydrate(&mut self, ctx: &azumi::LiveContext) -> Result<(), axum::response::Response> {
+///         self.can_edit = ctx.extension::<CurrentUser>().is_some_and(|u| u.is_admin);
+///         Ok(())
+///     }
+/// }
+/// ```
+#[cfg(feature = "axum")]
+pub trait LiveHydrate {
+    fn hydrate(
+        &mut self,
+        _ctx: &LiveContext,
+    ) -> impl std::future::Future<Output = Result<(), axum::response::Response>> + Send {
+        async { Ok(()) }
+    }
+}
+
+/// Placeholder value of a `#[live(server)]` field until
+/// [`LiveHydrate::hydrate`] reloads it.
+///
+/// Implemented for every `Default` type. Values without a default, such as a
+/// database pool, go in an `Option` that `hydrate` fills in:
+///
+/// ```compile_fail,E0277
+/// #[derive(Clone, Debug)]
+/// pub struct Pool;
+///
+/// #[azumi::live]
+/// pub struct Report {
+///     pub title: String,
+///     #[live(server)]
+///     pub db: Pool, // error: `#[live(server)]` field type `Pool` must implement `Default`
+/// }
+/// ```
+#[cfg(feature = "axum")]
+#[diagnostic::on_unimplemented(
+    message = "`#[live(server)]` field type `{Self}` must implement `Default`",
+    label = "server-only field without a `Default` value",
+    note = "`#[live(server)]` fields are not part of the signed state; they start as `Default::default()` until `LiveHydrate::hydrate` reloads them",
+    note = "wrap values without a default in `Option<_>` (e.g. `Option<PgPool>`) and set them in `hydrate`"
+)]
+pub trait LiveServerField {
+    fn initial() -> Self;
+}
+
+#[cfg(feature = "axum")]
+impl<T: Default> LiveServerField for T {
+    fn initial() -> Self {
+        T::default()
+    }
+}
+
+/// Used by `#[azumi::live]` as the serde default of `#[live(server)]` fields
+#[cfg(feature = "axum")]
+#[doc(hidden)]
+pub fn __live_server_field<T: LiveServerField>() -> T {
+    T::initial()
+}
+
+/// Lifecycle of a live component that can be mounted directly at a URL.
+///
+/// Enabled with `mount` on the impl block:
+///
+/// ```rust,ignore
+/// #[azumi::live_impl(component = "todo_view", mount = "/todos/:list_id")]
+/// impl TodoList { /* live methods */ }
+///
+/// impl azumi::LiveComponent for TodoList {
+///     type Params = u64; // path params (or query params when the path has none)
+///
+///     async fn mount(list_id: u64, ctx: &azumi::LiveContext) -> Result<Self, axum::response::Response> {
+///         let db = ctx.extension::<Db>().expect("Db extension");
+///         Ok(TodoList { items: db.items(list_id).await })
+///     }
+/// }
+/// ```
+///
+/// `GET /todos/42` then calls `mount` and renders the component. The root
+/// `az-scope` element carries `az-mount="/todos/42"` so the client can
+/// re-mount the component when its signed state is rejected (e.g. expired).
+///
+/// For mounted components every generated live handler also runs
+/// `on_action` after `hydrate` and before the method, and `after_render`
+/// once the component HTML has been produced.
+#[cfg(feature = "axum")]
+pub trait LiveComponent: LiveState + LiveHydrate + Sized {
+    type Params: serde::de::DeserializeOwned + Send + 'static;
+
+    fn mount(
+        params: Self::Params,
+        ctx: &LiveContext,
+    ) -> impl std::future::Future<Output = Result<Self, axum::response::Response>> + Send;
+
+    /// Runs before every live method. Return `Err(response)` to reject the call.
+    fn on_action(
+        &mut self,
+        _method: &'static str,
+        _ctx: &LiveContext,
+    ) -> impl std::future::Future<Output = Result<(), axum::response::Response>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Runs after the component has been rendered (mount and live methods)
+    fn after_render(&self, _html: &mut String, _ctx: &LiveContext) {}
+}
+
+/// Runs the handler hooks of a live struct and renders it the way its
+/// generated live handlers do.
+///
+/// Implemented by `#[azumi::live_impl]`. Used by handlers generated outside
+/// the impl block, such as `undo`/`redo` from `#[azumi::live(history = N)]`.
+#[cfg(feature = "axum")]
+pub trait LiveRender: LiveState + LiveHydrate {
+    /// `hydrate`, then `LiveComponent::on_action` for mounted components
+    fn before_action(
+        &mut self,
+        method: &'static str,
+        ctx: &LiveContext,
+    ) -> impl std::future::Future<Output = Result<(), axum::response::Response>> + Send;
+
+    /// The component HTML (or the JSON state when there is no component)
+    fn render_live(&self, ctx: &LiveContext) -> axum::response::Response;
+}
+
+/// Request header carrying the `az-mount` URL of the component a live
+/// request targets, so the re-rendered root keeps it
+pub const MOUNT_HEADER: &str = "x-azumi-mount";
+
+/// Response header set when the signed state of a live request is rejected:
+/// invalid signature, expired, or no longer matching the struct (e.g. after
+/// a deploy). The client re-mounts components that have an `az-mount` URL.
+pub const STALE_STATE_HEADER: &str = "x-azumi-stale-state";
+
+/// URL a live component was mounted at, provided around its render by
+/// [`render_mounted`] and taken by the first live component root
+#[derive(Clone)]
+struct MountUrl(std::sync::Arc<std::sync::Mutex<Option<String>>>);
+
+/// Render a live component with the URL it is mounted at, so its root
+/// `az-scope` element carries `az-mount` and the client knows where to
+/// re-mount it. Live components nested inside it are not tagged.
+pub fn render_mounted<C: Component + ?Sized>(component: &C, url: Option<&str>) -> String {
+    let _mount = url.map(|url| {
+        context::enter(MountUrl(std::sync::Arc::new(std::sync::Mutex::new(Some(
+            url.to_string(),
+        )))))
+    });
+    render_to_string(component)
+}
+
+/// Used by live component roots: writes `az-mount` inside [`render_mounted`]
+#[doc(hidden)]
+pub fn __live_mount_attr(f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let url = use_context::<MountUrl>()
+        .and_then(|MountUrl(url)| url.lock().ok().and_then(|mut url| url.take()));
+    match url {
+        Some(url) => write!(f, " az-mount=\"{}\"", Escaped(&url)),
+        None => Ok(()),
+    }
+}
+
+/// Used by live handlers: a rejected signed state, marked with
+/// [`STALE_STATE_HEADER`]
+#[cfg(feature = "axum")]
+#[doc(hidden)]
+pub fn __stale_state(
+    status: axum::http::StatusCode,
+    message: String,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    let mut response = (status, message).into_response();
+    response.headers_mut().insert(
+        STALE_STATE_HEADER,
+        axum::http::HeaderValue::from_static("true"),
+    );
+    response
 }
 
 /// Runtime helper to look up a prediction for a method on a state
@@ -172,35 +461,73 @@ impl<T: Component + ?Sized> Component for &T {
     fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         (**self).render(f)
     }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
 }
 
 impl<T: Component + ?Sized> Component for Box<T> {
     fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         (**self).render(f)
     }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
 }
 
 impl<T: Component + ?Sized> Component for std::rc::Rc<T> {
     fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         (**self).render(f)
     }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
 }
 
 impl<T: Component + ?Sized> Component for std::sync::Arc<T> {
     fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         (**self).render(f)
     }
+
+    fn size_hint(&self) -> usize {
+        (**self).size_hint()
+    }
+}
+
+/// Renders nothing for `None`, e.g. an optional slot
+impl<T: Component> Component for Option<T> {
+    fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Some(component) => component.render(f),
+            None => Ok(()),
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        self.as_ref().map_or(0, Component::size_hint)
+    }
 }
 
 impl Component for String {
     fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", Escaped(self))
+        write_escaped(f, self)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.len()
     }
 }
 
 impl Component for str {
     fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", Escaped(self))
+        write_escaped(f, self)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.len()
     }
 }
 
@@ -264,6 +591,7 @@ where
 {
     closure: std::cell::UnsafeCell<Option<F>>,
     rendered: std::cell::UnsafeCell<bool>,
+    size_hint: usize,
 }
 
 impl<F> FnOnceComponent<F>
@@ -278,8 +606,16 @@ where
         FnOnceComponent {
             closure: std::cell::UnsafeCell::new(Some(f)),
             rendered: std::cell::UnsafeCell::new(false),
+            size_hint: 0,
         }
     }
+
+    /// Set [`Component::size_hint`]. `html!` passes the length of its
+    /// static markup.
+    pub fn with_size_hint(mut self, bytes: usize) -> Self {
+        self.size_hint = bytes;
+        self
+    }
 }
 
 impl<F> Component for FnOnceComponent<F>
@@ -313,6 +649,10 @@ where
             Ok(())
         }
     }
+
+    fn size_hint(&self) -> usize {
+        self.size_hint
+    }
 }
 
 // NOTE: FnOnceComponent does NOT implement Send or Sync.
@@ -334,31 +674,50 @@ where
 }
 
 pub fn render_to_string<C: Component + ?Sized>(component: &C) -> String {
-    struct DisplayWrapper<'a, C: Component + ?Sized>(&'a C);
-    impl<'a, C: Component + ?Sized> std::fmt::Display for DisplayWrapper<'a, C> {
-        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-            self.0.render(f)
-        }
-    }
-    format!("{}", DisplayWrapper(component))
+    let mut html = String::new();
+    render::render_into(component, &mut html)
+        .expect("a Display implementation returned an error unexpectedly");
+    html
 }
 
 pub struct Escaped<T: std::fmt::Display>(pub T);
 
 impl<T: std::fmt::Display> std::fmt::Display for Escaped<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for c in self.0.to_string().chars() {
-            match c {
-                '<' => write!(f, "&lt;")?,
-                '>' => write!(f, "&gt;")?,
-                '&' => write!(f, "&amp;")?,
-                '"' => write!(f, "&quot;")?,
-                '\'' => write!(f, "&#x27;")?,
-                _ => write!(f, "{}", c)?,
+        // Escape as the value is formatted, without an intermediate String
+        struct EscapeWriter<'a, 'b>(&'a mut std::fmt::Formatter<'b>);
+        impl std::fmt::Write for EscapeWriter<'_, '_> {
+            fn write_str(&mut self, s: &str) -> std::fmt::Result {
+                write_escaped(self.0, s)
             }
         }
-        Ok(())
+        std::fmt::Write::write_fmt(&mut EscapeWriter(f), format_args!("{}", self.0))
+    }
+}
+
+/// Write `text` HTML-escaped. Runs of safe characters are written in one
+/// call; only the five special characters are replaced.
+pub fn write_escaped<W: std::fmt::Write + ?Sized>(out: &mut W, text: &str) -> std::fmt::Result {
+    let mut start = 0;
+    for (i, byte) in text.bytes().enumerate() {
+        let entity = match byte {
+            b'<' => "&lt;",
+            b'>' => "&gt;",
+            b'&' => "&amp;",
+            b'"' => "&quot;",
+            b'\'' => "&#x27;",
+            _ => continue,
+        };
+        if start < i {
+            out.write_str(&text[start..i])?;
+        }
+        out.write_str(entity)?;
+        start = i + 1;
+    }
+    if start < text.len() {
+        out.write_str(&text[start..])?;
     }
+    Ok(())
 }
 
 /// Escape a string for safe inclusion in a CSS property value.
@@ -386,23 +745,41 @@ pub fn escape_css_string(s: &str) -> String {
 
 // Smart Interpolation Machinery
 // Allows {} to handle both Components (render) and Display types (escape)
+//
+// `html!` calls `(&RenderWrapper(&value)).render_azumi(f)`. Method lookup
+// tries the receiver `&RenderWrapper` before auto-referencing it, so the
+// impls below are picked in order of their receiver level:
+// 1. `&RenderWrapper`: Component (inherent), then TryComponent (trait)
+// 2. `&&RenderWrapper`: Display
+// A type implementing both TryComponent and Display renders as a component.
 
 pub struct RenderWrapper<T>(pub T);
 
 impl<T: Component> RenderWrapper<T> {
     // Priority 1: Component (Render directly)
-    // This inherent method takes precedence over the trait implementation below
+    // This inherent method takes precedence over the trait implementations below
     pub fn render_azumi(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.0.render(f)
     }
 }
 
+pub trait TryRender {
+    fn render_azumi(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
+}
+
+// Priority 2: TryComponent (Render, failing with its error)
+impl<T: TryComponent> TryRender for RenderWrapper<T> {
+    fn render_azumi(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error::render_fallible(&self.0, f)
+    }
+}
+
 pub trait FallbackRender {
     fn render_azumi(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result;
 }
 
-// Priority 2: Display (Escape HTML)
-impl<T: std::fmt::Display> FallbackRender for RenderWrapper<T> {
+// Priority 3: Display (Escape HTML), one auto-reference further
+impl<T: std::fmt::Display> FallbackRender for &RenderWrapper<T> {
     fn render_azumi(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", Escaped(&self.0))
     }
@@ -669,7 +1046,13 @@ pub const AZUMI_JS: &str = include_str!("client.min.js");
 /// This is a Component, not a String. Use {azumi_script()} syntax (not @{azumi_script()})
 /// to render it directly without escaping.
 pub fn azumi_script() -> AzumiScript {
-    AzumiScript
+    AzumiScript::new("")
+}
+
+/// [`azumi_script`] for routes mounted under `prefix` with
+/// `azumi::routes().prefix(..)`
+pub fn azumi_script_at(prefix: &str) -> AzumiScript {
+    AzumiScript::new(prefix)
 }
 
 pub struct HotReloadClosure<'a>(pub &'a dyn Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result);
@@ -0,0 +1,281 @@
+//! # OpenAPI Export
+//!
+//! Builds an OpenAPI 3 document from the action registry
+//! ([`crate::action::registry`]): every `#[azumi::action]`, live method,
+//! emit handler, undo/redo and mount route, with its payload and response
+//! content types, live struct (as tag) and source location (`x-azumi-source`).
+//!
+//! Payload schemas come from `#[derive(azumi::ApiSchema)]`; payload types
+//! without it are documented without a schema.
+//!
+//! ```rust,ignore
+//! #[derive(Deserialize, azumi::ApiSchema)]
+//! pub struct Signup {
+//!     /// Display name
+//!     pub name: String,
+//!     pub age: Option<u32>,
+//! }
+//!
+//! let app = azumi::action::register_actions(Router::new())
+//!     .route("/openapi.json", azumi::openapi::route("My App", "1.0.0"));
+//! ```
+//!
+//! The derive follows `#[serde(rename = "..")]`, `#[serde(rename_all = "..")]`,
+//! `#[serde(skip)]` and `#[serde(default)]`; doc comments become
+//! descriptions. Live method bodies are signed states and are documented as
+//! opaque `text/plain`.
+
+use crate::action::{ActionEntry, ActionKind};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+
+/// Named schemas collected while building a document
+/// (`#/components/schemas/...`)
+#[derive(Default, Debug)]
+pub struct Components {
+    schemas: BTreeMap<String, Value>,
+}
+
+impl Components {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define `name` once (recursion-safe) and return a `$ref` to it
+    pub fn define(&mut self, name: &str, build: impl FnOnce(&mut Self) -> Value) -> Value {
+        if !self.schemas.contains_key(name) {
+            // Placeholder so recursive types stop here
+            self.schemas.insert(name.to_string(), Value::Null);
+            let schema = build(self);
+            self.schemas.insert(name.to_string(), schema);
+        }
+        json!({ "$ref": format!("#/components/schemas/{}", name) })
+    }
+
+    pub fn schemas(&self) -> &BTreeMap<String, Value> {
+        &self.schemas
+    }
+}
+
+/// JSON schema of a type. Implemented by `#[derive(azumi::ApiSchema)]`.
+pub trait ApiSchema {
+    fn api_schema(components: &mut Components) -> Value;
+
+    /// Whether a struct field of this type must be present
+    fn api_required() -> bool {
+        true
+    }
+}
+
+macro_rules! impl_api_schema {
+    ($($ty:ty => $schema:tt),* $(,)?) => {
+        $(impl ApiSchema for $ty {
+            fn api_schema(_: &mut Components) -> Value {
+                json!($schema)
+            }
+        })*
+    };
+}
+
+impl_api_schema! {
+    String => { "type": "string" },
+    &str => { "type": "string" },
+    char => { "type": "string", "minLength": 1, "maxLength": 1 },
+    bool => { "type": "boolean" },
+    i8 => { "type": "integer", "format": "int32" },
+    i16 => { "type": "integer", "format": "int32" },
+    i32 => { "type": "integer", "format": "int32" },
+    i64 => { "type": "integer", "format": "int64" },
+    isize => { "type": "integer", "format": "int64" },
+    u8 => { "type": "integer", "format": "int32", "minimum": 0 },
+    u16 => { "type": "integer", "format": "int32", "minimum": 0 },
+    u32 => { "type": "integer", "format": "int64", "minimum": 0 },
+    u64 => { "type": "integer", "format": "int64", "minimum": 0 },
+    usize => { "type": "integer", "format": "int64", "minimum": 0 },
+    f32 => { "type": "number", "format": "float" },
+    f64 => { "type": "number", "format": "double" },
+    Value => {},
+}
+
+impl<T: ApiSchema> ApiSchema for Option<T> {
+    fn api_schema(components: &mut Components) -> Value {
+        T::api_schema(components)
+    }
+
+    fn api_required() -> bool {
+        false
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for Vec<T> {
+    fn api_schema(components: &mut Components) -> Value {
+        json!({ "type": "array", "items": T::api_schema(components) })
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for Box<T> {
+    fn api_schema(components: &mut Components) -> Value {
+        T::api_schema(components)
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for HashMap<String, T> {
+    fn api_schema(components: &mut Components) -> Value {
+        json!({ "type": "object", "additionalProperties": T::api_schema(components) })
+    }
+}
+
+impl<T: ApiSchema> ApiSchema for BTreeMap<String, T> {
+    fn api_schema(components: &mut Components) -> Value {
+        json!({ "type": "object", "additionalProperties": T::api_schema(components) })
+    }
+}
+
+/// Used by the macros to record payload schemas.
+/// Types that do not implement [`ApiSchema`] fall back to no schema.
+pub struct SchemaOf<T>(PhantomData<T>);
+
+impl<T> SchemaOf<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        SchemaOf(PhantomData)
+    }
+}
+
+impl<T: ApiSchema> SchemaOf<T> {
+    // Takes precedence over FallbackSchema when T: ApiSchema
+    pub fn schema(&self, components: &mut Components) -> Option<Value> {
+        Some(T::api_schema(components))
+    }
+}
+
+pub trait FallbackSchema {
+    fn schema(&self, _components: &mut Components) -> Option<Value> {
+        None
+    }
+}
+
+impl<T> FallbackSchema for SchemaOf<T> {}
+
+/// `/items/:id/*rest` -> (`/items/{id}/{rest}`, `["id", "rest"]`)
+fn openapi_path(path: &str) -> (String, Vec<String>) {
+    let mut params = Vec::new();
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| {
+            match segment
+                .strip_prefix(':')
+                .or_else(|| segment.strip_prefix('*'))
+            {
+                Some(name) => {
+                    params.push(name.to_string());
+                    format!("{{{}}}", name)
+                }
+                None => segment.to_string(),
+            }
+        })
+        .collect();
+    (segments.join("/"), params)
+}
+
+fn operation(entry: &ActionEntry, components: &mut Components) -> Value {
+    let operation_id = match (entry.kind, entry.live_struct) {
+        (ActionKind::LiveEmit, Some(live)) => format!("{}_emit_{}", live, entry.name),
+        (_, Some(live)) => format!("{}_{}", live, entry.name),
+        _ => entry.name.to_string(),
+    };
+
+    let mut op = Map::new();
+    op.insert("operationId".into(), json!(operation_id));
+    op.insert(
+        "tags".into(),
+        json!([entry.live_struct.unwrap_or("actions")]),
+    );
+    if let Some(payload) = entry.payload_type {
+        op.insert(
+            "summary".into(),
+            json!(format!("{} ({})", entry.name, payload)),
+        );
+    }
+
+    let (_, params) = openapi_path(entry.path);
+    if !params.is_empty() {
+        let params: Vec<Value> = params
+            .iter()
+            .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }))
+            .collect();
+        op.insert("parameters".into(), Value::Array(params));
+    }
+
+    if let Some(content_type) = entry.content_type {
+        let schema = match (entry.payload_schema)(components) {
+            Some(schema) => schema,
+            None if content_type == "text/plain" => {
+                json!({ "type": "string", "description": "Signed live state" })
+            }
+            None => json!({}),
+        };
+        op.insert(
+            "requestBody".into(),
+            json!({ "required": true, "content": { content_type: { "schema": schema } } }),
+        );
+    }
+
+    let mut responses = Map::new();
+    responses.insert(
+        "200".into(),
+        json!({
+            "description": entry.response_type.unwrap_or("Success"),
+            "content": { entry.response_content_type: {} },
+        }),
+    );
+    if matches!(
+        entry.kind,
+        ActionKind::LiveMethod | ActionKind::LiveEmit | ActionKind::LiveHistory
+    ) {
+        responses.insert(
+            "400".into(),
+            json!({ "description": "Invalid or expired signed state" }),
+        );
+    }
+    op.insert("responses".into(), Value::Object(responses));
+
+    op.insert("x-azumi-kind".into(), json!(entry.kind.as_str()));
+    if !entry.file.is_empty() {
+        op.insert(
+            "x-azumi-source".into(),
+            json!(format!("{}:{}", entry.file, entry.line)),
+        );
+    }
+    Value::Object(op)
+}
+
+/// OpenAPI 3 document for every registered route
+pub fn document(title: &str, version: &str) -> Value {
+    let mut components = Components::new();
+    let mut paths = Map::new();
+    for entry in crate::action::registry() {
+        let (path, _) = openapi_path(entry.path);
+        let item = paths
+            .entry(path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(item) = item {
+            item.insert(
+                entry.http_method.to_ascii_lowercase(),
+                operation(entry, &mut components),
+            );
+        }
+    }
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": title, "version": version },
+        "paths": paths,
+        "components": { "schemas": components.schemas() },
+    })
+}
+
+/// `GET` route serving [`document`] as JSON
+pub fn route(title: &'static str, version: &'static str) -> axum::routing::MethodRouter<()> {
+    axum::routing::get(move || async move { axum::Json(document(title, version)) })
+}
@@ -0,0 +1,227 @@
+//! # Rate Limiting
+//!
+//! In-process token buckets for actions and live methods:
+//!
+//! ```rust,ignore
+//! #[azumi::action(rate_limit(5, per_minute))]
+//! pub async fn send_message(data: Message) -> impl Component { ... }
+//!
+//! #[azumi::live_impl(component = "post_view")]
+//! impl Post {
+//!     #[azumi::route(rate_limit(10, per_second, key = state))]
+//!     pub fn like(&mut self) { self.likes += 1; }
+//! }
+//! ```
+//!
+//! `rate_limit(N, per_second | per_minute | per_hour)` allows bursts of `N`
+//! requests, refilled evenly over the period. Each route has its own buckets,
+//! keyed by:
+//!
+//! - `key = ip` (default): the peer address from axum's
+//!   `ConnectInfo<SocketAddr>`. Serve the app with
+//!   `into_make_service_with_connect_info::<SocketAddr>()`; without a peer
+//!   address all clients share one bucket (a warning is printed once).
+//!   Behind a reverse proxy, insert the [`TrustedProxy`] extension to key by
+//!   the address the proxy forwards instead. `X-Forwarded-For`/`X-Real-IP`
+//!   are ignored otherwise, since any client can set them.
+//! - `key = user`: the [`UserKey`] request extension inserted by the
+//!   application's auth middleware (falls back to the IP).
+//! - `key = state` (live methods only): the `#[live(id)]` field of the signed
+//!   state (falls back to the IP).
+//!
+//! Limited requests get `429 Too Many Requests` with `Retry-After` (seconds).
+//! The client runtime rolls back the prediction, fires `azumi:rate-limited`
+//! on the element and shows a short message unless the event is cancelled.
+//! `ip`/`user` limits run before guards, `state` limits after them (the state
+//! must be verified first). Buckets live in process memory, so each server
+//! instance counts separately.
+
+use axum::http::request::Parts;
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Maximum number of buckets kept. When full, buckets that have refilled
+/// completely are dropped (they carry no information), then the least
+/// recently used ones.
+pub const MAX_BUCKETS: usize = 100_000;
+
+/// What a bucket is keyed by
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitKey {
+    Ip,
+    User,
+    State,
+}
+
+/// A limit of `capacity` requests per `period`
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub period: Duration,
+    pub key: RateLimitKey,
+}
+
+impl RateLimit {
+    pub const fn new(capacity: u32, period: Duration, key: RateLimitKey) -> Self {
+        Self {
+            capacity,
+            period,
+            key,
+        }
+    }
+
+    /// Bucket key for `key = ip` / `key = user`
+    pub fn request_key(&self, parts: &Parts) -> String {
+        match self.key {
+            RateLimitKey::User => match parts.extensions.get::<UserKey>() {
+                Some(user) => format!("user:{}", user.0),
+                None => ip_key(parts),
+            },
+            RateLimitKey::Ip | RateLimitKey::State => ip_key(parts),
+        }
+    }
+
+    /// Take one token from the bucket of `route` + `key`
+    pub fn check(&self, route: &str, key: &str) -> Result<(), RateLimited> {
+        let now = Instant::now();
+        let capacity = f64::from(self.capacity.max(1));
+        let rate = capacity / self.period.as_secs_f64().max(f64::EPSILON);
+
+        with_buckets(|buckets| {
+            let bucket_key = format!("{}|{}", route, key);
+            if buckets.len() >= MAX_BUCKETS && !buckets.contains_key(&bucket_key) {
+                evict(buckets, now);
+            }
+            let bucket = buckets.entry(bucket_key).or_insert(Bucket {
+                tokens: capacity,
+                updated: now,
+                rate,
+                capacity,
+            });
+            bucket.tokens = bucket.tokens_at(now);
+            bucket.updated = now;
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                Ok(())
+            } else {
+                Err(RateLimited {
+                    retry_after: Duration::from_secs_f64((1.0 - bucket.tokens) / rate),
+                })
+            }
+        })
+    }
+}
+
+/// Identity for `key = user`, inserted as a request extension by the
+/// application (e.g. `parts.extensions.insert(UserKey(user.id.to_string()))`)
+#[derive(Clone, Debug)]
+pub struct UserKey(pub String);
+
+/// Rejection of a rate-limited request (`429` with `Retry-After`)
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimited {
+    pub retry_after: Duration,
+}
+
+impl RateLimited {
+    /// Whole seconds to wait, at least 1
+    pub fn retry_after_secs(&self) -> u64 {
+        (self.retry_after.as_secs_f64().ceil() as u64).max(1)
+    }
+}
+
+impl IntoResponse for RateLimited {
+    fn into_response(self) -> Response {
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, "Too many requests").into_response();
+        response.headers_mut().insert(
+            header::RETRY_AFTER,
+            HeaderValue::from(self.retry_after_secs()),
+        );
+        response
+    }
+}
+
+/// Request extension telling `key = ip` that the app runs behind a reverse
+/// proxy which overwrites `X-Real-IP` or appends the peer address to
+/// `X-Forwarded-For`:
+///
+/// ```rust,ignore
+/// let app = app.layer(axum::Extension(azumi::rate_limit::TrustedProxy));
+/// ```
+///
+/// The last `X-Forwarded-For` entry (the one the proxy added) is used, then
+/// `X-Real-IP`. Only insert it when every request passes through the proxy.
+#[derive(Clone, Copy, Debug)]
+pub struct TrustedProxy;
+
+/// Client address: the proxy headers with [`TrustedProxy`], else
+/// `ConnectInfo`
+pub fn ip_key(parts: &Parts) -> String {
+    if parts.extensions.get::<TrustedProxy>().is_some() {
+        let header_str = |name: &str| parts.headers.get(name).and_then(|v| v.to_str().ok());
+        let forwarded = header_str("x-forwarded-for")
+            .and_then(|v| v.rsplit(',').next())
+            .or_else(|| header_str("x-real-ip"))
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        if let Some(ip) = forwarded {
+            return format!("ip:{}", ip);
+        }
+    }
+    if let Some(info) = parts
+        .extensions
+        .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+    {
+        return format!("ip:{}", info.0.ip());
+    }
+    static WARNED: std::sync::Once = std::sync::Once::new();
+    WARNED.call_once(|| {
+        eprintln!(
+            "[azumi] rate_limit(key = ip) has no client address, so all clients share one bucket. \
+             Serve with `into_make_service_with_connect_info::<SocketAddr>()` \
+             or insert `azumi::rate_limit::TrustedProxy` behind a proxy."
+        );
+    });
+    "ip:unknown".to_string()
+}
+
+/// Make room for one bucket without resetting active ones
+fn evict(buckets: &mut HashMap<String, Bucket>, now: Instant) {
+    buckets.retain(|_, b| b.tokens_at(now) < b.capacity);
+    if buckets.len() < MAX_BUCKETS {
+        return;
+    }
+    // Drop the least recently used tenth, so the scan is not repeated on
+    // every new key
+    let mut updated: Vec<Instant> = buckets.values().map(|b| b.updated).collect();
+    let cutoff_index = buckets.len() / 10;
+    let (_, cutoff, _) = updated.select_nth_unstable(cutoff_index);
+    let cutoff = *cutoff;
+    buckets.retain(|_, b| b.updated > cutoff);
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// Tokens per second
+    rate: f64,
+    capacity: f64,
+}
+
+impl Bucket {
+    fn tokens_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        (self.tokens + elapsed * self.rate).min(self.capacity)
+    }
+}
+
+static BUCKETS: OnceLock<Mutex<HashMap<String, Bucket>>> = OnceLock::new();
+
+fn with_buckets<R>(f: impl FnOnce(&mut HashMap<String, Bucket>) -> R) -> R {
+    let buckets = BUCKETS.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut guard = buckets.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut guard)
+}
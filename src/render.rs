@@ -0,0 +1,100 @@
+//! # Render Buffers
+//!
+//! [`render_into`] renders into a pre-sized buffer: the buffer is reserved
+//! up front from [`Component::size_hint`] (`html!` sets it to the length of
+//! its static markup), and each write is appended to it without an
+//! intermediate `String`. It is not a separate byte-level path:
+//! `Component::render` still writes `&str`s through `std::fmt::Formatter`,
+//! and [`RenderBuf`] receives them as they come.
+//!
+//! ```rust,ignore
+//! async fn orders(State(db): State<Db>) -> impl IntoResponse {
+//!     let rows = db.orders();
+//!     let body = azumi::render::render_to_bytes(&html! {
+//!         <table>
+//!             @for row in &rows { <tr><td>{&row.id}</td><td>{&row.title}</td></tr> }
+//!         </table>
+//!     });
+//!     ([(header::CONTENT_TYPE, "text/html; charset=utf-8")], body)
+//! }
+//! ```
+//!
+//! Existing components keep working unchanged: anything written through the
+//! formatter ends up in the buffer.
+
+use crate::Component;
+use bytes::{Bytes, BytesMut};
+use std::fmt::Write as _;
+
+/// A buffer components can be rendered into
+pub trait RenderBuf {
+    fn reserve(&mut self, additional: usize);
+    fn put_str(&mut self, s: &str);
+}
+
+impl RenderBuf for String {
+    fn reserve(&mut self, additional: usize) {
+        String::reserve(self, additional);
+    }
+
+    fn put_str(&mut self, s: &str) {
+        self.push_str(s);
+    }
+}
+
+impl RenderBuf for Vec<u8> {
+    fn reserve(&mut self, additional: usize) {
+        Vec::reserve(self, additional);
+    }
+
+    fn put_str(&mut self, s: &str) {
+        self.extend_from_slice(s.as_bytes());
+    }
+}
+
+impl RenderBuf for BytesMut {
+    fn reserve(&mut self, additional: usize) {
+        BytesMut::reserve(self, additional);
+    }
+
+    fn put_str(&mut self, s: &str) {
+        self.extend_from_slice(s.as_bytes());
+    }
+}
+
+/// `fmt::Write` adapter over a [`RenderBuf`]
+struct BufWriter<'a, B: RenderBuf + ?Sized>(&'a mut B);
+
+impl<B: RenderBuf + ?Sized> std::fmt::Write for BufWriter<'_, B> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.put_str(s);
+        Ok(())
+    }
+}
+
+/// A component as `Display`
+pub(crate) struct Rendered<'a, C: Component + ?Sized>(pub(crate) &'a C);
+
+impl<C: Component + ?Sized> std::fmt::Display for Rendered<'_, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.render(f)
+    }
+}
+
+/// Append the rendered `component` to `buf`
+pub fn render_into<C, B>(component: &C, buf: &mut B) -> std::fmt::Result
+where
+    C: Component + ?Sized,
+    B: RenderBuf + ?Sized,
+{
+    buf.reserve(component.size_hint());
+    write!(BufWriter(buf), "{}", Rendered(component))
+}
+
+/// Render `component` into a response-ready byte buffer
+pub fn render_to_bytes<C: Component + ?Sized>(component: &C) -> Bytes {
+    let mut buf = BytesMut::new();
+    render_into(component, &mut buf)
+        .expect("a Display implementation returned an error unexpectedly");
+    buf.freeze()
+}
@@ -0,0 +1,136 @@
+//! # HTML Responses
+//!
+//! [`Html`] turns a component into an axum response:
+//!
+//! ```rust,ignore
+//! async fn order(Path(id): Path<u32>) -> azumi::Html<impl Component> {
+//!     azumi::Html::new(order_page(id))
+//! }
+//!
+//! async fn missing() -> impl IntoResponse {
+//!     not_found_page()
+//!         .into_html()
+//!         .status(StatusCode::NOT_FOUND)
+//!         .header(header::CACHE_CONTROL, HeaderValue::from_static("no-store"))
+//! }
+//! ```
+//!
+//! The page is rendered into a byte buffer before the response is built, so
+//! it gets a `Content-Length` and an `ETag` (a hash of the body). If the
+//! component returns an error while rendering, the response is a plain
+//! `500 Internal Server Error` instead of a panic.
+//!
+//! `html!` results (and `from_fn` components) can also be returned from a
+//! handler as they are. For `impl Component` values, use
+//! [`Component::into_html`](crate::Component::into_html).
+
+use crate::Component;
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use std::hash::Hasher;
+
+/// A component sent as `text/html`. See the [module docs](self).
+pub struct Html<C> {
+    component: C,
+    status: StatusCode,
+    headers: HeaderMap,
+}
+
+impl<C: Component> Html<C> {
+    pub fn new(component: C) -> Self {
+        Html {
+            component,
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Response status (`200 OK` by default)
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Add a response header. Replaces the defaults (`Content-Type`, `ETag`)
+    /// when given the same name.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+}
+
+/// Strong `ETag` for a rendered body
+fn etag(body: &[u8]) -> HeaderValue {
+    let mut hasher = fnv::FnvHasher::default();
+    hasher.write(body);
+    // Hex digits and quotes are always a valid header value
+    HeaderValue::from_str(&format!("\"{:016x}\"", hasher.finish()))
+        .unwrap_or_else(|_| HeaderValue::from_static("\"\""))
+}
+
+impl<C: Component> Html<C> {
+    /// Status, headers and body, or `None` when rendering failed
+    pub(crate) fn into_parts(self) -> Option<(StatusCode, HeaderMap, Bytes)> {
+        let mut body = bytes::BytesMut::new();
+        if let Err(error) = crate::error::try_render_into(&self.component, &mut body) {
+            crate::error::log(&error, "component returned an error while rendering");
+            return None;
+        }
+        let body = body.freeze();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        );
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(body.len()));
+        headers.insert(header::ETAG, etag(&body));
+        let mut last = None;
+        for (name, value) in self.headers {
+            // `None` continues the previous header's values
+            let name = match name {
+                Some(name) => {
+                    headers.remove(&name);
+                    last.insert(name).clone()
+                }
+                None => match &last {
+                    Some(name) => name.clone(),
+                    None => continue,
+                },
+            };
+            headers.append(name, value);
+        }
+        Some((self.status, headers, body))
+    }
+}
+
+impl<C: Component> IntoResponse for Html<C> {
+    fn into_response(self) -> Response {
+        let Some((status, headers, body)) = self.into_parts() else {
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        };
+        let mut response = Response::new(Body::from(body));
+        *response.status_mut() = status;
+        *response.headers_mut() = headers;
+        response
+    }
+}
+
+impl<F> IntoResponse for crate::FnOnceComponent<F>
+where
+    F: FnOnce(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+{
+    fn into_response(self) -> Response {
+        Html::new(self).into_response()
+    }
+}
+
+impl<F> IntoResponse for crate::FnComponent<F>
+where
+    F: Fn(&mut std::fmt::Formatter<'_>) -> std::fmt::Result,
+{
+    fn into_response(self) -> Response {
+        Html::new(self).into_response()
+    }
+}
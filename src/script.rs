@@ -1,13 +1,41 @@
 use crate::Component;
 
-pub struct AzumiScript;
+/// The inline client runtime, see [`crate::azumi_script`]
+pub struct AzumiScript {
+    prefix: String,
+}
+
+impl AzumiScript {
+    /// Runtime for routes mounted under `prefix` (normalized like
+    /// [`crate::action::AzumiRoutes::prefix`])
+    pub(crate) fn new(prefix: &str) -> Self {
+        let trimmed = prefix.trim_matches('/');
+        AzumiScript {
+            prefix: if trimmed.is_empty() {
+                String::new()
+            } else {
+                format!("/{}", trimmed)
+            },
+        }
+    }
+}
+
+/// The client runtime, preceded by `window.AZUMI_PREFIX` when the routes are
+/// mounted under a prefix (see [`crate::action::routes`])
+pub(crate) fn client_js(prefix: &str) -> String {
+    if prefix.is_empty() {
+        return crate::AZUMI_JS.to_string();
+    }
+    let prefix = serde_json::to_string(prefix).unwrap_or_else(|_| "\"\"".to_string());
+    format!("window.AZUMI_PREFIX={};\n{}", prefix, crate::AZUMI_JS)
+}
 
 impl Component for AzumiScript {
     fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "<script>{}</script>",
-            crate::AZUMI_JS.replace("</script>", r"<\/script>")
+            client_js(&self.prefix).replace("</script>", r"<\/script>")
         )
     }
 }
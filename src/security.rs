@@ -75,6 +75,29 @@ fn get_current_timestamp() -> u64 {
         .expect("System clock is before UNIX_EPOCH or unavailable - this is a fatal error for state signing")
 }
 
+/// HMAC-SHA256 of `purpose` followed by `data` with the state secret.
+/// Used for values that are not states (e.g. CSRF tokens); `purpose` (such as
+/// `b"azumi-csrf:"`) keeps a tag made for one kind of value from verifying
+/// as another.
+#[cfg(feature = "axum")]
+pub(crate) fn mac_bytes(purpose: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac =
+        HmacSha256::new_from_slice(get_secret().as_bytes()).expect("HMAC can take any size key");
+    mac.update(purpose);
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Constant-time check of a tag produced by [`mac_bytes`] for the same `purpose`
+#[cfg(feature = "axum")]
+pub(crate) fn verify_mac(purpose: &[u8], data: &[u8], tag: &[u8]) -> bool {
+    let mut mac =
+        HmacSha256::new_from_slice(get_secret().as_bytes()).expect("HMAC can take any size key");
+    mac.update(purpose);
+    mac.update(data);
+    mac.verify_slice(tag).is_ok()
+}
+
 /// Signs a state string with HMAC-SHA256 and includes a timestamp for replay protection.
 /// Returns format: "{json}|{timestamp}|{signature_base64}"
 ///
@@ -129,7 +129,7 @@ pub fn generate_head(
 ) -> crate::Raw<String> {
     let global = SITE_CONFIG.get();
 
-    // 0. Resolve Context (ThreadLocal override from #[azumi::page])
+    // 0. Resolve Context (#[azumi::page] render, or the task's with_page_meta)
     let context_meta = crate::context::get_page_meta();
 
     // Resolve effective title/desc/image
@@ -0,0 +1,94 @@
+//! # Named Slots
+//!
+//! A component has at most one `children` parameter. Layouts that need more
+//! insertion points declare named slots with `#[slot]`:
+//!
+//! ```rust,ignore
+//! #[azumi::component]
+//! pub fn Card(
+//!     title: &str,
+//!     #[slot] header: impl Component,
+//!     #[slot] footer: Option<impl Component>,
+//!     children: impl Component,
+//! ) -> impl Component {
+//!     html! {
+//!         <article>
+//!             <header>{header}</header>
+//!             <h2>{title}</h2>
+//!             {children}
+//!             <footer>{footer}</footer>
+//!         </article>
+//!     }
+//! }
+//! ```
+//!
+//! and are filled with `@slot` blocks in the call. Anything outside a
+//! `@slot` block is `children`:
+//!
+//! ```rust,ignore
+//! html! {
+//!     @Card(title = "Orders") {
+//!         @slot header { <img src="/logo.svg" alt="Logo"/> }
+//!         <p>"3 open orders"</p>
+//!     }
+//! }
+//! ```
+//!
+//! `impl Component` slots are required and `Option<impl Component>` slots
+//! optional (`None` when not filled; `Option` renders nothing for `None`).
+//! A missing required slot or an unknown slot name is a compile error.
+//!
+//! In Rust code, slots are set on the props builder with `.slot_<name>(...)`:
+//! `Card::Props::builder().title("Orders").slot_header(logo()).build()`.
+
+use crate::Component;
+
+/// Content of a slot that was not filled
+pub struct Unfilled;
+
+/// Content of a filled slot
+pub struct Filled<C>(pub C);
+
+/// A filled slot (required slots)
+#[diagnostic::on_unimplemented(message = "a required slot is not filled")]
+pub trait Required {
+    type Content: Component;
+    fn into_content(self) -> Self::Content;
+}
+
+impl<C: Component> Required for Filled<C> {
+    type Content = C;
+
+    fn into_content(self) -> C {
+        self.0
+    }
+}
+
+/// A slot that may be left unfilled (optional slots)
+pub trait Optional {
+    type Content: Component;
+    fn into_option(self) -> Option<Self::Content>;
+}
+
+impl<C: Component> Optional for Filled<C> {
+    type Content = C;
+
+    fn into_option(self) -> Option<C> {
+        Some(self.0)
+    }
+}
+
+impl Optional for Unfilled {
+    type Content = Unfilled;
+
+    fn into_option(self) -> Option<Unfilled> {
+        None
+    }
+}
+
+/// Never rendered: the content type of an unfilled optional slot
+impl Component for Unfilled {
+    fn render(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+//! # Streaming Rendering
+//!
+//! [`crate::render_to_string`] builds the whole page before the first byte is
+//! sent. [`stream`] renders into chunks instead and sends each one as soon as
+//! it is complete, so the browser can start on the `<head>` (styles, scripts)
+//! while the rest of the page is still being produced:
+//!
+//! ```rust,ignore
+//! async fn report(State(db): State<Db>) -> impl IntoResponse {
+//!     let rows = db.rows();
+//!     azumi::stream::stream(move || html! {
+//!         <html>
+//!             <head>{azumi::azumi_script()}</head>
+//!             @flush
+//!             <body>
+//!                 @for row in rows.iter() { <p>{row.expensive_summary()}</p> }
+//!             </body>
+//!         </html>
+//!     })
+//!     .chunk_size(16 * 1024)
+//! }
+//! ```
+//!
+//! A chunk is sent when the buffer reaches the chunk size
+//! ([`DEFAULT_CHUNK_SIZE`] by default) and at every `@flush` in `html!`.
+//! The component is built and rendered on a blocking thread, hence the
+//! `Send + 'static` closure; the request path, page metadata, CSRF token and
+//! flash messages of the task that called [`stream`] are carried over. If the
+//! client disconnects, rendering stops at the next write. A render error or
+//! panic is logged and ends the body where it stopped (the status has
+//! already been sent).
+//!
+//! `@suspense` blocks are sent after the page, as they resolve (see
+//! [`crate::suspense`]).
+
+use crate::Component;
+#[cfg(feature = "axum")]
+use axum::body::{Body, Bytes};
+#[cfg(feature = "axum")]
+use axum::http::{header, HeaderValue};
+#[cfg(feature = "axum")]
+use axum::response::{IntoResponse, Response};
+use std::cell::Cell;
+use std::fmt::Write as _;
+
+/// Bytes buffered before a chunk is sent
+pub const DEFAULT_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Chunks waiting to be sent before rendering pauses
+#[cfg(feature = "axum")]
+const CHANNEL_CAPACITY: usize = 8;
+
+thread_local! {
+    static FLUSH_REQUESTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Send everything rendered so far as a chunk (`@flush` in `html!`).
+/// Outside a streaming render this does nothing.
+pub fn flush() {
+    FLUSH_REQUESTED.with(|requested| requested.set(true));
+}
+
+/// `fmt::Write` target that hands complete chunks to a sink
+struct ChunkWriter<S: FnMut(String) -> bool> {
+    buf: String,
+    chunk_size: usize,
+    sink: S,
+}
+
+impl<S: FnMut(String) -> bool> ChunkWriter<S> {
+    fn send(&mut self) -> std::fmt::Result {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buf, String::with_capacity(self.chunk_size));
+        if (self.sink)(chunk) {
+            Ok(())
+        } else {
+            Err(std::fmt::Error)
+        }
+    }
+}
+
+impl<S: FnMut(String) -> bool> std::fmt::Write for ChunkWriter<S> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if FLUSH_REQUESTED.with(|requested| requested.replace(false)) {
+            self.send()?;
+        }
+        self.buf.push_str(s);
+        if self.buf.len() >= self.chunk_size {
+            self.send()?;
+        }
+        Ok(())
+    }
+}
+
+/// Render `component` in chunks of about `chunk_size` bytes (and at every
+/// `@flush`). `sink` receives each chunk in order and returns `false` to
+/// stop rendering, which then returns `Err`.
+pub fn render_chunks<C, S>(component: &C, chunk_size: usize, sink: S) -> std::fmt::Result
+where
+    C: Component + ?Sized,
+    S: FnMut(String) -> bool,
+{
+    FLUSH_REQUESTED.with(|requested| requested.set(false));
+    let mut writer = ChunkWriter {
+        buf: String::with_capacity(chunk_size),
+        chunk_size: chunk_size.max(1),
+        sink,
+    };
+    let result = write!(writer, "{}", crate::render::Rendered(component));
+    FLUSH_REQUESTED.with(|requested| requested.set(false));
+    result?;
+    writer.send()
+}
+
+/// A page rendered into a chunked response body. See the [module docs](self).
+#[cfg(feature = "axum")]
+pub struct HtmlStream<F> {
+    build: F,
+    chunk_size: usize,
+    nonce: Option<String>,
+    request: crate::context::RequestScope,
+}
+
+/// Stream the component returned by `build`
+#[cfg(feature = "axum")]
+pub fn stream<F, C>(build: F) -> HtmlStream<F>
+where
+    F: FnOnce() -> C + Send + 'static,
+    C: Component,
+{
+    HtmlStream {
+        build,
+        chunk_size: DEFAULT_CHUNK_SIZE,
+        nonce: None,
+        request: crate::context::RequestScope::current(),
+    }
+}
+
+#[cfg(feature = "axum")]
+impl<F, C> HtmlStream<F>
+where
+    F: FnOnce() -> C + Send + 'static,
+    C: Component,
+{
+    /// Bytes buffered before a chunk is sent
+    pub fn chunk_size(mut self, bytes: usize) -> Self {
+        self.chunk_size = bytes;
+        self
+    }
+
+    /// CSP nonce for the inline scripts that put resolved `@suspense` blocks
+    /// in place (`script-src 'nonce-...'`)
+    pub fn nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.nonce = Some(nonce.into());
+        self
+    }
+
+    /// The chunked body. Must be called inside a Tokio runtime.
+    pub fn into_body(self) -> Body {
+        let (tx, rx) = tokio::sync::mpsc::channel::<Bytes>(CHANNEL_CAPACITY);
+        let HtmlStream {
+            build,
+            chunk_size,
+            nonce,
+            request,
+        } = self;
+        let collector = crate::suspense::collector(crate::suspense::Mode::Stream);
+        let task_request = request.clone();
+        tokio::spawn(task_request.scope(async move {
+            let render_tx = tx.clone();
+            let render_collector = collector.clone();
+            let rendered = tokio::task::spawn_blocking(move || {
+                request.run(|| {
+                    crate::suspense::with_collector(&render_collector, || {
+                        let component = build();
+                        let mut disconnected = false;
+                        let result = crate::error::capture(|| {
+                            render_chunks(&component, chunk_size, |chunk| {
+                                disconnected = render_tx.blocking_send(Bytes::from(chunk)).is_err();
+                                !disconnected
+                            })
+                        });
+                        match result {
+                            Err(error) if !disconnected => {
+                                crate::error::log(&error, "streamed page failed while rendering");
+                                false
+                            }
+                            result => result.is_ok(),
+                        }
+                    })
+                })
+            })
+            .await;
+            match rendered {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(error) => {
+                    if error.is_panic() {
+                        let error = crate::error::RenderError::new(crate::error::Panic {
+                            message: crate::error::panic_message(error.into_panic()),
+                        });
+                        crate::error::log(&error, "streamed page panicked while rendering");
+                    }
+                    return;
+                }
+            }
+
+            // @suspense blocks, as they resolve
+            let mut resolver = crate::suspense::Resolver::new(collector);
+            let mut first = true;
+            while let Some((id, html)) = resolver.next().await {
+                let chunk = crate::suspense::resolution_chunk(id, &html, first, nonce.as_deref());
+                first = false;
+                if tx.send(Bytes::from(chunk)).await.is_err() {
+                    return;
+                }
+            }
+        }));
+        Body::from_stream(futures_util::stream::unfold(rx, |mut rx| async move {
+            rx.recv()
+                .await
+                .map(|chunk| (Ok::<_, std::convert::Infallible>(chunk), rx))
+        }))
+    }
+}
+
+#[cfg(feature = "axum")]
+impl<F, C> IntoResponse for HtmlStream<F>
+where
+    F: FnOnce() -> C + Send + 'static,
+    C: Component,
+{
+    fn into_response(self) -> Response {
+        let mut response = Response::new(self.into_body());
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        );
+        response
+    }
+}
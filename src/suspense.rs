@@ -0,0 +1,244 @@
+//! # Suspense
+//!
+//! `Component::render` is synchronous. Slow data goes in a `@suspense`
+//! block instead: the fallback is rendered in place right away, and the
+//! block is rendered once its data is ready, later in the same response.
+//!
+//! ```rust,ignore
+//! html! {
+//!     <h1>"Dashboard"</h1>
+//!     @suspense(html! { <p>"Loading orders..."</p> }) {
+//!         @let orders = load_orders(user_id).await;
+//!         <ul>
+//!             @for order in &orders { <li>{&order.title}</li> }
+//!         </ul>
+//!     }
+//! }
+//! ```
+//!
+//! The top-level `@let` statements of the block run in an `async move`
+//! block, so they may `.await`; everything the block uses is moved into it
+//! and must be `Send + 'static`. In Rust code, any [`AsyncComponent`] (e.g.
+//! `async move { html! { ... } }`) can be wrapped with [`suspense`].
+//!
+//! How the block is resolved depends on how the page is rendered:
+//!
+//! - [`crate::stream::stream`]: the page is sent with each fallback inside an
+//!   `<azumi-suspense data-id="N">` element. As blocks resolve (in completion
+//!   order, concurrently) their HTML follows in a
+//!   `<template data-azumi-resolve="N">` with a small inline script that
+//!   swaps it into place and fires `azumi:resolved` on `document`. Under a
+//!   Content Security Policy without `'unsafe-inline'`, pass the page's nonce
+//!   to [`crate::stream::HtmlStream::nonce`].
+//! - [`render_to_string_async`]: the blocks are awaited and their HTML is put
+//!   in place of the fallback before the string is returned.
+//! - [`crate::render_to_string`]: only the fallback is rendered.
+//!
+//! Blocks resolved later may contain `@suspense` blocks of their own, and
+//! see the values provided (`@provide`) around them.
+
+use crate::Component;
+use futures_util::future::BoxFuture;
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+/// A component rendered from asynchronously loaded data.
+///
+/// Implemented for every `Future` whose output is a [`Component`].
+pub trait AsyncComponent: Send + 'static {
+    fn render_async(self) -> BoxFuture<'static, String>;
+}
+
+impl<F, C> AsyncComponent for F
+where
+    F: Future<Output = C> + Send + 'static,
+    C: Component,
+{
+    fn render_async(self) -> BoxFuture<'static, String> {
+        Box::pin(async move { crate::render_to_string(&self.await) })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "axum"), allow(dead_code))]
+pub(crate) enum Mode {
+    /// Fallback now, `<template>` + script later
+    Stream,
+    /// Marker replaced by [`render_to_string_async`]
+    Inline,
+}
+
+pub(crate) struct Collector {
+    mode: Mode,
+    next_id: usize,
+    pending: Vec<(usize, BoxFuture<'static, String>)>,
+}
+
+pub(crate) type SharedCollector = Arc<Mutex<Collector>>;
+
+pub(crate) fn collector(mode: Mode) -> SharedCollector {
+    Arc::new(Mutex::new(Collector {
+        mode,
+        next_id: 0,
+        pending: Vec::new(),
+    }))
+}
+
+thread_local! {
+    static COLLECTOR: RefCell<Option<SharedCollector>> = const { RefCell::new(None) };
+}
+
+/// Run `f` with `@suspense` blocks registering into `collector`
+pub(crate) fn with_collector<R>(collector: &SharedCollector, f: impl FnOnce() -> R) -> R {
+    let previous = COLLECTOR.with(|c| c.replace(Some(collector.clone())));
+    let result = f();
+    COLLECTOR.with(|c| *c.borrow_mut() = previous);
+    result
+}
+
+/// Placeholder of a block in [`Mode::Inline`]
+fn marker(id: usize) -> String {
+    format!("<!--azumi-suspense:{}-->", id)
+}
+
+/// A `@suspense` block. See the [module docs](self).
+pub struct Suspense<F, A> {
+    fallback: F,
+    content: Cell<Option<A>>,
+}
+
+/// Render `fallback` now and `content` once it resolves
+pub fn suspense<F: Component, A: AsyncComponent>(fallback: F, content: A) -> Suspense<F, A> {
+    Suspense {
+        fallback,
+        content: Cell::new(Some(content)),
+    }
+}
+
+impl<F: Component, A: AsyncComponent> Component for Suspense<F, A> {
+    fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let collector = COLLECTOR.with(|c| c.borrow().clone());
+        let (Some(collector), Some(content)) = (collector, self.content.take()) else {
+            return self.fallback.render(f);
+        };
+        let (id, mode) = {
+            let mut collector = collector.lock().map_err(|_| std::fmt::Error)?;
+            let id = collector.next_id;
+            collector.next_id += 1;
+            collector
+                .pending
+                .push((id, crate::context::scoped(content.render_async())));
+            (id, collector.mode)
+        };
+        match mode {
+            Mode::Inline => write!(f, "{}", marker(id)),
+            Mode::Stream => {
+                write!(f, "<azumi-suspense data-id=\"{}\">", id)?;
+                self.fallback.render(f)?;
+                write!(f, "</azumi-suspense>")
+            }
+        }
+    }
+}
+
+/// Polls a block with its collector installed, so that blocks found in its
+/// content are registered too
+struct Collecting {
+    collector: SharedCollector,
+    id: usize,
+    inner: BoxFuture<'static, String>,
+}
+
+impl Future for Collecting {
+    type Output = (usize, String);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let id = this.id;
+        let inner = &mut this.inner;
+        with_collector(&this.collector, || inner.as_mut().poll(cx)).map(|html| (id, html))
+    }
+}
+
+/// Resolves registered blocks concurrently, in completion order
+pub(crate) struct Resolver {
+    collector: SharedCollector,
+    running: FuturesUnordered<Collecting>,
+}
+
+impl Resolver {
+    pub(crate) fn new(collector: SharedCollector) -> Self {
+        Resolver {
+            collector,
+            running: FuturesUnordered::new(),
+        }
+    }
+
+    fn drain(&mut self) {
+        let pending = match self.collector.lock() {
+            Ok(mut collector) => std::mem::take(&mut collector.pending),
+            Err(_) => return,
+        };
+        for (id, inner) in pending {
+            self.running.push(Collecting {
+                collector: self.collector.clone(),
+                id,
+                inner,
+            });
+        }
+    }
+
+    /// The next resolved block: `(id, html)`
+    pub(crate) async fn next(&mut self) -> Option<(usize, String)> {
+        self.drain();
+        self.running.next().await
+    }
+}
+
+/// Defines `__azumiResolve`, sent once before the first resolved block
+#[cfg(feature = "axum")]
+const RESOLVE_SCRIPT: &str = r#"window.__azumiResolve=function(id){var t=document.querySelector('template[data-azumi-resolve="'+id+'"]');if(!t)return;var s=document.querySelector('azumi-suspense[data-id="'+id+'"]');if(s)s.replaceWith(t.content);t.remove();document.dispatchEvent(new CustomEvent("azumi:resolved",{detail:{id:id}}))}"#;
+
+/// Streamed chunk delivering a resolved block. `nonce` is the CSP nonce of
+/// the inline scripts (see [`crate::stream::HtmlStream::nonce`]).
+#[cfg(feature = "axum")]
+pub(crate) fn resolution_chunk(id: usize, html: &str, first: bool, nonce: Option<&str>) -> String {
+    let script = match nonce {
+        Some(nonce) => format!("<script nonce=\"{}\">", crate::Escaped(nonce)),
+        None => "<script>".to_string(),
+    };
+    let define = if first {
+        format!("{}{}</script>", script, RESOLVE_SCRIPT)
+    } else {
+        String::new()
+    };
+    format!(
+        "{}<template data-azumi-resolve=\"{id}\">{}</template>{}__azumiResolve({id})</script>",
+        define,
+        html,
+        script,
+        id = id
+    )
+}
+
+/// Render `component`, await its `@suspense` blocks and return the complete
+/// HTML. The component is rendered before this returns; only the blocks are
+/// awaited, so the future is `Send` even for non-`Send` components.
+pub fn render_to_string_async<C: Component + ?Sized>(
+    component: &C,
+) -> impl Future<Output = String> + Send + 'static {
+    let collector = collector(Mode::Inline);
+    let html = with_collector(&collector, || crate::render_to_string(component));
+    async move {
+        let mut html = html;
+        let mut resolver = Resolver::new(collector);
+        while let Some((id, content)) = resolver.next().await {
+            html = html.replacen(&marker(id), &content, 1);
+        }
+        html
+    }
+}
@@ -0,0 +1,180 @@
+//! # Prediction Telemetry
+//!
+//! The client runtime compares the state it predicted for a live method
+//! (`data-predict`) with the state the server actually returned. When the two
+//! differ, or when a method changed state without any prediction at all, it
+//! POSTs a [`PredictionMismatch`] report to `/_azumi/telemetry`.
+//!
+//! The route is registered by [`crate::action::register_actions`], but it
+//! answers `404 Not Found` until the application installs a reporter, and the
+//! client stops reporting for the page after the first failed POST:
+//!
+//! ```rust,ignore
+//! azumi::telemetry::set_reporter(|m: &azumi::telemetry::PredictionMismatch| {
+//!     eprintln!("{}::{} {:?}: {} field(s)", m.struct_name, m.method, m.kind, m.diff.len());
+//! });
+//! ```
+//!
+//! Reports come from the browser and are **not** signed. Treat them as
+//! untrusted diagnostics, never as application state.
+
+use axum::http::StatusCode;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Why the client reported a method
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    /// The method has a prediction, but the server state differs from it
+    Wrong,
+    /// The method has no prediction, but the server changed the state
+    Missing,
+}
+
+/// A single field whose predicted value differs from the server value
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    /// Dotted path of the field (e.g. `user.count`)
+    pub path: String,
+    pub predicted: serde_json::Value,
+    pub actual: serde_json::Value,
+}
+
+/// A prediction mismatch report sent by the client runtime
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PredictionMismatch {
+    /// Live struct name (from `az-struct`)
+    pub struct_name: String,
+    /// Live method name
+    pub method: String,
+    pub kind: MismatchKind,
+    /// The prediction DSL that was executed, if any
+    #[serde(default)]
+    pub prediction: Option<String>,
+    /// State after the client prediction (the pre-action state for `Missing`)
+    pub predicted: serde_json::Value,
+    /// State returned by the server
+    pub actual: serde_json::Value,
+    #[serde(default)]
+    pub diff: Vec<FieldDiff>,
+}
+
+/// Server-side hook receiving prediction mismatch reports
+pub trait PredictionReporter: Send + Sync + 'static {
+    fn report(&self, mismatch: &PredictionMismatch);
+}
+
+impl<F> PredictionReporter for F
+where
+    F: Fn(&PredictionMismatch) + Send + Sync + 'static,
+{
+    fn report(&self, mismatch: &PredictionMismatch) {
+        self(mismatch)
+    }
+}
+
+/// Reporter that logs every mismatch to stderr
+pub struct LogReporter;
+
+impl PredictionReporter for LogReporter {
+    fn report(&self, mismatch: &PredictionMismatch) {
+        let fields = mismatch
+            .diff
+            .iter()
+            .map(|d| d.path.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let kind = match mismatch.kind {
+            MismatchKind::Wrong => "wrong prediction",
+            MismatchKind::Missing => "missing prediction",
+        };
+        eprintln!(
+            "⚠️  Azumi: {} for {}::{} (fields: {})",
+            kind, mismatch.struct_name, mismatch.method, fields
+        );
+    }
+}
+
+static REPORTER: OnceLock<Box<dyn PredictionReporter>> = OnceLock::new();
+
+/// Install the process-wide prediction reporter.
+///
+/// Returns `false` if a reporter was already installed (the first one wins).
+pub fn set_reporter<R: PredictionReporter>(reporter: R) -> bool {
+    REPORTER.set(Box::new(reporter)).is_ok()
+}
+
+/// Whether a reporter has been installed
+pub fn is_enabled() -> bool {
+    REPORTER.get().is_some()
+}
+
+/// Forward a mismatch to the installed reporter.
+/// Returns `true` if a reporter received it.
+pub fn report(mismatch: &PredictionMismatch) -> bool {
+    match REPORTER.get() {
+        Some(reporter) => {
+            reporter.report(mismatch);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Compute the field-level differences between two JSON states.
+///
+/// Objects are compared recursively; any other value (including arrays) is
+/// compared as a whole. Used by tests and by servers that want to recompute
+/// the diff instead of trusting the one sent by the client.
+pub fn diff_states(predicted: &serde_json::Value, actual: &serde_json::Value) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    diff_recursive("", predicted, actual, &mut diffs);
+    diffs
+}
+
+fn diff_recursive(
+    path: &str,
+    predicted: &serde_json::Value,
+    actual: &serde_json::Value,
+    out: &mut Vec<FieldDiff>,
+) {
+    use serde_json::Value;
+
+    if let (Value::Object(p), Value::Object(a)) = (predicted, actual) {
+        let mut keys: Vec<&String> = p.keys().chain(a.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let child = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            diff_recursive(
+                &child,
+                p.get(key).unwrap_or(&Value::Null),
+                a.get(key).unwrap_or(&Value::Null),
+                out,
+            );
+        }
+    } else if predicted != actual {
+        out.push(FieldDiff {
+            path: path.to_string(),
+            predicted: predicted.clone(),
+            actual: actual.clone(),
+        });
+    }
+}
+
+/// Handler for `POST /_azumi/telemetry`.
+///
+/// Answers `404 Not Found` without a reporter so the client stops posting.
+pub async fn telemetry_handler(Json(mismatch): Json<PredictionMismatch>) -> StatusCode {
+    if report(&mismatch) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
@@ -0,0 +1,392 @@
+//! # Validation
+//!
+//! `#[derive(azumi::Validate)]` checks action payloads and live state against
+//! per-field rules and reports failures as a typed [`FieldErrors`] map:
+//!
+//! ```rust,ignore
+//! #[derive(Deserialize, azumi::Validate)]
+//! pub struct Signup {
+//!     #[validate(required, length(max = 40))]
+//!     pub name: String,
+//!     #[validate(required, email)]
+//!     pub email: String,
+//!     #[validate(range(min = 13, max = 130))]
+//!     pub age: u32,
+//!     #[validate(regex = "[a-z0-9_]+", custom = not_reserved)]
+//!     pub handle: String,
+//!     #[validate(nested)]
+//!     pub address: Address,
+//! }
+//!
+//! fn not_reserved(handle: &String) -> Result<(), String> {
+//!     if handle == "admin" { Err("This handle is reserved".into()) } else { Ok(()) }
+//! }
+//! ```
+//!
+//! | Rule | Check | HTML5 attribute |
+//! |------|-------|-----------------|
+//! | `required` | non-empty string/collection, `Some` | `required` |
+//! | `length(min = a, max = b)` | character/item count | `minlength` / `maxlength` |
+//! | `range(min = a, max = b)` | numeric bounds | `min` / `max` |
+//! | `email` | `local@domain.tld` shape | `type="email"` |
+//! | `regex = "..."` | whole value matches | `pattern` |
+//! | `custom = path` | `fn(&T) -> Result<(), String>` | – |
+//! | `nested` | runs the field's own `validate` (`address.city`) | – |
+//!
+//! `None` values skip every rule except `required`. `nested` also accepts
+//! `Option<T>` (skipped when `None`) and `Vec<T>` (errors keyed by index,
+//! `items.0.name`).
+//!
+//! `#[azumi::action(validate)]` validates the payload before the action runs
+//! and answers `422 Unprocessable Entity` with the [`FieldErrors`] as JSON.
+//!
+//! Inputs inside `<form bind={Signup}>` in `html!` get the matching HTML5
+//! constraint attributes, so browsers reject bad input before it is sent.
+//! Attributes written by hand on the input win over derived ones.
+//!
+//! Render messages next to the inputs with [`FieldErrors::message`]:
+//!
+//! ```rust,ignore
+//! <input name="email" type="email" />
+//! {errors.message("email")}
+//! ```
+
+use crate::{Component, Escaped};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+pub use regex::Regex;
+
+/// A single rule failure
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// Rule that failed (`required`, `length`, `range`, `email`, `regex`, `custom`)
+    pub code: String,
+    pub message: String,
+}
+
+/// Validation failures keyed by field name (dotted for nested structs)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FieldErrors {
+    errors: BTreeMap<String, Vec<FieldError>>,
+}
+
+impl FieldErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(
+        &mut self,
+        field: impl Into<String>,
+        code: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.errors
+            .entry(field.into())
+            .or_default()
+            .push(FieldError {
+                code: code.into(),
+                message: message.into(),
+            });
+    }
+
+    /// Merge the errors of a nested struct under `prefix.`
+    pub fn merge_nested(&mut self, prefix: &str, nested: FieldErrors) {
+        for (field, errors) in nested.errors {
+            self.errors
+                .entry(format!("{}.{}", prefix, field))
+                .or_default()
+                .extend(errors);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Number of fields with errors
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn has(&self, field: &str) -> bool {
+        self.errors.contains_key(field)
+    }
+
+    pub fn get(&self, field: &str) -> &[FieldError] {
+        self.errors.get(field).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// First message for a field
+    pub fn first(&self, field: &str) -> Option<&str> {
+        self.get(field).first().map(|e| e.message.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[FieldError])> {
+        self.errors.iter().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+
+    /// `Ok(())` when empty
+    pub fn into_result(self) -> Result<(), FieldErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Component rendering the first message of a field (nothing when valid):
+    /// `<p class="field-error" id="{field}-error" role="alert">...</p>`
+    pub fn message<'a>(&'a self, field: &'a str) -> FieldMessage<'a> {
+        FieldMessage {
+            field,
+            message: self.first(field),
+        }
+    }
+}
+
+impl std::fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for (field, errors) in &self.errors {
+            for error in errors {
+                if !first {
+                    write!(f, "; ")?;
+                }
+                first = false;
+                write!(f, "{}: {}", field, error.message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FieldErrors {}
+
+/// `422 Unprocessable Entity` with the errors as JSON (`{"field": [{code, message}]}`)
+#[cfg(feature = "axum")]
+impl axum::response::IntoResponse for FieldErrors {
+    fn into_response(self) -> axum::response::Response {
+        (
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            axum::Json(self),
+        )
+            .into_response()
+    }
+}
+
+/// Per-field message component returned by [`FieldErrors::message`]
+pub struct FieldMessage<'a> {
+    field: &'a str,
+    message: Option<&'a str>,
+}
+
+impl Component for FieldMessage<'_> {
+    fn render(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.message {
+            Some(message) => write!(
+                f,
+                "<p class=\"field-error\" id=\"{}-error\" role=\"alert\">{}</p>",
+                Escaped(self.field),
+                Escaped(message)
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Implemented by `#[derive(azumi::Validate)]`
+pub trait Validate {
+    fn validate(&self) -> Result<(), FieldErrors>;
+
+    /// HTML5 constraint attributes for a field (`name`, optional value)
+    fn constraints(field: &str) -> &'static [(&'static str, Option<&'static str>)]
+    where
+        Self: Sized;
+}
+
+/// `None` is valid; `Some` validates the value
+impl<T: Validate> Validate for Option<T> {
+    fn validate(&self) -> Result<(), FieldErrors> {
+        match self {
+            Some(value) => value.validate(),
+            None => Ok(()),
+        }
+    }
+
+    fn constraints(field: &str) -> &'static [(&'static str, Option<&'static str>)] {
+        T::constraints(field)
+    }
+}
+
+/// Validates every item; errors are keyed by index (`0.name`)
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&self) -> Result<(), FieldErrors> {
+        let mut errors = FieldErrors::new();
+        for (index, item) in self.iter().enumerate() {
+            if let Err(nested) = item.validate() {
+                errors.merge_nested(&index.to_string(), nested);
+            }
+        }
+        errors.into_result()
+    }
+
+    fn constraints(field: &str) -> &'static [(&'static str, Option<&'static str>)] {
+        match field.split_once('.') {
+            Some((index, rest)) if index.parse::<usize>().is_ok() => T::constraints(rest),
+            _ => &[],
+        }
+    }
+}
+
+/// Used by `html!` to add constraint attributes to inputs of `bind` forms.
+/// Structs that do not derive `Validate` fall back to no attributes.
+pub struct Constraints<T>(PhantomData<T>);
+
+impl<T> Constraints<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Constraints(PhantomData)
+    }
+}
+
+impl<T: Validate> Constraints<T> {
+    // Takes precedence over FallbackConstraints when T: Validate
+    pub fn html_attrs(&self, field: &str, skip: &[&str]) -> String {
+        let mut out = String::new();
+        for (name, value) in T::constraints(field) {
+            if skip.contains(name) {
+                continue;
+            }
+            match value {
+                Some(value) => out.push_str(&format!(" {}=\"{}\"", name, Escaped(value))),
+                None => out.push_str(&format!(" {}", name)),
+            }
+        }
+        out
+    }
+}
+
+pub trait FallbackConstraints {
+    fn html_attrs(&self, _field: &str, _skip: &[&str]) -> String {
+        String::new()
+    }
+}
+
+impl<T> FallbackConstraints for Constraints<T> {}
+
+// ---------------------------------------------------------------------------
+// Rule helpers used by the derive
+// ---------------------------------------------------------------------------
+
+/// Values understood by the `required` rule
+pub trait ValidateRequired {
+    fn is_present(&self) -> bool;
+}
+
+impl ValidateRequired for String {
+    fn is_present(&self) -> bool {
+        !self.trim().is_empty()
+    }
+}
+
+impl ValidateRequired for &str {
+    fn is_present(&self) -> bool {
+        !self.trim().is_empty()
+    }
+}
+
+impl<T> ValidateRequired for Vec<T> {
+    fn is_present(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<T: ValidateRequired> ValidateRequired for Option<T> {
+    fn is_present(&self) -> bool {
+        self.as_ref().is_some_and(ValidateRequired::is_present)
+    }
+}
+
+/// Values understood by the `length`, `email` and `regex` rules
+pub trait ValidateValue {
+    /// Character count (strings) or item count (collections); `None` skips the rule
+    fn length(&self) -> Option<usize>;
+
+    /// Text to match against `email`/`regex`; `None` skips the rule
+    fn text(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl ValidateValue for String {
+    fn length(&self) -> Option<usize> {
+        Some(self.chars().count())
+    }
+    fn text(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl ValidateValue for &str {
+    fn length(&self) -> Option<usize> {
+        Some(self.chars().count())
+    }
+    fn text(&self) -> Option<&str> {
+        Some(self)
+    }
+}
+
+impl<T> ValidateValue for Vec<T> {
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl<T: ValidateValue> ValidateValue for Option<T> {
+    fn length(&self) -> Option<usize> {
+        self.as_ref().and_then(ValidateValue::length)
+    }
+    fn text(&self) -> Option<&str> {
+        self.as_ref().and_then(ValidateValue::text)
+    }
+}
+
+/// Values understood by the `range` rule
+pub trait ValidateRange {
+    /// `None` skips the rule
+    fn range_value(&self) -> Option<f64>;
+}
+
+macro_rules! impl_validate_range {
+    ($($ty:ty),*) => {
+        $(impl ValidateRange for $ty {
+            fn range_value(&self) -> Option<f64> {
+                Some(*self as f64)
+            }
+        })*
+    };
+}
+
+impl_validate_range!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl<T: ValidateRange> ValidateRange for Option<T> {
+    fn range_value(&self) -> Option<f64> {
+        self.as_ref().and_then(ValidateRange::range_value)
+    }
+}
+
+/// Structural email check: one `@`, non-empty local part, dotted domain, no spaces
+pub fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && !value.chars().any(char::is_whitespace)
+        && domain.contains('.')
+        && domain.split('.').all(|part| !part.is_empty())
+}
@@ -0,0 +1,188 @@
+//! Action Guard Tests
+//!
+//! `guard = ...` / `layer = ...` on `#[azumi::action]` and
+//! `#[azumi::route(..)]` on live methods.
+
+use axum::body::Body;
+use axum::http::{HeaderValue, StatusCode};
+use axum::middleware::map_response;
+use axum::response::Response;
+use azumi::action::Parts;
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tower::ServiceExt;
+
+static DELETED: AtomicUsize = AtomicUsize::new(0);
+
+async fn require_admin(parts: &Parts) -> Result<(), StatusCode> {
+    match parts.headers.get("x-role") {
+        Some(role) if role == "admin" => Ok(()),
+        Some(_) => Err(StatusCode::FORBIDDEN),
+        None => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+async fn require_reason(parts: &Parts) -> Result<(), (StatusCode, &'static str)> {
+    if parts.headers.contains_key("x-reason") {
+        Ok(())
+    } else {
+        Err((StatusCode::BAD_REQUEST, "reason required"))
+    }
+}
+
+/// Records the layer order in `x-layers` (outermost first)
+fn mark(mut response: Response, name: &str) -> Response {
+    let value = match response.headers().get("x-layers") {
+        Some(previous) => format!("{},{}", name, previous.to_str().unwrap()),
+        None => name.to_string(),
+    };
+    response
+        .headers_mut()
+        .insert("x-layers", HeaderValue::from_str(&value).unwrap());
+    response
+}
+
+async fn mark_outer(response: Response) -> Response {
+    mark(response, "outer")
+}
+
+async fn mark_inner(response: Response) -> Response {
+    mark(response, "inner")
+}
+
+#[derive(Deserialize)]
+pub struct DeleteUser {
+    pub id: u32,
+}
+
+#[azumi::action(
+    guard = require_admin,
+    guard = require_reason,
+    layer = map_response(mark_outer),
+    layer = map_response(mark_inner)
+)]
+pub async fn delete_user(data: DeleteUser) -> impl Component {
+    DELETED.fetch_add(1, Ordering::SeqCst);
+    html! { <p>"deleted "{data.id}</p> }
+}
+
+mod admin {
+    use super::*;
+
+    #[azumi::live]
+    pub struct Panel {
+        pub purged: bool,
+    }
+
+    #[azumi::live_impl]
+    impl Panel {
+        #[azumi::route(guard = require_admin)]
+        pub fn purge(&mut self) {
+            self.purged = true;
+        }
+
+        pub fn reset(&mut self) {
+            self.purged = false;
+        }
+    }
+}
+
+use admin::Panel;
+
+async fn post(
+    path: &str,
+    headers: &[(&str, &str)],
+    body: String,
+) -> (StatusCode, Option<String>, String) {
+    let router = azumi::action::register_actions(axum::Router::new());
+    let mut request = axum::http::Request::post(path).header("content-type", "application/json");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let res = router
+        .oneshot(request.body(Body::from(body)).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let layers = res
+        .headers()
+        .get("x-layers")
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, layers, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_action_guards_short_circuit() {
+    let before = DELETED.load(Ordering::SeqCst);
+
+    let (status, _, _) = post("/_azumi/action/delete_user", &[], r#"{"id":1}"#.into()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, _, _) = post(
+        "/_azumi/action/delete_user",
+        &[("x-role", "user"), ("x-reason", "spam")],
+        r#"{"id":1}"#.into(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Second guard runs after the first passes
+    let (status, _, body) = post(
+        "/_azumi/action/delete_user",
+        &[("x-role", "admin")],
+        r#"{"id":1}"#.into(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, "reason required");
+
+    assert_eq!(DELETED.load(Ordering::SeqCst), before);
+}
+
+#[tokio::test]
+async fn test_action_runs_when_guards_pass() {
+    let (status, _, body) = post(
+        "/_azumi/action/delete_user",
+        &[("x-role", "admin"), ("x-reason", "spam")],
+        r#"{"id":7}"#.into(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("deleted 7"));
+}
+
+#[tokio::test]
+async fn test_layers_wrap_guards_outermost_first() {
+    let (status, layers, _) = post("/_azumi/action/delete_user", &[], r#"{"id":1}"#.into()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(layers.as_deref(), Some("outer,inner"));
+}
+
+#[tokio::test]
+async fn test_live_method_guard() {
+    let state = Panel { purged: false }.to_scope();
+
+    let (status, _, _) = post(
+        "/_azumi/action/Panel/purge",
+        &[("x-role", "user")],
+        state.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _, body) = post(
+        "/_azumi/action/Panel/purge",
+        &[("x-role", "admin")],
+        state.clone(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains(r#""purged":true"#));
+
+    // Methods without #[azumi::route] stay unguarded
+    let (status, _, _) = post("/_azumi/action/Panel/reset", &[], state).await;
+    assert_eq!(status, StatusCode::OK);
+}
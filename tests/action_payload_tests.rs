@@ -0,0 +1,140 @@
+//! Action Payload Tests
+//!
+//! JSON, form-encoded and multipart payloads for `#[azumi::action]`.
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use azumi::action::{Form, Multipart};
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use serde::Deserialize;
+use tower::ServiceExt;
+
+#[derive(Deserialize)]
+pub struct Signup {
+    pub email: String,
+    pub name: String,
+}
+
+#[azumi::action]
+pub async fn signup_json(data: Signup) -> impl Component {
+    html! { <p>"json:"{data.name}" "{data.email}</p> }
+}
+
+#[azumi::action]
+pub async fn signup_form(Form(data): Form<Signup>) -> impl Component {
+    html! { <p>"form:"{data.name}" "{data.email}</p> }
+}
+
+#[azumi::action(form)]
+pub async fn signup_plain_form(data: Signup) -> impl Component {
+    html! { <p>"plain:"{data.name}</p> }
+}
+
+#[azumi::action]
+pub async fn upload(mut files: Multipart) -> impl Component {
+    let mut entries = Vec::new();
+    while let Ok(Some(field)) = files.next_field().await {
+        let name = field.name().unwrap_or_default().to_string();
+        let file = field.file_name().map(str::to_string);
+        let bytes = field.bytes().await.map(|b| b.len()).unwrap_or(0);
+        entries.push(match file {
+            Some(file) => format!("{}={}({})", name, file, bytes),
+            None => format!("{}({})", name, bytes),
+        });
+    }
+    let summary = entries.join(",");
+    html! { <p>{summary}</p> }
+}
+
+async fn post(
+    path: &str,
+    content_type: &str,
+    body: impl Into<Body>,
+) -> (StatusCode, String, String) {
+    let router = azumi::action::register_actions(axum::Router::new());
+    let request = Request::post(path)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(body.into())
+        .unwrap();
+    let res = router.oneshot(request).await.unwrap();
+    let status = res.status();
+    let ct = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, ct, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_json_payload() {
+    let (status, ct, body) = post(
+        "/_azumi/action/signup_json",
+        "application/json",
+        r#"{"email":"a@b.c","name":"Ann"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(ct.starts_with("text/html"));
+    assert!(body.contains("json:Ann a@b.c"));
+}
+
+#[tokio::test]
+async fn test_form_payload_by_type() {
+    let (status, ct, body) = post(
+        "/_azumi/action/signup_form",
+        "application/x-www-form-urlencoded",
+        "email=a%40b.c&name=Ann+Lee",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(ct.starts_with("text/html"));
+    assert!(body.contains("form:Ann Lee a@b.c"));
+}
+
+#[tokio::test]
+async fn test_form_payload_by_attribute() {
+    let (status, _, body) = post(
+        "/_azumi/action/signup_plain_form",
+        "application/x-www-form-urlencoded",
+        "email=x%40y.z&name=Bo",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("plain:Bo"));
+}
+
+#[tokio::test]
+async fn test_form_action_rejects_json() {
+    let (status, _, _) = post(
+        "/_azumi/action/signup_plain_form",
+        "application/json",
+        r#"{"email":"a@b.c","name":"Ann"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn test_multipart_upload() {
+    let body = concat!(
+        "--XBOUNDARY\r\n",
+        "Content-Disposition: form-data; name=\"title\"\r\n\r\n",
+        "hello\r\n",
+        "--XBOUNDARY\r\n",
+        "Content-Disposition: form-data; name=\"doc\"; filename=\"notes.txt\"\r\n",
+        "Content-Type: text/plain\r\n\r\n",
+        "0123456789\r\n",
+        "--XBOUNDARY--\r\n",
+    );
+    let (status, _, html) = post(
+        "/_azumi/action/upload",
+        "multipart/form-data; boundary=XBOUNDARY",
+        body,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(html.contains("title(5),doc=notes.txt(10)"));
+}
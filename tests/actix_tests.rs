@@ -0,0 +1,104 @@
+//! actix-web Tests
+//!
+//! `azumi::actix`: actions, hand-registered routes with path parameters and
+//! the client script forwarded from an actix app, and `azumi::Html` as an
+//! actix `Responder`.
+
+use actix_web::http::header;
+use actix_web::{test, web, App};
+use azumi::action::ActionEntry;
+use azumi::axum::extract::Path;
+use azumi::axum::routing::{get, MethodRouter};
+use azumi::{html, Component};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct Greeting {
+    pub name: String,
+}
+
+#[azumi::action]
+pub async fn greet_from_actix(greeting: Greeting) -> impl Component {
+    html! { <p>"Hello, " {greeting.name}</p> }
+}
+
+fn invoice_router() -> MethodRouter<()> {
+    get(|Path(id): Path<u32>| async move { format!("invoice {}", id) })
+}
+
+azumi::inventory::submit! {
+    ActionEntry::new("/invoices/:id", invoice_router)
+}
+
+async fn home() -> impl actix_web::Responder {
+    html! { <h1>"Home"</h1> }
+}
+
+#[actix_web::test]
+async fn test_action_through_actix() {
+    let app = test::init_service(App::new().configure(azumi::actix::register_actions)).await;
+    let req = test::TestRequest::post()
+        .uri("/_azumi/action/greet_from_actix")
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload(r#"{"name":"<Ann>"}"#)
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    assert!(res.status().is_success());
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(test::read_body(res).await, "<p>Hello, &lt;Ann&gt;</p>");
+
+    // Path parameters are translated to actix syntax
+    let req = test::TestRequest::get().uri("/invoices/7").to_request();
+    assert_eq!(test::call_and_read_body(&app, req).await, "invoice 7");
+
+    let req = test::TestRequest::get().uri("/azumi.js").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(
+        res.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/javascript"
+    );
+    assert_eq!(test::read_body(res).await, azumi::AZUMI_JS);
+}
+
+#[actix_web::test]
+async fn test_prefixed_mount() {
+    let routes = azumi::routes().prefix("/app");
+    let mount = azumi::actix::mount(routes.mount_prefix(), routes.clone().build());
+    let app = test::init_service(App::new().configure(mount)).await;
+    let req = test::TestRequest::post()
+        .uri("/app/_azumi/action/greet_from_actix")
+        .insert_header((header::CONTENT_TYPE, "application/json"))
+        .set_payload(r#"{"name":"Bo"}"#)
+        .to_request();
+    assert_eq!(
+        test::call_and_read_body(&app, req).await,
+        "<p>Hello, Bo</p>"
+    );
+
+    let req = test::TestRequest::get().uri("/azumi.js").to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_html_responder() {
+    let app = test::init_service(App::new().route("/", web::get().to(home)).route(
+        "/missing",
+        web::get().to(|| async {
+            azumi::Html::new(html! { <h1>"Not found"</h1> })
+                .status(azumi::axum::http::StatusCode::NOT_FOUND)
+        }),
+    ))
+    .await;
+
+    let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+    assert!(res.status().is_success());
+    assert!(res.headers().contains_key(header::ETAG));
+    assert_eq!(test::read_body(res).await, "<h1>Home</h1>");
+
+    let req = test::TestRequest::get().uri("/missing").to_request();
+    let res = test::call_service(&app, req).await;
+    assert_eq!(res.status().as_u16(), 404);
+}
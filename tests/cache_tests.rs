@@ -0,0 +1,117 @@
+//! Cache Tests
+//!
+//! `@cache(key = ..., ttl = ...)` fragment caching, prefix invalidation and
+//! the in-memory LRU `MemoryCache`.
+
+use azumi::cache::{invalidate_prefix, MemoryCache, RenderCache};
+use azumi::{html, test, Component};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+fn counted(renders: &'static AtomicUsize) -> usize {
+    renders.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+fn menu(locale: &'static str, renders: &'static AtomicUsize) -> impl Component {
+    html! {
+        <header>
+            @cache(key = format!("menu:{}", locale)) {
+                <nav>{locale} " #" {counted(renders)}</nav>
+            }
+        </header>
+    }
+}
+
+#[test]
+fn test_cache_hit_skips_rendering() {
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+    assert_eq!(
+        test::render(&menu("en", &RENDERS)),
+        "<header><nav>en #1</nav></header>"
+    );
+    assert_eq!(
+        test::render(&menu("en", &RENDERS)),
+        "<header><nav>en #1</nav></header>"
+    );
+    assert_eq!(
+        test::render(&menu("fr", &RENDERS)),
+        "<header><nav>fr #2</nav></header>"
+    );
+    assert_eq!(RENDERS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_ttl_expires() {
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+    let card = || {
+        html! {
+            @cache(key = "card:ttl", ttl = Duration::from_millis(50)) {
+                <p>{counted(&RENDERS)}</p>
+            }
+        }
+    };
+    assert_eq!(test::render(&card()), "<p>1</p>");
+    assert_eq!(test::render(&card()), "<p>1</p>");
+    std::thread::sleep(Duration::from_millis(80));
+    assert_eq!(test::render(&card()), "<p>2</p>");
+}
+
+#[test]
+fn test_invalidate_prefix() {
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+    let page = || {
+        html! {
+            @cache(key = "shop:nav:en") { <nav>{counted(&RENDERS)}</nav> }
+            @cache(key = "shop:nav:fr") { <nav>{counted(&RENDERS)}</nav> }
+            @cache(key = "shop:footer") { <footer>{counted(&RENDERS)}</footer> }
+        }
+    };
+    assert_eq!(
+        test::render(&page()),
+        "<nav>1</nav><nav>2</nav><footer>3</footer>"
+    );
+    invalidate_prefix("shop:nav:");
+    assert_eq!(
+        test::render(&page()),
+        "<nav>4</nav><nav>5</nav><footer>3</footer>"
+    );
+}
+
+#[test]
+fn test_failed_render_is_not_cached() {
+    static RENDERS: AtomicUsize = AtomicUsize::new(0);
+    let fragment = |input: &'static str| {
+        html! {
+            @cache(key = "price:failing") {
+                @let price: u32 = input.parse()?;
+                <b>{price} " / " {counted(&RENDERS)}</b>
+            }
+        }
+    };
+    assert!(azumi::try_render_to_string(&fragment("n/a")).is_err());
+    assert_eq!(test::render(&fragment("12")), "<b>12 / 1</b>");
+    assert_eq!(test::render(&fragment("n/a")), "<b>12 / 1</b>");
+}
+
+#[test]
+fn test_memory_cache_evicts_least_recently_used() {
+    let cache = MemoryCache::new(3);
+    cache.insert("a", "A".to_string(), None);
+    cache.insert("b", "B".to_string(), None);
+    cache.insert("c", "C".to_string(), None);
+    assert_eq!(cache.get("a").as_deref(), Some("A"));
+    cache.insert("d", "D".to_string(), None);
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.get("b"), None);
+    assert_eq!(cache.get("a").as_deref(), Some("A"));
+    assert_eq!(cache.get("c").as_deref(), Some("C"));
+    assert_eq!(cache.get("d").as_deref(), Some("D"));
+
+    // Replacing an entry does not evict
+    cache.insert("a", "A2".to_string(), Some(Duration::from_secs(60)));
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.get("a").as_deref(), Some("A2"));
+
+    cache.invalidate_prefix("");
+    assert!(cache.is_empty());
+}
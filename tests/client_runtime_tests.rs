@@ -0,0 +1,42 @@
+//! Client Runtime Tests
+//!
+//! Static checks on `client/azumi.js`: every method the runtime calls on
+//! itself is defined, and the headers it sends match the server constants.
+
+use regex::Regex;
+use std::collections::BTreeSet;
+
+const RUNTIME: &str = include_str!("../client/azumi.js");
+
+#[test]
+fn test_called_methods_are_defined() {
+    let called: BTreeSet<&str> = Regex::new(r"this\.(\w+)\(")
+        .unwrap()
+        .captures_iter(RUNTIME)
+        .map(|c| c.get(1).unwrap().as_str())
+        .collect();
+    // Class methods (`name(args) {`) and functions assigned to `this`
+    let defined: BTreeSet<&str> =
+        Regex::new(r"(?m)^\s*(?:async\s+|static\s+)?(\w+)\s*\([^)]*\)\s*\{|this\.(\w+)\s*=")
+            .unwrap()
+            .captures_iter(RUNTIME)
+            .filter_map(|c| c.get(1).or_else(|| c.get(2)))
+            .map(|m| m.as_str())
+            .collect();
+    let missing: Vec<_> = called.difference(&defined).collect();
+    assert!(missing.is_empty(), "called but not defined: {:?}", missing);
+}
+
+#[test]
+fn test_runtime_sends_client_header() {
+    // `see_other` answers a redirect with 303 unless this header is present
+    let header = azumi::action::CLIENT_HEADER;
+    assert!(
+        azumi::AZUMI_JS
+            .to_ascii_lowercase()
+            .contains(&format!("\"{}\": \"1\"", header)),
+        "the client runtime must send {}",
+        header
+    );
+    assert!(azumi::AZUMI_JS.contains("X-Azumi-Request"));
+}
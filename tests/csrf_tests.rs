@@ -0,0 +1,285 @@
+//! CSRF Tests
+//!
+//! Double-submit and Origin modes of `azumi::csrf::CsrfLayer` in front of
+//! `#[azumi::action]` routes.
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::routing::get;
+use azumi::action::{redirect, Form, Multipart, Reply};
+use azumi::csrf::CsrfLayer;
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+#[azumi::action]
+pub async fn csrf_ping(Form(_data): Form<Vec<(String, String)>>) -> impl Component {
+    html! { <p>"pong"</p> }
+}
+
+#[azumi::action]
+pub async fn csrf_upload(mut files: Multipart) -> impl Component {
+    let mut names = Vec::new();
+    while let Ok(Some(field)) = files.next_field().await {
+        let name = field.name().unwrap_or_default().to_string();
+        let len = field.bytes().await.map(|b| b.len()).unwrap_or(0);
+        names.push(format!("{}({})", name, len));
+    }
+    let summary = names.join(",");
+    html! { <p>{summary}</p> }
+}
+
+#[azumi::action]
+pub async fn csrf_flash(Form(_data): Form<Vec<(String, String)>>) -> Reply {
+    redirect("/").flash(azumi::flash::Level::Info, "Saved")
+}
+
+async fn page() -> axum::response::Html<String> {
+    axum::response::Html(azumi::render_to_string(&html! {
+        <head>{azumi::csrf::csrf_meta()}</head>
+        <form>{azumi::csrf::csrf_input()}</form>
+    }))
+}
+
+fn app(layer: CsrfLayer) -> axum::Router {
+    azumi::action::register_actions_with_csrf(axum::Router::new().route("/", get(page)), layer)
+}
+
+async fn send(layer: CsrfLayer, request: Request<Body>) -> (StatusCode, Option<String>, String) {
+    let res = app(layer).oneshot(request).await.unwrap();
+    let status = res.status();
+    let cookie = res
+        .headers()
+        .get(header::SET_COOKIE)
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, cookie, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+/// Token issued on a page load
+async fn issue_token() -> String {
+    let (_, cookie, _) = send(
+        CsrfLayer::double_submit(),
+        Request::get("/").body(Body::empty()).unwrap(),
+    )
+    .await;
+    let cookie = cookie.expect("token cookie");
+    let pair = cookie.split(';').next().unwrap();
+    pair.strip_prefix("azumi_csrf=").unwrap().to_string()
+}
+
+fn ping(cookie: Option<&str>, header_token: Option<&str>, body: &str) -> Request<Body> {
+    let mut request = Request::post("/_azumi/action/csrf_ping")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+    if let Some(token) = cookie {
+        request = request.header(header::COOKIE, format!("theme=dark; azumi_csrf={}", token));
+    }
+    if let Some(token) = header_token {
+        request = request.header("x-azumi-csrf", token);
+    }
+    request.body(Body::from(body.to_string())).unwrap()
+}
+
+#[tokio::test]
+async fn test_page_gets_cookie_and_meta() {
+    let (status, cookie, body) = send(
+        CsrfLayer::double_submit(),
+        Request::get("/").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let cookie = cookie.unwrap();
+    assert!(cookie.contains("HttpOnly"));
+    assert!(cookie.contains("SameSite=Strict"));
+    assert!(cookie.contains("Secure"));
+
+    let token = cookie
+        .split(';')
+        .next()
+        .unwrap()
+        .strip_prefix("azumi_csrf=")
+        .unwrap();
+    assert!(body.contains(&format!(r#"<meta name="azumi-csrf" content="{}">"#, token)));
+    assert!(body.contains(&format!(
+        r#"<input type="hidden" name="_azumi_csrf" value="{}">"#,
+        token
+    )));
+}
+
+#[tokio::test]
+async fn test_existing_cookie_is_reused() {
+    let token = issue_token().await;
+    let request = Request::get("/")
+        .header(header::COOKIE, format!("azumi_csrf={}", token))
+        .body(Body::empty())
+        .unwrap();
+    let (_, cookie, body) = send(CsrfLayer::double_submit(), request).await;
+    assert!(cookie.is_none());
+    assert!(body.contains(&token));
+}
+
+#[tokio::test]
+async fn test_missing_token_is_rejected() {
+    let token = issue_token().await;
+    let (status, _, _) = send(CsrfLayer::double_submit(), ping(Some(&token), None, "a=1")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let (status, _, _) = send(CsrfLayer::double_submit(), ping(None, Some(&token), "a=1")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_header_token_is_accepted() {
+    let token = issue_token().await;
+    let (status, _, body) = send(
+        CsrfLayer::double_submit(),
+        ping(Some(&token), Some(&token), "a=1"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("pong"));
+}
+
+#[tokio::test]
+async fn test_form_field_token_is_accepted() {
+    let token = issue_token().await;
+    let body = format!("a=1&_azumi_csrf={}", token);
+    let (status, _, body) = send(CsrfLayer::double_submit(), ping(Some(&token), None, &body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("pong"));
+}
+
+#[tokio::test]
+async fn test_mismatched_or_forged_token_is_rejected() {
+    let token = issue_token().await;
+    let other = issue_token().await;
+    let (status, _, _) = send(
+        CsrfLayer::double_submit(),
+        ping(Some(&token), Some(&other), "a=1"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Attacker-planted cookie matching the header, but not signed by the server
+    let forged = "bm9uY2U.c2lnbmF0dXJl";
+    let (status, _, _) = send(
+        CsrfLayer::double_submit(),
+        ping(Some(forged), Some(forged), "a=1"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+fn upload(cookie: &str, token: &str, file: &[u8]) -> Request<Body> {
+    let mut body = format!(
+        "--XyZ\r\nContent-Disposition: form-data; name=\"_azumi_csrf\"\r\n\r\n{}\r\n\
+         --XyZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.bin\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        token
+    )
+    .into_bytes();
+    body.extend_from_slice(file);
+    body.extend_from_slice(b"\r\n--XyZ--\r\n");
+    Request::post("/_azumi/action/csrf_upload")
+        .header(header::CONTENT_TYPE, "multipart/form-data; boundary=XyZ")
+        .header(header::COOKIE, format!("azumi_csrf={}", cookie))
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_flash_cookie_is_not_a_token() {
+    // A value signed with the same secret for the flash cookie
+    let token = issue_token().await;
+    let request = Request::post("/_azumi/action/csrf_flash")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(header::COOKIE, format!("azumi_csrf={}", token))
+        .header("x-azumi-csrf", &token)
+        .body(Body::from("a=1"))
+        .unwrap();
+    let res = app(CsrfLayer::double_submit())
+        .oneshot(request)
+        .await
+        .unwrap();
+    let flash = res
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok()?.strip_prefix("azumi_flash="))
+        .map(|v| v.split(';').next().unwrap().to_string())
+        .next()
+        .expect("flash cookie");
+
+    let (status, _, _) = send(
+        CsrfLayer::double_submit(),
+        ping(Some(&flash), Some(&flash), "a=1"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_multipart_field_token_is_accepted() {
+    let token = issue_token().await;
+    // Larger than the buffered prefix, so the rest is streamed through
+    let file = vec![b'x'; 200 * 1024];
+    let (status, _, body) = send(CsrfLayer::double_submit(), upload(&token, &token, &file)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        body.contains("_azumi_csrf(") && body.contains("file(204800)"),
+        "{}",
+        body
+    );
+
+    let other = issue_token().await;
+    let (status, _, _) = send(CsrfLayer::double_submit(), upload(&token, &other, b"x")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+fn origin_ping(headers: &[(&str, &str)]) -> Request<Body> {
+    let mut request = Request::post("/_azumi/action/csrf_ping")
+        .header(header::HOST, "app.test")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    request.body(Body::from("a=1")).unwrap()
+}
+
+#[tokio::test]
+async fn test_origin_mode() {
+    let layer = || CsrfLayer::origin().allow_origin("https://admin.test");
+
+    let cases: &[(&[(&str, &str)], StatusCode)] = &[
+        (&[("sec-fetch-site", "same-origin")], StatusCode::OK),
+        (&[("sec-fetch-site", "cross-site")], StatusCode::FORBIDDEN),
+        (&[("origin", "https://app.test")], StatusCode::OK),
+        (&[("origin", "https://evil.test")], StatusCode::FORBIDDEN),
+        (
+            &[
+                ("origin", "https://admin.test"),
+                ("sec-fetch-site", "same-site"),
+            ],
+            StatusCode::OK,
+        ),
+        // Non-browser client
+        (&[], StatusCode::OK),
+    ];
+    for (headers, expected) in cases {
+        let (status, cookie, _) = send(layer(), origin_ping(headers)).await;
+        assert_eq!(status, *expected, "{:?}", headers);
+        assert!(cookie.is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_safe_methods_and_other_paths_are_not_checked() {
+    let (status, _, _) = send(
+        CsrfLayer::origin(),
+        Request::get("/")
+            .header("sec-fetch-site", "cross-site")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,200 @@
+//! Error Boundary Tests
+//!
+//! Fallible rendering: `?` in `html!`, `TryComponent`, and
+//! `@try { ... } @catch(err) { ... }` boundaries.
+
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use azumi::error::Panic;
+use azumi::{html, test, Component, RenderError, TryComponent};
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+struct DbError(&'static str);
+
+impl fmt::Display for DbError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "database error: {}", self.0)
+    }
+}
+
+impl std::error::Error for DbError {}
+
+fn load_orders(fail: bool) -> Result<Vec<&'static str>, DbError> {
+    if fail {
+        Err(DbError("timeout"))
+    } else {
+        Ok(vec!["Lamp", "Desk"])
+    }
+}
+
+struct OrderCount(Result<usize, DbError>);
+
+impl TryComponent for OrderCount {
+    fn try_render(&self, f: &mut fmt::Formatter<'_>) -> Result<(), RenderError> {
+        match &self.0 {
+            Ok(count) => write!(f, "<b>{}</b>", count)?,
+            Err(err) => return Err(DbError(err.0).into()),
+        }
+        Ok(())
+    }
+}
+
+fn orders(fail: bool) -> impl Component {
+    html! {
+        <h1>"Orders"</h1>
+        @try {
+            <ul>
+                @for order in load_orders(fail)? {
+                    <li>{order}</li>
+                }
+            </ul>
+        } @catch(err) {
+            <p>"Unavailable: " {err.downcast_ref::<DbError>().map(|e| e.0)}</p>
+        }
+        <footer>"End"</footer>
+    }
+}
+
+#[test]
+fn test_question_mark_in_html() {
+    assert_eq!(
+        test::render(&orders(false)),
+        "<h1>Orders</h1><ul><li>Lamp</li><li>Desk</li></ul><footer>End</footer>"
+    );
+    // The partial <ul> is dropped; the rest of the page is rendered
+    assert_eq!(
+        test::render(&orders(true)),
+        "<h1>Orders</h1><p>Unavailable: timeout</p><footer>End</footer>"
+    );
+}
+
+/// Implements both `Display` and `TryComponent`
+struct Badge(&'static str);
+
+impl fmt::Display for Badge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl TryComponent for Badge {
+    fn try_render(&self, f: &mut fmt::Formatter<'_>) -> Result<(), RenderError> {
+        write!(f, "<em>{}</em>", azumi::Escaped(self.0))?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_try_component_wins_over_display() {
+    let badge = Badge("new");
+    let name = "<b>";
+    assert_eq!(
+        test::render(&html! { <p>{badge}{name}</p> }),
+        "<p><em>new</em>&lt;b&gt;</p>"
+    );
+}
+
+#[test]
+fn test_catch_handler_runs_once() {
+    let boundary = azumi::error::catch(azumi::from_fn(|_| Err(fmt::Error)), |_| {
+        azumi::from_fn(|f| f.write_str("fallback"))
+    });
+    assert_eq!(azumi::render_to_string(&boundary), "fallback");
+    // The FnOnce handler was consumed by the first render
+    assert_eq!(azumi::render_to_string(&boundary), "");
+}
+
+#[test]
+fn test_try_component() {
+    let page = |count| {
+        html! {
+            @try {
+                <span>"Open: " {OrderCount(count)}</span>
+            } @catch(err) {
+                <span>{err.type_name().rsplit("::").next()}</span>
+            }
+        }
+    };
+    assert_eq!(test::render(&page(Ok(3))), "<span>Open: <b>3</b></span>");
+    assert_eq!(
+        test::render(&page(Err(DbError("down")))),
+        "<span>DbError</span>"
+    );
+}
+
+#[test]
+fn test_let_and_nested_boundaries() {
+    let page = html! {
+        @try {
+            <section>
+                @try {
+                    @let count: u32 = "many".parse()?;
+                    {count}
+                } @catch(err) {
+                    <em>{err}</em>
+                }
+                @let orders = load_orders(true)?;
+                {orders.len()}
+            </section>
+        } @catch(_) {
+            <section>"Outer fallback"</section>
+        }
+    };
+    assert_eq!(test::render(&page), "<section>Outer fallback</section>");
+
+    let page = html! {
+        @try {
+            <section>
+                @try {
+                    @let count: u32 = "many".parse()?;
+                    {count}
+                } @catch(err) {
+                    <em>{err}</em>
+                }
+            </section>
+        } @catch(_) {
+            "Outer fallback"
+        }
+    };
+    assert_eq!(
+        test::render(&page),
+        "<section><em>invalid digit found in string</em></section>"
+    );
+}
+
+#[azumi::component]
+fn Exploding() -> impl Component {
+    let rows: Vec<u32> = Vec::new();
+    html! { <p>{rows[3]}</p> }
+}
+
+#[test]
+fn test_panic_is_caught() {
+    let page = html! {
+        <main>
+            @try {
+                @Exploding()
+            } @catch(err) {
+                <p>"Panicked: " {err.downcast_ref::<Panic>().is_some()}</p>
+            }
+        </main>
+    };
+    assert_eq!(test::render(&page), "<main><p>Panicked: true</p></main>");
+}
+
+#[test]
+fn test_uncaught_error() {
+    let err = azumi::try_render_to_string(&html! {
+        <ul>@for order in load_orders(true)? { <li>{order}</li> }</ul>
+    })
+    .unwrap_err();
+    assert_eq!(err.downcast_ref::<DbError>(), Some(&DbError("timeout")));
+    assert_eq!(err.to_string(), "database error: timeout");
+
+    let html = azumi::try_render_to_string(&orders(true)).unwrap();
+    assert!(html.contains("Unavailable"));
+
+    let res = html! { <p>{OrderCount(Err(DbError("down")))}</p> }.into_response();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
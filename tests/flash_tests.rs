@@ -0,0 +1,193 @@
+//! Flash Tests
+//!
+//! `azumi::action::{redirect, refresh, flash}` replies and the signed flash
+//! cookie read back by `azumi::flash::FlashLayer` on the next page.
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::response::Response;
+use axum::routing::get;
+use azumi::action::{flash, redirect, refresh, Reply};
+use azumi::flash::{Flash, FlashLayer, Level};
+use azumi::html;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use http_body_util::BodyExt;
+use serde::Deserialize;
+use tower::ServiceExt;
+
+#[derive(Deserialize)]
+pub struct NewOrder {
+    pub id: u32,
+}
+
+#[azumi::action]
+pub async fn place_order(order: NewOrder) -> Reply {
+    redirect(format!("/orders/{}", order.id)).flash(Level::Success, "Order <placed>")
+}
+
+#[azumi::action]
+pub async fn reload_orders(_order: NewOrder) -> Reply {
+    refresh()
+}
+
+#[azumi::action]
+pub async fn save_draft(_order: NewOrder) -> Reply {
+    flash(Level::Info, "Draft saved").flash(Level::Warning, "Offline")
+}
+
+async fn order_page() -> axum::response::Html<String> {
+    axum::response::Html(azumi::render_to_string(&html! {
+        <main>{azumi::flash::flash_messages()}</main>
+    }))
+}
+
+fn app() -> axum::Router {
+    azumi::action::register_actions(axum::Router::new().route("/orders/42", get(order_page)))
+        .layer(FlashLayer)
+}
+
+async fn body(res: Response) -> String {
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// Action request sent by the client runtime
+async fn call(action: &str) -> Response {
+    let request = Request::post(format!("/_azumi/action/{}", action))
+        .header(header::CONTENT_TYPE, "application/json")
+        .header("x-azumi-request", "1")
+        .body(Body::from(r#"{"id":42}"#))
+        .unwrap();
+    app().oneshot(request).await.unwrap()
+}
+
+/// Page load by the browser
+async fn page(cookie: Option<&str>) -> Response {
+    let mut request = Request::get("/orders/42").header(header::ACCEPT, "text/html,*/*;q=0.8");
+    if let Some(cookie) = cookie {
+        request = request.header(header::COOKIE, cookie);
+    }
+    app()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+fn set_cookie(res: &Response) -> Option<String> {
+    res.headers()
+        .get(header::SET_COOKIE)
+        .map(|v| v.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn test_redirect_stores_flash_for_next_page() {
+    let res = call("place_order").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["x-azumi-redirect"], "/orders/42");
+    assert!(res.headers().get("x-azumi-flash").is_none());
+    let cookie = set_cookie(&res).unwrap();
+    assert!(cookie.starts_with("azumi_flash="));
+    assert!(cookie.contains("HttpOnly"));
+
+    let pair = cookie.split(';').next().unwrap();
+    let res = page(Some(pair)).await;
+    assert!(set_cookie(&res).unwrap().contains("Max-Age=0"));
+    assert_eq!(
+        body(res).await,
+        concat!(
+            "<main><div data-azumi-flashes>",
+            r#"<div class="azumi-flash azumi-flash-success" role="status">Order &lt;placed&gt;</div>"#,
+            "</div></main>"
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_redirect_without_client_is_see_other() {
+    // Plain <form> post
+    let request = Request::post("/_azumi/action/place_order")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"id":42}"#))
+        .unwrap();
+    let res = app().oneshot(request).await.unwrap();
+    assert_eq!(res.status(), StatusCode::SEE_OTHER);
+    assert_eq!(res.headers()[header::LOCATION], "/orders/42");
+    assert!(res.headers().get("x-azumi-redirect").is_none());
+    assert!(set_cookie(&res).unwrap().starts_with("azumi_flash="));
+    assert_eq!(body(res).await, "");
+}
+
+#[test]
+fn test_redirect_outside_routes_renders_meta_refresh() {
+    use axum::response::IntoResponse;
+    let res = redirect("/orders/42").into_response();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["x-azumi-redirect"], "/orders/42");
+}
+
+#[tokio::test]
+async fn test_other_requests_leave_the_cookie() {
+    let res = call("place_order").await;
+    let cookie = set_cookie(&res).unwrap();
+    let pair = cookie.split(';').next().unwrap();
+
+    // An action fetch, a non-HTML fetch and a POST land before the page load
+    let action = Request::post("/_azumi/action/save_draft")
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::COOKIE, pair)
+        .body(Body::from(r#"{"id":1}"#))
+        .unwrap();
+    let json = Request::get("/orders/42")
+        .header(header::ACCEPT, "application/json")
+        .header(header::COOKIE, pair)
+        .body(Body::empty())
+        .unwrap();
+    let post = Request::post("/orders/42")
+        .header(header::ACCEPT, "text/html")
+        .header(header::COOKIE, pair)
+        .body(Body::empty())
+        .unwrap();
+    for request in [action, json, post] {
+        let res = app().oneshot(request).await.unwrap();
+        assert!(set_cookie(&res).is_none_or(|c| !c.starts_with("azumi_flash=")));
+    }
+
+    let res = page(Some(pair)).await;
+    assert!(set_cookie(&res).unwrap().contains("Max-Age=0"));
+    assert!(body(res).await.contains("Order &lt;placed&gt;"));
+}
+
+#[tokio::test]
+async fn test_refresh() {
+    let res = call("reload_orders").await;
+    assert_eq!(res.headers()["x-azumi-refresh"], "true");
+    assert!(res.headers().get("x-azumi-redirect").is_none());
+}
+
+#[tokio::test]
+async fn test_flash_without_navigation_uses_header() {
+    let res = call("save_draft").await;
+    assert!(set_cookie(&res).is_none());
+    let encoded = res.headers()["x-azumi-flash"].to_str().unwrap();
+    let flashes: Vec<Flash> = serde_json::from_slice(&STANDARD.decode(encoded).unwrap()).unwrap();
+    assert_eq!(
+        flashes,
+        [
+            Flash::new(Level::Info, "Draft saved"),
+            Flash::new(Level::Warning, "Offline"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_tampered_cookie_is_ignored() {
+    let res = page(Some("azumi_flash=W3sibGV2ZWwiOiJlcnJvciJ9XQ.AAAA")).await;
+    assert!(set_cookie(&res).unwrap().contains("Max-Age=0"));
+    assert_eq!(
+        body(res).await,
+        "<main><div data-azumi-flashes></div></main>"
+    );
+
+    let res = page(None).await;
+    assert!(set_cookie(&res).is_none());
+}
@@ -0,0 +1,110 @@
+//! Fragments Tests
+//!
+//! `azumi::fragments::Fragments` returned from `#[azumi::action]`: several
+//! targets with their swap modes in one response, and the swap modes
+//! accepted by `az-on`.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use azumi::fragments::{Fragments, Swap, FRAGMENTS_HEADER};
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use serde::Deserialize;
+use tower::ServiceExt;
+
+#[derive(Deserialize)]
+pub struct AddItem {
+    pub product_id: u32,
+}
+
+fn cart_badge(count: usize) -> impl Component {
+    html! { <span>{count}</span> }
+}
+
+#[azumi::action]
+pub async fn add_to_cart(item: AddItem) -> Fragments {
+    Fragments::new().morph("#cart-badge", cart_badge(3)).inner(
+        format!("#product-{} .stock", item.product_id),
+        html! { <em>"In your cart"</em> },
+    )
+}
+
+#[azumi::action]
+pub async fn plain_greeting(item: AddItem) -> impl Component {
+    html! { <p>"Product " {item.product_id}</p> }
+}
+
+async fn post(path: &str) -> (StatusCode, Option<String>, String) {
+    let router = azumi::action::register_actions(axum::Router::new());
+    let request = Request::post(path)
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"product_id":42}"#))
+        .unwrap();
+    let res = router.oneshot(request).await.unwrap();
+    let status = res.status();
+    let header = res
+        .headers()
+        .get(FRAGMENTS_HEADER)
+        .map(|v| v.to_str().unwrap().to_string());
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, header, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_action_returns_fragments() {
+    let (status, header, body) = post("/_azumi/action/add_to_cart").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(header.as_deref(), Some("2"));
+    assert_eq!(
+        body,
+        concat!(
+            r##"<template az-fragment="#cart-badge" az-swap="morph"><span>3</span></template>"##,
+            r##"<template az-fragment="#product-42 .stock" az-swap="inner"><em>In your cart</em></template>"##,
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_component_actions_are_unchanged() {
+    let (status, header, body) = post("/_azumi/action/plain_greeting").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(header.is_none());
+    assert_eq!(body, "<p>Product 42</p>");
+}
+
+#[test]
+fn test_selectors_are_escaped() {
+    let fragments = Fragments::new().add(r#"[data-name="a"]"#, Swap::Outer, html! { <i>"x"</i> });
+    assert_eq!(fragments.fragments()[0].swap, Swap::Outer);
+    assert_eq!(
+        fragments.to_html(),
+        r#"<template az-fragment="[data-name=&quot;a&quot;]" az-swap="outer"><i>x</i></template>"#
+    );
+}
+
+#[test]
+fn test_swap_modes() {
+    for swap in Swap::ALL {
+        assert_eq!(swap.as_str().parse::<Swap>(), Ok(swap));
+    }
+    assert!("append".parse::<Swap>().is_err());
+
+    let fragments = Fragments::new()
+        .append("#log", html! { <li>"new"</li> })
+        .prepend("#log", html! { <li>"first"</li> })
+        .delete("#row_3");
+    let swaps: Vec<_> = fragments.fragments().iter().map(|f| f.swap).collect();
+    assert_eq!(swaps, [Swap::BeforeEnd, Swap::AfterBegin, Swap::Delete]);
+    assert!(fragments
+        .to_html()
+        .ends_with(r##"<template az-fragment="#row_3" az-swap="delete"></template>"##));
+}
+
+#[test]
+fn test_swap_in_az_on() {
+    let component = html! {
+        <button az-on="click call send -> #log beforeend">"Send"</button>
+    };
+    assert!(azumi::render_to_string(&component)
+        .contains(r##"az-on="click call send -&gt; #log beforeend""##));
+}
@@ -0,0 +1,89 @@
+//! HTML Response Tests
+//!
+//! `azumi::Html` and components returned directly from axum handlers:
+//! status, headers, `Content-Length`, `ETag` and render errors.
+
+use axum::body::Body;
+use axum::http::{header, HeaderValue, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use azumi::{html, Component, Html};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+fn greeting(name: &'static str) -> impl Component {
+    html! { <p>"Hello, " {name}</p> }
+}
+
+async fn get_page(app: axum::Router, uri: &str) -> Response {
+    app.oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+}
+
+async fn body(res: Response) -> String {
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_html_bang_as_response() {
+    let app = axum::Router::new()
+        .route(
+            "/",
+            get(|| async {
+                html! { <h1>"Home"</h1> }
+            }),
+        )
+        .route("/ann", get(|| async { greeting("Ann").into_html() }))
+        .route("/bob", get(|| async { Html::new(greeting("Bob")) }));
+
+    let res = get_page(app.clone(), "/").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers()[header::CONTENT_TYPE],
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(res.headers()[header::CONTENT_LENGTH], "13");
+    assert_eq!(body(res).await, "<h1>Home</h1>");
+
+    let ann = get_page(app.clone(), "/ann").await;
+    let ann_again = get_page(app.clone(), "/ann").await;
+    let bob = get_page(app, "/bob").await;
+    let etag = ann.headers()[header::ETAG].to_str().unwrap().to_string();
+    assert!(etag.starts_with('"') && etag.ends_with('"'));
+    assert_eq!(ann_again.headers()[header::ETAG], etag.as_str());
+    assert_ne!(bob.headers()[header::ETAG], etag.as_str());
+    assert_eq!(body(bob).await, "<p>Hello, Bob</p>");
+}
+
+#[tokio::test]
+async fn test_status_and_headers() {
+    let res = Html::new(greeting("nobody"))
+        .status(StatusCode::NOT_FOUND)
+        .header(header::CACHE_CONTROL, HeaderValue::from_static("no-store"))
+        .header(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/xhtml+xml"),
+        )
+        .into_response();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "no-store");
+    assert_eq!(
+        res.headers().get_all(header::CONTENT_TYPE).iter().count(),
+        1
+    );
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/xhtml+xml");
+    assert_eq!(body(res).await, "<p>Hello, nobody</p>");
+}
+
+#[tokio::test]
+async fn test_render_error_is_500() {
+    let broken = azumi::from_fn(|f| {
+        f.write_str("<p>partial")?;
+        Err(std::fmt::Error)
+    });
+    let res = broken.into_response();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!body(res).await.contains("partial"));
+}
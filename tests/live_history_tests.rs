@@ -0,0 +1,230 @@
+//! Live History Tests
+//!
+//! `#[azumi::live(history = N)]`: generated undo/redo routes and predictions,
+//! client storage (restore the sent state) and server storage (stacks kept
+//! per history id).
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+mod editor {
+    use super::*;
+
+    #[azumi::live(history = 5)]
+    pub struct Editor {
+        pub text: String,
+    }
+
+    #[azumi::live_impl(component = "editor_view")]
+    impl Editor {
+        pub fn append(&mut self) {
+            self.text.push('!');
+        }
+    }
+
+    #[azumi::component]
+    pub fn editor_view<'a>(state: &'a Editor) -> impl Component + 'a {
+        html! { <p>{&state.text}</p> }
+    }
+}
+
+mod counter {
+    use super::*;
+
+    #[azumi::live(history = 2, history_storage = "server")]
+    pub struct Tally {
+        pub count: i32,
+    }
+
+    #[azumi::live_impl(component = "tally_view")]
+    impl Tally {
+        pub fn increment(&mut self) {
+            self.count += 1;
+        }
+
+        pub fn noop(&mut self) {}
+    }
+
+    #[azumi::component]
+    pub fn tally_view<'a>(state: &'a Tally) -> impl Component + 'a {
+        html! { <span>"count:"{state.count}</span> }
+    }
+
+    #[azumi::live]
+    pub struct Plain {
+        pub open: bool,
+    }
+
+    #[azumi::live_impl]
+    impl Plain {
+        pub fn toggle(&mut self) {
+            self.open = !self.open;
+        }
+    }
+}
+
+use counter::{Plain, Tally};
+use editor::Editor;
+
+async fn post(path: &str, history_id: Option<&str>, body: String) -> (StatusCode, String) {
+    let router = azumi::action::register_actions(axum::Router::new());
+    let mut request = Request::post(path);
+    if let Some(id) = history_id {
+        request = request.header(azumi::history::HISTORY_HEADER, id);
+    }
+    let res = router
+        .oneshot(request.body(Body::from(body)).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+/// Signed state embedded in the rendered az-scope attribute
+fn scope_of(html: &str) -> String {
+    let start = html.find("az-scope=\"").unwrap() + "az-scope=\"".len();
+    let end = start + html[start..].find('"').unwrap();
+    html[start..end].replace("&quot;", "\"")
+}
+
+#[test]
+fn test_history_predictions() {
+    let editor = Editor {
+        text: String::new(),
+    };
+    assert_eq!(azumi::get_prediction(&editor, "undo"), Some("@undo"));
+    assert_eq!(azumi::get_prediction(&editor, "redo"), Some("@redo"));
+
+    let plain = Plain { open: false };
+    assert_eq!(azumi::get_prediction(&plain, "undo"), None);
+    assert!(azumi::get_prediction(&plain, "toggle").is_some());
+}
+
+#[test]
+fn test_component_renders_history_attributes() {
+    let editor = Editor { text: "a".into() };
+    let html = azumi::render_to_string(&html! { @editor::editor_view(state = &editor) });
+    assert!(html.contains(r#"az-history="5" az-history-storage="client""#));
+
+    let tally = Tally { count: 0 };
+    let html = azumi::render_to_string(&html! { @counter::tally_view(state = &tally) });
+    assert!(html.contains(r#"az-history="2" az-history-storage="server""#));
+}
+
+#[tokio::test]
+async fn test_client_storage_restores_sent_state() {
+    let previous = Editor {
+        text: "draft".into(),
+    };
+    let (status, body) = post("/_azumi/action/Editor/undo", None, previous.to_scope()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<p>draft</p>"));
+    // The restored state is re-signed with a fresh timestamp
+    assert!(azumi::security::verify_state(&scope_of(&body)).is_ok());
+}
+
+#[tokio::test]
+async fn test_undo_rejects_unsigned_state() {
+    let (status, _) = post(
+        "/_azumi/action/Editor/redo",
+        None,
+        r#"{"text":"forged"}"#.to_string(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_server_storage_undo_redo() {
+    let id = Some("server-undo-redo");
+    let mut scope = Tally { count: 0 }.to_scope();
+
+    for expected in 1..=2 {
+        let (status, body) = post("/_azumi/action/Tally/increment", id, scope).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(&format!("count:{}", expected)));
+        scope = scope_of(&body);
+    }
+
+    let (status, body) = post("/_azumi/action/Tally/undo", id, scope).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("count:1"));
+
+    let (status, body) = post("/_azumi/action/Tally/redo", id, scope_of(&body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("count:2"));
+
+    let (status, _) = post("/_azumi/action/Tally/redo", id, scope_of(&body)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_server_storage_is_bounded_and_skips_noops() {
+    let id = Some("server-bounded");
+    let mut scope = Tally { count: 0 }.to_scope();
+
+    for _ in 0..3 {
+        let (_, body) = post("/_azumi/action/Tally/increment", id, scope).await;
+        scope = scope_of(&body);
+    }
+    // Unchanged state is not recorded
+    let (_, body) = post("/_azumi/action/Tally/noop", id, scope).await;
+    scope = scope_of(&body);
+
+    // Capacity 2: only count 2 and count 1 can be restored
+    for expected in [2, 1] {
+        let (status, body) = post("/_azumi/action/Tally/undo", id, scope).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains(&format!("count:{}", expected)));
+        scope = scope_of(&body);
+    }
+    let (status, body) = post("/_azumi/action/Tally/undo", id, scope).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body, "Nothing to undo");
+}
+
+#[tokio::test]
+async fn test_server_storage_requires_history_id() {
+    let (status, _) = post(
+        "/_azumi/action/Tally/undo",
+        None,
+        Tally { count: 3 }.to_scope(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_server_storage_rejects_unverified_current_state() {
+    let id = Some("server-unverified");
+    let (_, body) = post(
+        "/_azumi/action/Tally/increment",
+        id,
+        Tally { count: 0 }.to_scope(),
+    )
+    .await;
+    let scope = scope_of(&body);
+
+    // Neither a forged nor an oversized body reaches the redo stack
+    let forged = r#"{"count":99}"#.to_string();
+    let (status, body) = post("/_azumi/action/Tally/undo", id, forged).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.starts_with("Security Error"), "{}", body);
+    let oversized = "x".repeat(azumi::history::MAX_SERVER_STATE_BYTES + 1);
+    let (status, body) = post("/_azumi/action/Tally/undo", id, oversized).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("State too large"), "{}", body);
+
+    // The stacks are untouched: the real undo still works, and redo returns
+    // the state it was sent from
+    let (status, body) = post("/_azumi/action/Tally/undo", id, scope).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("count:0"));
+    let (status, body) = post("/_azumi/action/Tally/redo", id, scope_of(&body)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("count:1"));
+}
@@ -0,0 +1,253 @@
+//! Live Component Mount Tests
+//!
+//! `#[azumi::live_impl(mount = "...")]` routes, `LiveComponent::mount` and the
+//! `on_action` / `after_render` lifecycle hooks.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::response::IntoResponse;
+use azumi::{html, Component, LiveComponent, LiveContext};
+use http_body_util::BodyExt;
+use serde::Deserialize;
+use tower::ServiceExt;
+
+mod todos {
+    use super::*;
+
+    #[azumi::live]
+    pub struct TodoList {
+        pub list_id: u64,
+        pub items: Vec<String>,
+        pub locked: bool,
+    }
+
+    #[azumi::live_impl(component = "todo_view", mount = "/todos/:list_id")]
+    impl TodoList {
+        pub fn add(&mut self) {
+            self.items.push("New".to_string());
+        }
+    }
+
+    impl LiveComponent for TodoList {
+        type Params = u64;
+
+        async fn mount(list_id: u64, _ctx: &LiveContext) -> Result<Self, axum::response::Response> {
+            if list_id == 404 {
+                return Err(StatusCode::NOT_FOUND.into_response());
+            }
+            Ok(TodoList {
+                list_id,
+                items: vec![format!("Item of {}", list_id)],
+                locked: list_id == 7,
+            })
+        }
+
+        async fn on_action(
+            &mut self,
+            method: &'static str,
+            _ctx: &LiveContext,
+        ) -> Result<(), axum::response::Response> {
+            if self.locked && method == "add" {
+                return Err(StatusCode::FORBIDDEN.into_response());
+            }
+            Ok(())
+        }
+
+        fn after_render(&self, html: &mut String, _ctx: &LiveContext) {
+            html.push_str("<!-- rendered -->");
+        }
+    }
+
+    #[azumi::component]
+    pub fn todo_view<'a>(state: &'a TodoList) -> impl Component + 'a {
+        html! {
+            <ul>
+                @for item in &state.items {
+                    <li>{item}</li>
+                }
+            </ul>
+        }
+    }
+}
+
+mod search {
+    use super::*;
+
+    #[derive(Deserialize)]
+    pub struct SearchParams {
+        pub q: String,
+    }
+
+    #[azumi::live]
+    pub struct SearchBox {
+        pub query: String,
+    }
+
+    #[azumi::live_impl(component = "search_view", mount = "/search")]
+    impl SearchBox {
+        pub fn clear(&mut self) {
+            self.query = String::new();
+        }
+    }
+
+    impl LiveComponent for SearchBox {
+        type Params = SearchParams;
+
+        async fn mount(
+            params: SearchParams,
+            _ctx: &LiveContext,
+        ) -> Result<Self, axum::response::Response> {
+            Ok(SearchBox { query: params.q })
+        }
+    }
+
+    #[azumi::component]
+    pub fn search_view<'a>(state: &'a SearchBox) -> impl Component + 'a {
+        html! { <p>{&state.query}</p> }
+    }
+}
+
+use todos::TodoList;
+
+async fn send(request: Request<Body>) -> (StatusCode, String) {
+    let router = azumi::action::register_actions(axum::Router::new());
+    let res = router.oneshot(request).await.unwrap();
+    let status = res.status();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+async fn get(path: &str) -> (StatusCode, String) {
+    send(Request::get(path).body(Body::empty()).unwrap()).await
+}
+
+async fn post_state(path: &str, state: String) -> (StatusCode, String) {
+    send(Request::post(path).body(Body::from(state)).unwrap()).await
+}
+
+#[test]
+fn test_render_mounted_tags_first_scope_only() {
+    let outer = TodoList {
+        list_id: 1,
+        items: vec![],
+        locked: false,
+    };
+    let inner = search::SearchBox { query: "q".into() };
+    let plain = azumi::render_to_string(&todos::todo_view::render(
+        todos::todo_view::Props::builder()
+            .state(&outer)
+            .build()
+            .unwrap(),
+    ));
+    assert!(!plain.contains("az-mount="));
+
+    let view = html! {
+        <section>
+            @todos::todo_view(state = &outer)
+            @search::search_view(state = &inner)
+        </section>
+    };
+    let html = azumi::render_mounted(&view, Some("/todos/1?a=\"b\""));
+    assert_eq!(html.matches("az-mount=").count(), 1, "{}", html);
+    assert!(
+        html.contains("az-struct=\"TodoList\" az-mount=\"/todos/1?a=&quot;b&quot;\""),
+        "{}",
+        html
+    );
+}
+
+#[tokio::test]
+async fn test_mount_route_renders_component() {
+    let (status, body) = get("/todos/42").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        body.contains("az-mount=\"/todos/42\""),
+        "missing az-mount: {}",
+        body
+    );
+    assert!(body.contains("az-struct=\"TodoList\""));
+    assert!(body.contains("<li>Item of 42</li>"));
+    assert!(
+        body.ends_with("<!-- rendered -->"),
+        "after_render not called"
+    );
+}
+
+#[tokio::test]
+async fn test_mount_can_reject() {
+    let (status, _) = get("/todos/404").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_mount_rejects_bad_params() {
+    let (status, _) = get("/todos/not-a-number").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_mount_uses_query_without_path_params() {
+    let (status, body) = get("/search?q=rust").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<p>rust</p>"));
+    assert!(body.contains("az-mount=\"/search?q=rust\""));
+}
+
+#[tokio::test]
+async fn test_action_runs_hooks() {
+    let state = TodoList {
+        list_id: 1,
+        items: vec![],
+        locked: false,
+    };
+    let (status, body) = post_state("/_azumi/action/TodoList/add", state.to_scope()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<li>New</li>"));
+    assert!(body.ends_with("<!-- rendered -->"));
+}
+
+#[tokio::test]
+async fn test_on_action_can_reject() {
+    let state = TodoList {
+        list_id: 7,
+        items: vec![],
+        locked: true,
+    };
+    let (status, _) = post_state("/_azumi/action/TodoList/add", state.to_scope()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_action_keeps_mount_url() {
+    let state = TodoList {
+        list_id: 1,
+        items: vec![],
+        locked: false,
+    };
+    let request = Request::post("/_azumi/action/TodoList/add")
+        .header(azumi::MOUNT_HEADER, "/todos/1")
+        .body(Body::from(state.to_scope()))
+        .unwrap();
+    let (status, body) = send(request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        body.contains("az-mount=\"/todos/1\""),
+        "missing az-mount: {}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn test_rejected_state_is_marked_stale() {
+    let router = azumi::action::register_actions(axum::Router::new());
+    let res = router
+        .oneshot(
+            Request::post("/_azumi/action/TodoList/add")
+                .body(Body::from("{}|0|forged"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert!(res.headers().contains_key(azumi::STALE_STATE_HEADER));
+}
@@ -0,0 +1,335 @@
+//! Nested Live Component Tests
+//!
+//! Explicit child ids (`#[live(id)]`), signed events returned by
+//! `#[live(emit)]` child methods and `#[azumi::on_emit]` handlers
+//! re-rendering the parent.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use azumi::emit::{self, Emitted};
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use tower::ServiceExt;
+
+mod board {
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub enum RowEvent {
+        Deleted(u64),
+    }
+
+    #[azumi::live]
+    pub struct Row {
+        #[live(id)]
+        pub id: u64,
+        pub title: String,
+    }
+
+    #[azumi::live_impl(component = "row_view")]
+    impl Row {
+        #[live(emit)]
+        pub fn delete(&mut self) -> RowEvent {
+            RowEvent::Deleted(self.id)
+        }
+
+        // Returns a value without being marked #[live(emit)]
+        pub fn title_len(&mut self) -> usize {
+            self.title.len()
+        }
+
+        pub fn shout(&mut self) {
+            self.title = self.title.to_uppercase();
+        }
+    }
+
+    #[azumi::component]
+    pub fn row_view<'a>(state: &'a Row) -> impl Component + 'a {
+        html! { <li>{&state.title}</li> }
+    }
+
+    #[azumi::live]
+    pub struct Board {
+        pub rows: Vec<Row>,
+        pub last_source: Option<String>,
+    }
+
+    #[azumi::live_impl(component = "board_view")]
+    impl Board {
+        #[azumi::on_emit(Row)]
+        #[live(emit)]
+        pub fn row_event(&mut self, event: Emitted<RowEvent>) -> Option<usize> {
+            self.last_source = event.source_id;
+            match event.event {
+                RowEvent::Deleted(id) => self.rows.retain(|r| r.id != id),
+            }
+            // Tell our own parent how many rows are left once the board is empty
+            self.rows.is_empty().then_some(0)
+        }
+    }
+
+    #[azumi::component]
+    pub fn board_view<'a>(state: &'a Board) -> impl Component + 'a {
+        html! {
+            <ul>
+                @for row in &state.rows {
+                    @row_view(state = row)
+                }
+            </ul>
+        }
+    }
+}
+
+use board::{Board, Row, RowEvent};
+
+fn row(id: u64, title: &str) -> Row {
+    Row {
+        id,
+        title: title.to_string(),
+    }
+}
+
+/// Action request from a component nested in `parents` (`X-Azumi-Parents`)
+async fn post_in(
+    path: &str,
+    parents: &str,
+    body: String,
+) -> (StatusCode, axum::http::HeaderMap, String) {
+    let router = azumi::action::register_actions(axum::Router::new());
+    let request = Request::post(path)
+        .header(emit::PARENTS_HEADER, parents)
+        .body(Body::from(body))
+        .unwrap();
+    let res = router.oneshot(request).await.unwrap();
+    let status = res.status();
+    let headers = res.headers().clone();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, headers, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+async fn post(path: &str, body: String) -> (StatusCode, axum::http::HeaderMap, String) {
+    post_in(path, "", body).await
+}
+
+fn envelope(state: &Board, event: String) -> String {
+    serde_json::json!({
+        "state": state.to_scope(),
+        "event": event,
+    })
+    .to_string()
+}
+
+fn signed(source_id: Option<&str>, event: &RowEvent) -> String {
+    emit::sign(
+        "Row",
+        source_id.map(str::to_string),
+        vec!["Board".to_string()],
+        event,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_child_renders_explicit_id() {
+    let board = Board {
+        rows: vec![row(1, "a"), row(2, "b")],
+        last_source: None,
+    };
+    let html = azumi::render_to_string(&html! { @board::board_view(state = &board) });
+    assert!(html.contains(r#"az-struct="Row" az-id="1""#));
+    assert!(html.contains(r#"az-struct="Row" az-id="2""#));
+    // Components without an id field get no az-id
+    assert!(!html.contains(r#"az-struct="Board" az-id"#));
+}
+
+#[tokio::test]
+async fn test_child_method_emits_event() {
+    let (status, headers, body) = post_in(
+        "/_azumi/action/Row/delete",
+        "Board,Page",
+        row(2, "b").to_scope(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("az-struct=\"Row\""));
+    let emitted = emit::read::<RowEvent>(&headers, "Row", "Board").unwrap();
+    assert_eq!(emitted.event, RowEvent::Deleted(2));
+    assert_eq!(emitted.source_id.as_deref(), Some("2"));
+    assert!(emit::read::<RowEvent>(&headers, "Row", "Page").is_some());
+    // Signed for Row only, and for the components it was rendered in
+    assert!(emit::read::<RowEvent>(&headers, "Board", "Board").is_none());
+    assert!(emit::read::<RowEvent>(&headers, "Row", "Sidebar").is_none());
+}
+
+#[tokio::test]
+async fn test_emit_is_opt_in() {
+    let (status, headers, _) = post("/_azumi/action/Row/title_len", row(1, "a").to_scope()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(headers.get(emit::EMIT_HEADER).is_none());
+}
+
+#[tokio::test]
+async fn test_unit_method_emits_nothing() {
+    let (status, headers, body) = post("/_azumi/action/Row/shout", row(1, "a").to_scope()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<li>A</li>"));
+    assert!(headers.get(emit::EMIT_HEADER).is_none());
+}
+
+#[tokio::test]
+async fn test_parent_handles_event_and_rerenders() {
+    let board = Board {
+        rows: vec![row(1, "a"), row(2, "b")],
+        last_source: None,
+    };
+    let (status, headers, body) = post(
+        "/_azumi/action/Board/__emit/Row",
+        envelope(&board, signed(Some("2"), &RowEvent::Deleted(2))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<li>a</li>"));
+    assert!(!body.contains("<li>b</li>"));
+    assert!(body.contains("&quot;last_source&quot;:&quot;2&quot;"));
+    // Rows remain, so the handler's None is not emitted further
+    assert!(headers.get(emit::EMIT_HEADER).is_none());
+}
+
+#[tokio::test]
+async fn test_parent_handler_can_emit_further() {
+    let board = Board {
+        rows: vec![row(1, "a")],
+        last_source: None,
+    };
+    let (status, headers, _) = post_in(
+        "/_azumi/action/Board/__emit/Row",
+        "Page",
+        envelope(&board, signed(Some("1"), &RowEvent::Deleted(1))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let emitted = emit::read::<usize>(&headers, "Board", "Page").unwrap();
+    assert_eq!(emitted.event, 0);
+}
+
+#[tokio::test]
+async fn test_emit_requires_signed_parent_state() {
+    let body = serde_json::json!({
+        "state": r#"{"rows":[],"last_source":null}"#,
+        "event": signed(None, &RowEvent::Deleted(1)),
+    })
+    .to_string();
+    let (status, _, _) = post("/_azumi/action/Board/__emit/Row", body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_emit_rejects_malformed_event() {
+    let board = Board {
+        rows: vec![],
+        last_source: None,
+    };
+    let parents = vec!["Board".to_string()];
+    let event = emit::sign("Row", None, parents, &serde_json::json!({ "Renamed": "x" })).unwrap();
+    let (status, _, body) = post("/_azumi/action/Board/__emit/Row", envelope(&board, event)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.starts_with("Emit Event Error"));
+}
+
+#[tokio::test]
+async fn test_emit_rejects_forged_event() {
+    let board = Board {
+        rows: vec![row(1, "a")],
+        last_source: None,
+    };
+    // Unsigned event written by the client
+    let forged = serde_json::json!({
+        "source": "Row",
+        "source_id": "1",
+        "event": RowEvent::Deleted(1),
+    })
+    .to_string();
+    let (status, _, body) = post(
+        "/_azumi/action/Board/__emit/Row",
+        envelope(&board, base64_encode(&forged)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("invalid signature"), "{}", body);
+
+    // Signed, but emitted by another struct
+    let parents = vec!["Board".to_string()];
+    let other = emit::sign("Board", None, parents, &RowEvent::Deleted(1)).unwrap();
+    let (status, _, body) = post("/_azumi/action/Board/__emit/Row", envelope(&board, other)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("expected Row"), "{}", body);
+
+    // Signed for a Row rendered inside another component
+    let parents = vec!["Sidebar".to_string()];
+    let elsewhere = emit::sign("Row", None, parents, &RowEvent::Deleted(1)).unwrap();
+    let (status, _, body) = post(
+        "/_azumi/action/Board/__emit/Row",
+        envelope(&board, elsewhere),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("not Board"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_signed_state_is_not_an_event() {
+    let board = Board {
+        rows: vec![row(1, "a")],
+        last_source: None,
+    };
+    // Live state signed by the same server, passed off as an event
+    let state = azumi::security::sign_state(
+        &serde_json::json!({
+            "source": "Row",
+            "source_id": "1",
+            "parents": ["Board"],
+            "event": RowEvent::Deleted(1),
+        })
+        .to_string(),
+    );
+    let (status, _, body) = post(
+        "/_azumi/action/Board/__emit/Row",
+        envelope(&board, base64_encode(&state)),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("not an emitted event"), "{}", body);
+
+    // and the other way round
+    use base64::Engine as _;
+    let event = base64::engine::general_purpose::STANDARD
+        .decode(signed(None, &RowEvent::Deleted(1)))
+        .unwrap();
+    let (status, _, body) = post(
+        "/_azumi/action/Row/shout",
+        String::from_utf8(event).unwrap(),
+    )
+    .await;
+    assert_ne!(status, StatusCode::OK);
+    assert!(body.contains("State Deserialization Error"), "{}", body);
+}
+
+fn base64_encode(s: &str) -> String {
+    use base64::Engine as _;
+    base64::engine::general_purpose::STANDARD.encode(s)
+}
+
+#[tokio::test]
+async fn test_unhandled_source_is_not_found() {
+    let board = Board {
+        rows: vec![],
+        last_source: None,
+    };
+    let (status, _, _) = post(
+        "/_azumi/action/Row/__emit/Board",
+        envelope(&board, signed(None, &RowEvent::Deleted(1))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
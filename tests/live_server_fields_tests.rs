@@ -0,0 +1,128 @@
+//! Live Server-Only Field Tests
+//!
+//! `#[live(server)]` fields must never reach `az-scope` and must be reloaded
+//! by `LiveHydrate::hydrate` before a live method runs.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use azumi::{LiveContext, LiveHydrate};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+#[derive(Clone, Debug, Default)]
+pub struct Permissions {
+    pub can_edit: bool,
+}
+
+#[azumi::live]
+#[derive(Default)]
+pub struct Board {
+    pub title: String,
+    #[live(server)]
+    pub can_edit: bool,
+}
+
+impl LiveHydrate for Board {
+    async fn hydrate(&mut self, ctx: &LiveContext) -> Result<(), axum::response::Response> {
+        match ctx.extension::<Permissions>() {
+            Some(perms) => {
+                self.can_edit = perms.can_edit;
+                Ok(())
+            }
+            None => Err(axum::response::IntoResponse::into_response(
+                StatusCode::UNAUTHORIZED,
+            )),
+        }
+    }
+}
+
+#[azumi::live_impl]
+impl Board {
+    pub fn rename(&mut self) {
+        if self.can_edit {
+            self.title = "Renamed".to_string();
+        }
+    }
+}
+
+#[azumi::live]
+#[derive(Default)]
+pub struct PlainCounter {
+    pub count: i32,
+}
+
+fn json_part(scope: &str) -> String {
+    let mut parts = scope.rsplitn(3, '|');
+    parts.next();
+    parts.next();
+    parts.next().unwrap().to_string()
+}
+
+async fn call(path: &str, body: String, perms: Option<Permissions>) -> (StatusCode, String) {
+    let mut router = azumi::action::register_actions(axum::Router::new());
+    if let Some(perms) = perms {
+        router = router.layer(axum::Extension(perms));
+    }
+    let res = router
+        .oneshot(
+            Request::post(path)
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = res.status();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[test]
+fn test_server_field_excluded_from_scope() {
+    let board = Board {
+        title: "Todo".into(),
+        can_edit: true,
+    };
+    let json = json_part(&board.to_scope());
+    assert_eq!(json, r#"{"title":"Todo"}"#);
+}
+
+#[test]
+fn test_server_field_defaults_on_deserialize() {
+    let board: Board = serde_json::from_str(r#"{"title":"Todo","can_edit":true}"#).unwrap();
+    assert!(
+        !board.can_edit,
+        "server field must not be trusted from the client"
+    );
+}
+
+#[tokio::test]
+async fn test_plain_struct_gets_noop_hydrate() {
+    let mut counter = PlainCounter::default();
+    let ctx = LiveContext::new(Request::new(()).into_parts().0);
+    assert!(counter.hydrate(&ctx).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_handler_hydrates_before_method() {
+    let state = Board {
+        title: "Todo".into(),
+        can_edit: false,
+    }
+    .to_scope();
+    let (status, body) = call(
+        "/_azumi/action/Board/rename",
+        state,
+        Some(Permissions { can_edit: true }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, r#"{"title":"Renamed"}"#);
+}
+
+#[tokio::test]
+async fn test_hydrate_error_short_circuits() {
+    let state = Board::default().to_scope();
+    let (status, _) = call("/_azumi/action/Board/rename", state, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,255 @@
+//! OpenAPI Tests
+//!
+//! Action registry metadata (`azumi::action::registry()`),
+//! `#[derive(azumi::ApiSchema)]` and the generated OpenAPI document.
+
+use azumi::action::{ActionKind, Form, Multipart};
+use azumi::openapi::{ApiSchema, Components};
+use azumi::{html, Component};
+use serde::Deserialize;
+use serde_json::json;
+
+/// Where a parcel goes
+#[derive(Deserialize, azumi::ApiSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Shipment {
+    /// Recipient name
+    pub recipient_name: String,
+    pub weight_kg: f64,
+    pub express: Option<bool>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(rename = "zip")]
+    pub postal_code: String,
+    #[serde(skip)]
+    pub internal: u32,
+    pub priority: Priority,
+}
+
+#[derive(Deserialize, azumi::ApiSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    High,
+}
+
+#[derive(Deserialize, azumi::ApiSchema)]
+pub struct Tree {
+    pub children: Vec<Tree>,
+}
+
+#[derive(Deserialize)]
+pub struct Untyped {
+    pub note: String,
+}
+
+#[azumi::action]
+pub async fn ship(data: Shipment) -> impl Component {
+    html! { <p>{data.recipient_name}</p> }
+}
+
+#[azumi::action]
+pub async fn annotate(Form(data): Form<Untyped>) -> impl Component {
+    html! { <p>{data.note}</p> }
+}
+
+#[azumi::action]
+pub async fn attach(mut files: Multipart) -> impl Component {
+    let _ = files.next_field().await;
+    html! { <p>"ok"</p> }
+}
+
+mod widgets {
+    use super::*;
+
+    #[azumi::live(history = 3)]
+    pub struct Dial {
+        pub value: i32,
+    }
+
+    #[azumi::live_impl(component = "dial_view")]
+    impl Dial {
+        pub fn turn(&mut self) {
+            self.value += 1;
+        }
+    }
+
+    #[azumi::component]
+    pub fn dial_view<'a>(state: &'a Dial) -> impl Component + 'a {
+        html! { <span>{state.value}</span> }
+    }
+
+    #[azumi::live]
+    pub struct Gauge {
+        pub level: u8,
+    }
+
+    #[azumi::live_impl]
+    impl Gauge {
+        pub fn fill(&mut self) {
+            self.level = 100;
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+fn report_router() -> axum::routing::MethodRouter<()> {
+    axum::routing::post(|| async { "ok" })
+}
+
+// Hand-registered route with metadata
+azumi::inventory::submit! {
+    azumi::action::ActionEntry::new("/reports/export", report_router)
+        .http_method("POST")
+        .name("export")
+        .content_type("application/json")
+        .location(file!(), line!())
+}
+
+fn entry(path: &str) -> &'static azumi::action::ActionEntry {
+    azumi::action::registry()
+        .into_iter()
+        .find(|e| e.path == path)
+        .unwrap_or_else(|| panic!("{} not registered", path))
+}
+
+#[test]
+fn test_registry_is_sorted_and_complete() {
+    let paths: Vec<_> = azumi::action::registry().iter().map(|e| e.path).collect();
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert_eq!(paths, sorted);
+    for path in [
+        "/_azumi/action/ship",
+        "/_azumi/action/annotate",
+        "/_azumi/action/attach",
+        "/_azumi/action/Dial/turn",
+        "/_azumi/action/Dial/undo",
+        "/_azumi/action/Dial/redo",
+        "/_azumi/action/Gauge/fill",
+    ] {
+        assert!(paths.contains(&path), "{}", path);
+    }
+}
+
+#[test]
+fn test_action_metadata() {
+    let ship = entry("/_azumi/action/ship");
+    assert_eq!(ship.kind, ActionKind::Action);
+    assert_eq!(ship.http_method, "POST");
+    assert_eq!(ship.name, "ship");
+    assert_eq!(ship.payload_type, Some("Shipment"));
+    assert_eq!(ship.content_type, Some("application/json"));
+    assert_eq!(ship.response_type, Some("impl Component"));
+    assert!(ship.file.ends_with("openapi_tests.rs"));
+    assert!(ship.line > 0);
+
+    let annotate = entry("/_azumi/action/annotate");
+    assert_eq!(annotate.payload_type, Some("Form < Untyped >"));
+    assert_eq!(
+        annotate.content_type,
+        Some("application/x-www-form-urlencoded")
+    );
+    assert!((annotate.payload_schema)(&mut Components::new()).is_none());
+
+    let attach = entry("/_azumi/action/attach");
+    assert_eq!(attach.content_type, Some("multipart/form-data"));
+
+    let export = entry("/reports/export");
+    assert_eq!(export.kind, ActionKind::Custom);
+    assert_eq!(export.http_method, "POST");
+    assert_eq!(export.name, "export");
+    assert_eq!(export.content_type, Some("application/json"));
+    assert_eq!(export.payload_type, None);
+    assert!(export.file.ends_with("openapi_tests.rs"));
+}
+
+#[test]
+fn test_live_metadata() {
+    let turn = entry("/_azumi/action/Dial/turn");
+    assert_eq!(turn.kind, ActionKind::LiveMethod);
+    assert_eq!(turn.live_struct, Some("Dial"));
+    assert_eq!(turn.content_type, Some("text/plain"));
+    assert_eq!(turn.response_content_type, "text/html");
+
+    assert_eq!(
+        entry("/_azumi/action/Dial/undo").kind,
+        ActionKind::LiveHistory
+    );
+    assert_eq!(
+        entry("/_azumi/action/Gauge/fill").response_content_type,
+        "application/json"
+    );
+}
+
+#[test]
+fn test_derived_schema() {
+    let mut components = Components::new();
+    let reference = Shipment::api_schema(&mut components);
+    assert_eq!(
+        reference,
+        json!({ "$ref": "#/components/schemas/Shipment" })
+    );
+
+    let schema = &components.schemas()["Shipment"];
+    assert_eq!(schema["description"], "Where a parcel goes");
+    assert_eq!(
+        schema["properties"]["recipientName"],
+        json!({ "type": "string", "description": "Recipient name" })
+    );
+    assert_eq!(schema["properties"]["weightKg"]["type"], "number");
+    assert_eq!(schema["properties"]["tags"]["items"]["type"], "string");
+    assert!(schema["properties"].get("zip").is_some());
+    assert!(schema["properties"].get("internal").is_none());
+    assert_eq!(
+        schema["required"],
+        json!(["recipientName", "weightKg", "zip", "priority"])
+    );
+    assert_eq!(
+        components.schemas()["Priority"],
+        json!({ "type": "string", "enum": ["low", "high"] })
+    );
+}
+
+#[test]
+fn test_recursive_schema() {
+    let mut components = Components::new();
+    Tree::api_schema(&mut components);
+    assert_eq!(
+        components.schemas()["Tree"]["properties"]["children"]["items"],
+        json!({ "$ref": "#/components/schemas/Tree" })
+    );
+}
+
+#[test]
+fn test_openapi_document() {
+    let doc = azumi::openapi::document("Shop", "1.2.0");
+    assert_eq!(doc["openapi"], "3.0.3");
+    assert_eq!(doc["info"]["title"], "Shop");
+
+    let ship = &doc["paths"]["/_azumi/action/ship"]["post"];
+    assert_eq!(ship["operationId"], "ship");
+    assert_eq!(ship["tags"], json!(["actions"]));
+    assert_eq!(
+        ship["requestBody"]["content"]["application/json"]["schema"],
+        json!({ "$ref": "#/components/schemas/Shipment" })
+    );
+    assert!(ship["responses"]["200"]["content"]
+        .get("text/html")
+        .is_some());
+    assert!(ship["x-azumi-source"]
+        .as_str()
+        .unwrap()
+        .contains("openapi_tests.rs:"));
+    assert!(doc["components"]["schemas"].get("Priority").is_some());
+
+    let turn = &doc["paths"]["/_azumi/action/Dial/turn"]["post"];
+    assert_eq!(turn["operationId"], "Dial_turn");
+    assert_eq!(turn["tags"], json!(["Dial"]));
+    assert_eq!(turn["x-azumi-kind"], "live_method");
+    assert!(turn["responses"].get("400").is_some());
+    assert_eq!(
+        turn["requestBody"]["content"]["text/plain"]["schema"]["type"],
+        "string"
+    );
+}
@@ -0,0 +1,161 @@
+//! Page Meta Tests
+//!
+//! Page metadata is scoped to a render (`#[azumi::page]`,
+//! `context::set_page_meta`) or to a task (`context::with_page_meta`), so
+//! concurrent requests never see each other's metadata.
+
+use axum::body::Body;
+use axum::extract::Path;
+use axum::http::Request;
+use axum::routing::get;
+use azumi::context::{get_page_meta, set_page_meta, with_page_meta, PageMeta};
+use azumi::{html, seo, Component};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+#[azumi::component]
+fn MetaLayout(children: impl Component) -> impl Component {
+    html! {
+        <html>
+            <head>{seo::render_automatic_seo()}</head>
+            <body>{children}</body>
+        </html>
+    }
+}
+
+/// Open orders
+#[azumi::page]
+fn orders_page() -> impl Component {
+    html! {
+        @MetaLayout {
+            @suspense(html! { "..." }) {
+                @let _ = tokio::task::yield_now().await;
+                <p>{get_page_meta().title.unwrap_or_default()}</p>
+            }
+        }
+    }
+}
+
+/// Customer accounts
+#[azumi::page]
+fn customers_page() -> impl Component {
+    html! {
+        @MetaLayout {
+            @suspense(html! { "..." }) {
+                @let _ = tokio::task::yield_now().await;
+                <p>{get_page_meta().title.unwrap_or_default()}</p>
+            }
+        }
+    }
+}
+
+async fn product(Path(name): Path<String>) -> axum::response::Html<String> {
+    let meta = PageMeta {
+        title: Some(name.clone()),
+        description: Some(format!("All about {}", name)),
+        image: None,
+    };
+    with_page_meta(meta, async move {
+        tokio::task::yield_now().await;
+        axum::response::Html(azumi::render_to_string(&html! {
+            @MetaLayout { <h1>{name}</h1> }
+        }))
+    })
+    .await
+}
+
+async fn get_body(app: axum::Router, uri: String) -> String {
+    let res = app
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[test]
+fn test_meta_is_scoped_to_render() {
+    assert!(get_page_meta().title.is_none());
+    {
+        let _guard = set_page_meta(Some("Draft".to_string()), None, None);
+        assert_eq!(get_page_meta().title.as_deref(), Some("Draft"));
+    }
+    assert!(get_page_meta().title.is_none());
+
+    let html = azumi::render_to_string(&orders_page());
+    assert!(html.contains("<title>Orders Page</title>"), "{}", html);
+    assert!(get_page_meta().title.is_none());
+    assert!(get_page_meta().description.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_pages_keep_their_meta() {
+    let mut tasks = Vec::new();
+    for i in 0..40 {
+        tasks.push(tokio::spawn(async move {
+            let (html, title) = if i % 2 == 0 {
+                let html = azumi::suspense::render_to_string_async(&orders_page());
+                (html.await, "Orders Page")
+            } else {
+                let html = azumi::suspense::render_to_string_async(&customers_page());
+                (html.await, "Customers Page")
+            };
+            (html, title)
+        }));
+    }
+    for task in tasks {
+        let (html, title) = task.await.unwrap();
+        assert!(
+            html.contains(&format!("<title>{}</title>", title)),
+            "{}",
+            html
+        );
+        assert!(html.contains(&format!("<p>{}</p>", title)), "{}", html);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_requests_keep_their_meta() {
+    let app = axum::Router::new().route("/products/:name", get(product));
+    let mut requests = Vec::new();
+    for i in 0..40 {
+        let app = app.clone();
+        requests.push(tokio::spawn(async move {
+            let name = format!("product-{}", i);
+            (get_body(app, format!("/products/{}", name)).await, name)
+        }));
+    }
+    for request in requests {
+        let (html, name) = request.await.unwrap();
+        assert!(
+            html.contains(&format!("<title>{}</title>", name)),
+            "{}",
+            html
+        );
+        assert!(
+            html.contains(&format!(r#"content="All about {}""#, name)),
+            "{}",
+            html
+        );
+        assert_eq!(html.matches("<title>").count(), 1);
+    }
+    assert!(get_page_meta().title.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_stream_keeps_task_meta() {
+    let app = axum::Router::new().route(
+        "/report",
+        get(|| {
+            let meta = PageMeta {
+                title: Some("Report".to_string()),
+                ..PageMeta::default()
+            };
+            with_page_meta(meta, async {
+                azumi::stream::stream(|| html! { @MetaLayout { <h1>"Report"</h1> } })
+            })
+        }),
+    );
+    let html = get_body(app, "/report".to_string()).await;
+    assert!(html.contains("<title>Report</title>"), "{}", html);
+}
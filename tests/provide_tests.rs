@@ -0,0 +1,96 @@
+//! Provide Tests
+//!
+//! `@provide(value) { ... }` and `azumi::provide` make values available to
+//! `azumi::use_context` while the block renders, including in components
+//! and `@suspense` blocks.
+
+use azumi::{html, provide, test, use_context, Component};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+#[derive(Clone)]
+struct User(String);
+
+#[azumi::component]
+fn ThemedButton<'a>(label: &'a str) -> impl Component + 'a {
+    let theme = use_context::<Theme>().unwrap_or_default();
+    let theme = format!("{:?}", theme);
+    html! { <button data-theme={theme}>{label}</button> }
+}
+
+fn greeting() -> impl Component {
+    azumi::from_fn(|f| {
+        let user = use_context::<User>().map_or("guest".to_string(), |user| user.0);
+        write!(f, "Hello, {}", user)
+    })
+}
+
+#[test]
+fn test_component_reads_provided_value() {
+    let output = test::render(&html! {
+        @ThemedButton(label = "Before")
+        @provide(Theme::Dark) {
+            @ThemedButton(label = "Save")
+        }
+        @ThemedButton(label = "After")
+    });
+    assert_eq!(
+        output,
+        concat!(
+            r#"<button data-theme="Light">Before</button>"#,
+            r#"<button data-theme="Dark">Save</button>"#,
+            r#"<button data-theme="Light">After</button>"#
+        )
+    );
+}
+
+#[test]
+fn test_innermost_value_wins() {
+    let output = test::render(&html! {
+        @provide(User("ada".to_string())) {
+            <p>{greeting()}</p>
+            @provide(User("grace".to_string())) {
+                @provide(Theme::Dark) {
+                    <p>{greeting()}</p>
+                }
+            }
+            <p>{greeting()}</p>
+        }
+    });
+    assert_eq!(
+        output,
+        "<p>Hello, ada</p><p>Hello, grace</p><p>Hello, ada</p>"
+    );
+    assert!(use_context::<User>().is_none());
+}
+
+#[test]
+fn test_provide_in_rust() {
+    let page = provide(Theme::Dark, html! { @ThemedButton(label = "Save") });
+    assert_eq!(
+        test::render(&page),
+        r#"<button data-theme="Dark">Save</button>"#
+    );
+    assert_eq!(use_context::<Theme>(), None);
+}
+
+#[tokio::test]
+async fn test_suspense_keeps_provided_values() {
+    let page = html! {
+        @provide(User("ada".to_string())) {
+            @suspense(html! { "..." }) {
+                @let name = async { use_context::<User>().map(|user| user.0) }.await;
+                <p>{name.unwrap_or_default()}</p>
+                <p>{greeting()}</p>
+            }
+        }
+    };
+    let output = azumi::suspense::render_to_string_async(&page);
+    let output = tokio::spawn(output).await.unwrap();
+    assert_eq!(output, "<p>ada</p><p>Hello, ada</p>");
+}
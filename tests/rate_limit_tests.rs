@@ -0,0 +1,185 @@
+//! Rate Limit Tests
+//!
+//! `rate_limit(..)` on `#[azumi::action]` and `#[azumi::route]` live methods:
+//! per-IP, per-user and per-state buckets, `429` with `Retry-After`.
+
+use axum::body::Body;
+use axum::extract::Request;
+use axum::http::StatusCode;
+use axum::middleware::{from_fn, Next};
+use axum::response::Response;
+use azumi::rate_limit::{RateLimit, RateLimitKey, TrustedProxy, UserKey};
+use azumi::{html, Component};
+use serde::Deserialize;
+use std::time::Duration;
+use tower::ServiceExt;
+
+#[derive(Deserialize)]
+pub struct Message {
+    pub text: String,
+}
+
+#[azumi::action(rate_limit(2, per_hour))]
+pub async fn send_message(data: Message) -> impl Component {
+    html! { <p>{data.text}</p> }
+}
+
+/// Stand-in for an auth middleware
+async fn identify(mut request: Request, next: Next) -> Response {
+    if let Some(user) = request.headers().get("x-user") {
+        let user = user.to_str().unwrap().to_string();
+        request.extensions_mut().insert(UserKey(user));
+    }
+    next.run(request).await
+}
+
+#[azumi::action(rate_limit(1, per_minute, key = user), layer = from_fn(identify))]
+pub async fn like(data: Message) -> impl Component {
+    html! { <p>{data.text}</p> }
+}
+
+mod posts {
+    #[azumi::live]
+    pub struct Post {
+        #[live(id)]
+        pub id: u32,
+        pub likes: u32,
+    }
+
+    #[azumi::live_impl]
+    impl Post {
+        #[azumi::route(rate_limit(1, per_hour, key = state))]
+        pub fn upvote(&mut self) {
+            self.likes += 1;
+        }
+    }
+}
+
+use posts::Post;
+
+async fn post(path: &str, headers: &[(&str, &str)], body: String) -> (StatusCode, Option<String>) {
+    send(path, None, false, headers, body).await
+}
+
+/// Request from the peer address `peer`, optionally through a trusted proxy
+async fn send(
+    path: &str,
+    peer: Option<&str>,
+    proxy: bool,
+    headers: &[(&str, &str)],
+    body: String,
+) -> (StatusCode, Option<String>) {
+    let router = azumi::action::register_actions(axum::Router::new());
+    let mut request = axum::http::Request::post(path).header("content-type", "application/json");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    if let Some(peer) = peer {
+        let addr: std::net::SocketAddr = format!("{}:4000", peer).parse().unwrap();
+        request = request.extension(axum::extract::ConnectInfo(addr));
+    }
+    if proxy {
+        request = request.extension(TrustedProxy);
+    }
+    let res = router
+        .oneshot(request.body(Body::from(body)).unwrap())
+        .await
+        .unwrap();
+    let retry_after = res
+        .headers()
+        .get("retry-after")
+        .map(|v| v.to_str().unwrap().to_string());
+    (res.status(), retry_after)
+}
+
+fn message() -> String {
+    r#"{"text":"hi"}"#.to_string()
+}
+
+#[tokio::test]
+async fn test_ip_limit() {
+    let path = "/_azumi/action/send_message";
+    // Spoofed forwarding headers are ignored without a trusted proxy
+    for spoofed in ["198.51.100.1", "198.51.100.2"] {
+        let headers = [("x-forwarded-for", spoofed)];
+        let (status, _) = send(path, Some("203.0.113.7"), false, &headers, message()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let (status, retry_after) = send(path, Some("203.0.113.7"), false, &[], message()).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    // One token refills every 30 minutes
+    let retry_after: u64 = retry_after.unwrap().parse().unwrap();
+    assert!((1790..=1800).contains(&retry_after), "{}", retry_after);
+
+    // Other clients have their own bucket
+    let (status, _) = send(path, Some("203.0.113.8"), false, &[], message()).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_trusted_proxy_limit() {
+    let path = "/_azumi/action/send_message";
+    // The proxy appends the address it saw; the first entry is client-controlled
+    for forwarded in ["192.0.2.1, 203.0.113.20", "192.0.2.2, 203.0.113.20"] {
+        let headers = [("x-forwarded-for", forwarded)];
+        let (status, _) = send(path, Some("10.0.0.1"), true, &headers, message()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+    let headers = [("x-forwarded-for", "203.0.113.20")];
+    let (status, _) = send(path, Some("10.0.0.1"), true, &headers, message()).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+
+    let headers = [("x-real-ip", "203.0.113.21")];
+    let (status, _) = send(path, Some("10.0.0.1"), true, &headers, message()).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_user_limit() {
+    let (status, _) = post("/_azumi/action/like", &[("x-user", "ann")], message()).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = post("/_azumi/action/like", &[("x-user", "ann")], message()).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    let (status, _) = post("/_azumi/action/like", &[("x-user", "bo")], message()).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_state_limit() {
+    let first = Post { id: 1, likes: 0 }.to_scope();
+    let second = Post { id: 2, likes: 0 }.to_scope();
+
+    let (status, _) = post("/_azumi/action/Post/upvote", &[], first.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, retry_after) = post("/_azumi/action/Post/upvote", &[], first).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(retry_after.is_some());
+
+    let (status, _) = post("/_azumi/action/Post/upvote", &[], second).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[test]
+fn test_routes_have_separate_buckets() {
+    let limit = RateLimit::new(1, Duration::from_secs(60), RateLimitKey::Ip);
+    assert!(limit.check("/test/a", "ip:1").is_ok());
+    assert!(limit.check("/test/a", "ip:1").is_err());
+    assert!(limit.check("/test/b", "ip:1").is_ok());
+    let limited = limit.check("/test/b", "ip:1").unwrap_err();
+    assert_eq!(limited.retry_after_secs(), 60);
+}
+
+#[test]
+fn test_full_table_keeps_active_buckets() {
+    let limit = RateLimit::new(1, Duration::from_secs(3600), RateLimitKey::Ip);
+    assert!(limit.check("/test/full", "ip:victim").is_ok());
+    // Rotating keys fills the table and forces evictions
+    for i in 0..azumi::rate_limit::MAX_BUCKETS + 10 {
+        let _ = limit.check("/test/full-spam", &format!("ip:{}", i));
+        if i % 20_000 == 0 {
+            // The victim stays recently used, so its bucket survives
+            assert!(limit.check("/test/full", "ip:victim").is_err());
+        }
+    }
+    assert!(limit.check("/test/full", "ip:victim").is_err());
+}
@@ -0,0 +1,114 @@
+//! Render Tests
+//!
+//! `azumi::render`: pre-sized buffers, size hints, bulk escaping and the
+//! merged static writes generated by `html!`.
+
+use azumi::render::{render_into, render_to_bytes};
+use azumi::{html, Component, Escaped};
+use std::fmt::Write as _;
+
+fn table(rows: usize) -> impl Component {
+    html! {
+        <table>
+            @for i in 0..rows {
+                <tr><td>{i}</td><td>{format!("<row {}>", i)}</td></tr>
+            }
+        </table>
+    }
+}
+
+/// Counts the writes a component makes
+fn writes(component: &impl Component) -> Vec<String> {
+    struct Recorder(Vec<String>);
+    impl std::fmt::Write for Recorder {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            self.0.push(s.to_string());
+            Ok(())
+        }
+    }
+    struct Display<'a, C>(&'a C);
+    impl<C: Component> std::fmt::Display for Display<'_, C> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            self.0.render(f)
+        }
+    }
+    let mut recorder = Recorder(Vec::new());
+    write!(recorder, "{}", Display(component)).unwrap();
+    recorder.0
+}
+
+#[test]
+fn test_bytes_match_string() {
+    let bytes = render_to_bytes(&table(3));
+    assert_eq!(&bytes[..], azumi::render_to_string(&table(3)).as_bytes());
+    assert!(std::str::from_utf8(&bytes)
+        .unwrap()
+        .contains("<tr><td>2</td><td>&lt;row 2&gt;</td></tr>"));
+}
+
+#[test]
+fn test_render_into_appends() {
+    let mut buf = b"<!DOCTYPE html>".to_vec();
+    render_into(&html! { <p>"Hi"</p> }, &mut buf).unwrap();
+    assert_eq!(buf, b"<!DOCTYPE html><p>Hi</p>");
+}
+
+#[test]
+fn test_size_hint_is_static_markup() {
+    let page = html! { <p>"Hi " {"there"}</p> };
+    assert_eq!(page.size_hint(), "<p>Hi </p>".len());
+    assert_eq!("plain".size_hint(), 5);
+    assert!(table(100).size_hint() < azumi::render_to_string(&table(100)).len());
+}
+
+#[test]
+fn test_static_text_is_coalesced() {
+    let list = html! {
+        <ul>
+            <li>"Tom & Jerry"</li>
+            <li>"Spike"</li>
+        </ul>
+    };
+    assert_eq!(
+        writes(&list),
+        ["<ul><li>Tom &amp; Jerry</li><li>Spike</li></ul>"]
+    );
+
+    let name = "Tyke";
+    let list = html! { <ul><li>{name}</li></ul> };
+    assert_eq!(writes(&list), ["<ul><li>", "Tyke", "</li></ul>"]);
+}
+
+#[test]
+fn test_bulk_escaping() {
+    let mut out = String::new();
+    azumi::write_escaped(&mut out, "héllo <b> & 'x' \"y\" ✓").unwrap();
+    assert_eq!(out, "héllo &lt;b&gt; &amp; &#x27;x&#x27; &quot;y&quot; ✓");
+
+    // Safe runs are written whole
+    assert_eq!(writes(&"a<b".to_string()), ["a", "&lt;", "b"]);
+    assert_eq!(writes(&"plain text".to_string()), ["plain text"]);
+
+    // Display values are escaped as they are formatted
+    assert_eq!(format!("{}", Escaped(42)), "42");
+    assert_eq!(
+        format!("{}", Escaped(format_args!("{}<{}", 1, 2))),
+        "1&lt;2"
+    );
+}
+
+/// `html!` escapes static text and attributes at compile time with its own
+/// copy of the escaping; it must agree with `azumi::write_escaped`
+#[test]
+fn test_static_escaping_matches_runtime() {
+    const SAMPLE: &str = "a<b>&c\"d'e é ✓ &amp; \\ / = ` ~";
+    let mut escaped = String::new();
+    azumi::write_escaped(&mut escaped, SAMPLE).unwrap();
+    assert_eq!(escaped, Escaped(SAMPLE).to_string());
+
+    let text = azumi::render_to_string(&html! { <p>"a<b>&c\"d'e é ✓ &amp; \\ / = ` ~"</p> });
+    assert_eq!(text, format!("<p>{}</p>", escaped));
+
+    let attr = azumi::render_to_string(&html! { <p title="a<b>&c\"d'e é ✓ &amp; \\ / = ` ~"></p> });
+    assert_eq!(attr, format!("<p title=\"{}\"></p>", escaped));
+}
@@ -0,0 +1,124 @@
+//! Routes Prefix Tests
+//!
+//! `azumi::routes().prefix(..)`: actions, live methods, the client script and
+//! telemetry mounted under a base path, with the prefix injected into the
+//! client runtime.
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use azumi::csrf::CsrfLayer;
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use serde::Deserialize;
+use tower::ServiceExt;
+
+#[derive(Deserialize)]
+pub struct Echo {
+    pub text: String,
+}
+
+#[azumi::action]
+pub async fn prefixed_echo(data: Echo) -> impl Component {
+    html! { <p>{data.text}</p> }
+}
+
+mod counters {
+    #[azumi::live]
+    pub struct Tally {
+        pub count: i32,
+    }
+
+    #[azumi::live_impl]
+    impl Tally {
+        pub fn bump(&mut self) {
+            self.count += 1;
+        }
+    }
+}
+
+use counters::Tally;
+
+async fn send(router: axum::Router, request: Request<Body>) -> (StatusCode, String) {
+    let res = router.oneshot(request).await.unwrap();
+    let status = res.status();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+fn echo(path: &str) -> Request<Body> {
+    Request::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(r#"{"text":"hi"}"#))
+        .unwrap()
+}
+
+#[test]
+fn test_prefix_is_normalized() {
+    assert_eq!(azumi::routes().prefix("/app").mount_prefix(), "/app");
+    assert_eq!(azumi::routes().prefix("app/").mount_prefix(), "/app");
+    assert_eq!(azumi::routes().prefix("/a/b/").mount_prefix(), "/a/b");
+    assert_eq!(azumi::routes().prefix("/").mount_prefix(), "");
+    assert_eq!(azumi::routes().mount_prefix(), "");
+}
+
+#[tokio::test]
+async fn test_actions_are_mounted_under_prefix() {
+    let app = azumi::routes().prefix("/app").build();
+    let (status, body) = send(app.clone(), echo("/app/_azumi/action/prefixed_echo")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("hi"));
+
+    let (status, _) = send(app, echo("/_azumi/action/prefixed_echo")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_live_methods_are_mounted_under_prefix() {
+    let app = azumi::routes().prefix("/app").build();
+    let request = Request::post("/app/_azumi/action/Tally/bump")
+        .body(Body::from(Tally { count: 1 }.to_scope()))
+        .unwrap();
+    let (status, body) = send(app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("\"count\":2"), "{}", body);
+}
+
+#[tokio::test]
+async fn test_client_receives_prefix() {
+    let app = azumi::routes().prefix("/app").build();
+    let (status, js) = send(
+        app.clone(),
+        Request::get("/app/azumi.js").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(js.starts_with("window.AZUMI_PREFIX=\"/app\";"));
+    assert!(js.ends_with(azumi::AZUMI_JS));
+
+    let (status, _) = send(app, Request::get("/azumi.js").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_inline_script_takes_prefix_explicitly() {
+    let routes = azumi::routes().prefix("/app");
+    let page = azumi::render_to_string(&routes.script());
+    assert!(page.starts_with("<script>window.AZUMI_PREFIX=\"/app\";"));
+    let page = azumi::render_to_string(&azumi::azumi_script_at("app/"));
+    assert!(page.starts_with("<script>window.AZUMI_PREFIX=\"/app\";"));
+
+    // Building other routes does not change the default script
+    let _other = azumi::routes().prefix("/other").build();
+    let page = azumi::render_to_string(&azumi::azumi_script());
+    assert!(page.starts_with(&format!("<script>{}", &azumi::AZUMI_JS[..20])));
+}
+
+#[tokio::test]
+async fn test_csrf_follows_prefix() {
+    let app = azumi::routes()
+        .prefix("/app")
+        .csrf(CsrfLayer::double_submit())
+        .build();
+    let (status, _) = send(app, echo("/app/_azumi/action/prefixed_echo")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,123 @@
+//! Slots Tests
+//!
+//! Named slots: `#[slot]` component parameters filled with
+//! `@slot name { ... }` blocks, next to the component's `children`.
+
+use azumi::{html, test, Component};
+
+#[azumi::component]
+fn Card<'a>(
+    title: &'a str,
+    #[slot] header: impl Component + 'a,
+    #[slot] footer: Option<impl Component + 'a>,
+    children: impl Component + 'a,
+) -> impl Component + 'a {
+    html! {
+        <article>
+            <header>{header}</header>
+            <h2>{title}</h2>
+            {children}
+            @if let Some(footer) = &footer {
+                <footer>{footer}</footer>
+            }
+        </article>
+    }
+}
+
+#[azumi::component]
+fn Dialog(#[slot] body: impl Component, #[slot] actions: impl Component) -> impl Component {
+    html! {
+        <dialog>
+            {body}
+            <menu>{actions}</menu>
+        </dialog>
+    }
+}
+
+#[test]
+fn test_fill_all_slots() {
+    let user = "<ada>";
+    let output = test::render(&html! {
+        @Card(title = "Orders") {
+            @slot header { <strong>"Shop"</strong> }
+            <p>"3 open orders for " {user}</p>
+            @slot footer { <a href="/orders">"All orders"</a> }
+        }
+    });
+    assert_eq!(
+        output,
+        concat!(
+            "<article><header><strong>Shop</strong></header><h2>Orders</h2>",
+            "<p>3 open orders for &lt;ada&gt;</p>",
+            r#"<footer><a href="/orders">All orders</a></footer></article>"#
+        )
+    );
+}
+
+#[test]
+fn test_optional_slot_left_out() {
+    let output = test::render(&html! {
+        @Card(title = "Orders") {
+            @slot header { "Shop" }
+            <p>"Nothing yet"</p>
+        }
+    });
+    assert_eq!(
+        output,
+        "<article><header>Shop</header><h2>Orders</h2><p>Nothing yet</p></article>"
+    );
+}
+
+#[test]
+fn test_slots_without_children() {
+    let items = ["Cancel", "Delete"];
+    let output = test::render(&html! {
+        @Dialog {
+            @slot actions {
+                @for item in items.iter() { <button>{item}</button> }
+            }
+            @slot body { <p>"Delete this order?"</p> }
+        }
+    });
+    assert_eq!(
+        output,
+        "<dialog><p>Delete this order?</p><menu><button>Cancel</button><button>Delete</button></menu></dialog>"
+    );
+}
+
+#[test]
+fn test_nested_slots() {
+    let output = test::render(&html! {
+        @Dialog {
+            @slot body {
+                @Card(title = "Order 42") {
+                    @slot header { "Shop" }
+                    "Paid"
+                }
+            }
+            @slot actions { <button>"Close"</button> }
+        }
+    });
+    assert_eq!(
+        output,
+        concat!(
+            "<dialog><article><header>Shop</header><h2>Order 42</h2>Paid</article>",
+            "<menu><button>Close</button></menu></dialog>"
+        )
+    );
+}
+
+#[test]
+fn test_slots_from_rust() {
+    let props = Card::Props::builder()
+        .title("Orders")
+        .slot_header("Shop")
+        .slot_footer(html! { <small>"v2"</small> })
+        .build()
+        .unwrap();
+    let output = test::render(&Card::render(props, "Total: 3"));
+    assert_eq!(
+        output,
+        "<article><header>Shop</header><h2>Orders</h2>Total: 3<footer><small>v2</small></footer></article>"
+    );
+}
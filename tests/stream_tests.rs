@@ -0,0 +1,191 @@
+//! Stream Tests
+//!
+//! `azumi::stream`: chunked rendering at size thresholds and `@flush`
+//! points, and the streamed axum response body.
+
+use axum::body::Body;
+use axum::http::{header, Request};
+use axum::routing::get;
+use azumi::stream::{render_chunks, stream};
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+fn page(rows: usize) -> impl Component {
+    html! {
+        <html>
+            <head><title>"Report"</title></head>
+            @flush
+            <body>
+                @for i in 0..rows {
+                    <p>"Row " {i}</p>
+                }
+            </body>
+        </html>
+    }
+}
+
+fn chunks_of(component: &impl Component, chunk_size: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    render_chunks(component, chunk_size, |chunk| {
+        chunks.push(chunk);
+        true
+    })
+    .unwrap();
+    chunks
+}
+
+#[test]
+fn test_flush_splits_chunks() {
+    let chunks = chunks_of(&page(2), 1 << 20);
+    assert_eq!(
+        chunks,
+        [
+            "<html><head><title>Report</title></head>",
+            "<body><p>Row 0</p><p>Row 1</p></body></html>",
+        ]
+    );
+}
+
+#[test]
+fn test_chunk_size_threshold() {
+    let chunks = chunks_of(&page(50), 64);
+    assert!(chunks.len() > 5);
+    for chunk in &chunks[1..chunks.len() - 1] {
+        assert!(chunk.len() >= 64, "{:?}", chunk);
+    }
+    assert_eq!(chunks.concat(), azumi::render_to_string(&page(50)));
+}
+
+#[test]
+fn test_flush_is_ignored_by_render_to_string() {
+    assert_eq!(
+        azumi::render_to_string(&page(1)),
+        "<html><head><title>Report</title></head><body><p>Row 0</p></body></html>"
+    );
+}
+
+#[test]
+fn test_sink_can_stop_rendering() {
+    let mut sent = 0;
+    let result = render_chunks(&page(100), 32, |_| {
+        sent += 1;
+        sent < 2
+    });
+    assert!(result.is_err());
+    assert_eq!(sent, 2);
+}
+
+#[tokio::test]
+async fn test_streamed_response() {
+    let app = axum::Router::new().route(
+        "/report",
+        get(|| async { stream(|| page(200)).chunk_size(256) }),
+    );
+    let res = app
+        .oneshot(Request::get("/report").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(
+        res.headers()[header::CONTENT_TYPE],
+        "text/html; charset=utf-8"
+    );
+    assert!(res.headers().get(header::CONTENT_LENGTH).is_none());
+
+    let mut body = res.into_body();
+    let mut frames = Vec::new();
+    while let Some(frame) = body.frame().await {
+        let data = frame.unwrap().into_data().unwrap();
+        frames.push(String::from_utf8(data.to_vec()).unwrap());
+    }
+    assert_eq!(frames[0], "<html><head><title>Report</title></head>");
+    assert!(frames.len() > 2);
+    assert_eq!(frames.concat(), azumi::render_to_string(&page(200)));
+}
+
+#[azumi::action]
+pub async fn stream_flash(_data: serde_json::Value) -> azumi::action::Reply {
+    azumi::action::redirect("/form").flash(azumi::flash::Level::Success, "Saved")
+}
+
+async fn body_of(res: axum::response::Response) -> String {
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn test_stream_keeps_csrf_token_and_flashes() {
+    let form = || {
+        stream(|| {
+            html! {
+                <main>
+                    {azumi::flash::flash_messages()}
+                    <form method="post">{azumi::csrf::csrf_input()}</form>
+                </main>
+            }
+        })
+    };
+    let app = axum::Router::new()
+        .route("/form", get(move || async move { form() }))
+        .layer(azumi::csrf::CsrfLayer::double_submit())
+        .layer(azumi::flash::FlashLayer);
+
+    // A redirect stores the message for the next page
+    let request = Request::post("/_azumi/action/stream_flash")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from("{}"))
+        .unwrap();
+    let res = azumi::action::register_actions(axum::Router::new())
+        .oneshot(request)
+        .await
+        .unwrap();
+    let cookie = res.headers()[header::SET_COOKIE].to_str().unwrap();
+    let flash = cookie.split(';').next().unwrap().to_string();
+
+    let request = Request::get("/form")
+        .header(header::ACCEPT, "text/html")
+        .header(header::COOKIE, flash);
+    let res = app
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let page = body_of(res).await;
+    assert!(
+        page.contains(r#"<input type="hidden" name="_azumi_csrf" value=""#),
+        "{}",
+        page
+    );
+    assert!(page.contains(">Saved</div>"), "{}", page);
+}
+
+#[derive(Debug)]
+struct Unavailable;
+
+impl std::fmt::Display for Unavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("unavailable")
+    }
+}
+
+impl std::error::Error for Unavailable {}
+
+fn load() -> Result<&'static str, Unavailable> {
+    Err(Unavailable)
+}
+
+#[tokio::test]
+async fn test_render_error_ends_stream() {
+    let app = axum::Router::new().route(
+        "/broken",
+        get(|| async {
+            stream(|| html! { <p>"before"</p> @flush <p>{load()?}</p> <p>"after"</p> })
+        }),
+    );
+    let res = app
+        .oneshot(Request::get("/broken").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let page = body_of(res).await;
+    assert!(page.starts_with("<p>before</p>"), "{}", page);
+    assert!(!page.contains("after"));
+}
@@ -0,0 +1,159 @@
+//! Suspense Tests
+//!
+//! `@suspense(fallback) { ... }` and `azumi::suspense`: fallback-only
+//! rendering, inline resolution and out-of-order streaming.
+
+use axum::body::Body;
+use axum::http::Request;
+use axum::routing::get;
+use azumi::stream::stream;
+use azumi::suspense::{render_to_string_async, suspense};
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use tokio::sync::oneshot;
+use tower::ServiceExt;
+
+async fn load_orders(user: u32) -> Vec<String> {
+    tokio::task::yield_now().await;
+    vec![format!("order-{}-a", user), format!("order-{}-b", user)]
+}
+
+fn dashboard(user: u32) -> impl Component {
+    html! {
+        <h1>"Dashboard"</h1>
+        @suspense(html! { <p>"Loading orders"</p> }) {
+            @let orders = load_orders(user).await;
+            <ul>
+                @for order in &orders {
+                    <li>{order}</li>
+                }
+            </ul>
+        }
+    }
+}
+
+async fn streamed_component<C: Component + 'static>(build: fn() -> C) -> String {
+    let app = axum::Router::new().route("/", get(move || async move { stream(build) }));
+    let res = app
+        .oneshot(Request::get("/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+#[test]
+fn test_fallback_without_async_renderer() {
+    assert_eq!(
+        azumi::render_to_string(&dashboard(7)),
+        "<h1>Dashboard</h1><p>Loading orders</p>"
+    );
+}
+
+#[tokio::test]
+async fn test_render_to_string_async() {
+    assert_eq!(
+        render_to_string_async(&dashboard(7)).await,
+        "<h1>Dashboard</h1><ul><li>order-7-a</li><li>order-7-b</li></ul>"
+    );
+}
+
+#[tokio::test]
+async fn test_streamed_fallback_then_content() {
+    let body = streamed_component(|| dashboard(3)).await;
+    let (page, rest) = body.split_once("<script>").unwrap();
+    assert_eq!(
+        page,
+        r#"<h1>Dashboard</h1><azumi-suspense data-id="0"><p>Loading orders</p></azumi-suspense>"#
+    );
+    assert!(rest.contains("window.__azumiResolve="));
+    assert!(body.ends_with(concat!(
+        r#"<template data-azumi-resolve="0"><ul><li>order-3-a</li><li>order-3-b</li></ul></template>"#,
+        "<script>__azumiResolve(0)</script>"
+    )));
+}
+
+#[tokio::test]
+async fn test_streamed_scripts_carry_nonce() {
+    let app = axum::Router::new().route(
+        "/",
+        get(|| async { stream(|| dashboard(4)).nonce("r4nd\"om") }),
+    );
+    let res = app
+        .oneshot(Request::get("/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    let body = String::from_utf8(bytes.to_vec()).unwrap();
+    assert_eq!(body.matches("<script").count(), 2);
+    assert_eq!(body.matches(r#"<script nonce="r4nd&quot;om">"#).count(), 2);
+}
+
+fn racing() -> impl Component {
+    let (tx, rx) = oneshot::channel::<()>();
+    html! {
+        @suspense("slow") {
+            @let _ = rx.await;
+            <p>"first in page"</p>
+        }
+        @suspense("fast") {
+            @let _ = tx.send(());
+            <p>"second in page"</p>
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_out_of_order_resolution() {
+    let body = streamed_component(racing).await;
+    assert!(body.starts_with(concat!(
+        r#"<azumi-suspense data-id="0">slow</azumi-suspense>"#,
+        r#"<azumi-suspense data-id="1">fast</azumi-suspense>"#
+    )));
+    let fast = body.find(r#"data-azumi-resolve="1""#).unwrap();
+    let slow = body.find(r#"data-azumi-resolve="0""#).unwrap();
+    assert!(fast < slow);
+    // The resolver script is sent once
+    assert_eq!(body.matches("window.__azumiResolve=").count(), 1);
+}
+
+fn nested() -> impl Component {
+    html! {
+        @suspense("outer") {
+            @let name = async { "outer" }.await;
+            <section>
+                {name}
+                @suspense("inner") {
+                    @let n = async { 42 }.await;
+                    <b>{n}</b>
+                }
+            </section>
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_nested_suspense() {
+    assert_eq!(
+        render_to_string_async(&nested()).await,
+        "<section>outer<b>42</b></section>"
+    );
+    let body = streamed_component(nested).await;
+    assert!(body.contains(concat!(
+        r#"<template data-azumi-resolve="0"><section>outer"#,
+        r#"<azumi-suspense data-id="1">inner</azumi-suspense></section></template>"#
+    )));
+    assert!(body.contains(r#"<template data-azumi-resolve="1"><b>42</b></template>"#));
+}
+
+#[tokio::test]
+async fn test_async_component_in_rust() {
+    let component = suspense("…", async move {
+        let orders = load_orders(1).await;
+        html! { <i>{orders.len()}</i> }
+    });
+    assert_eq!(render_to_string_async(&component).await, "<i>2</i>");
+
+    // The content is consumed by the first render
+    assert_eq!(azumi::render_to_string(&component), "…");
+}
@@ -0,0 +1,87 @@
+//! Prediction Telemetry Tests
+//!
+//! Tests for mismatch report parsing, state diffing and the reporter hook.
+
+use axum::http::StatusCode;
+use axum::Json;
+use azumi::telemetry::{self, FieldDiff, MismatchKind, PredictionMismatch};
+use serde_json::json;
+use std::sync::Mutex;
+
+static RECEIVED: Mutex<Vec<PredictionMismatch>> = Mutex::new(Vec::new());
+
+#[test]
+fn test_diff_states_equal() {
+    let state = json!({ "count": 1, "open": true });
+    assert!(telemetry::diff_states(&state, &state).is_empty());
+}
+
+#[test]
+fn test_diff_states_reports_changed_fields() {
+    let predicted = json!({ "count": 2, "open": true });
+    let actual = json!({ "count": 3, "open": true });
+    let diff = telemetry::diff_states(&predicted, &actual);
+    assert_eq!(
+        diff,
+        vec![FieldDiff {
+            path: "count".into(),
+            predicted: json!(2),
+            actual: json!(3),
+        }]
+    );
+}
+
+#[test]
+fn test_diff_states_nested_and_missing_fields() {
+    let predicted = json!({ "user": { "name": "a", "age": 1 } });
+    let actual = json!({ "user": { "name": "b" }, "extra": [1] });
+    let paths: Vec<String> = telemetry::diff_states(&predicted, &actual)
+        .into_iter()
+        .map(|d| d.path)
+        .collect();
+    assert_eq!(paths, vec!["extra", "user.age", "user.name"]);
+}
+
+#[test]
+fn test_client_report_deserializes() {
+    let body = r#"{
+        "struct_name": "CounterState",
+        "method": "increment",
+        "kind": "missing",
+        "predicted": {"count": 0},
+        "actual": {"count": 1},
+        "diff": [{"path": "count", "predicted": 0, "actual": 1}]
+    }"#;
+    let report: PredictionMismatch = serde_json::from_str(body).unwrap();
+    assert_eq!(report.kind, MismatchKind::Missing);
+    assert_eq!(report.prediction, None);
+    assert_eq!(report.diff.len(), 1);
+}
+
+#[tokio::test]
+async fn test_reporter_receives_reports() {
+    let report = PredictionMismatch {
+        struct_name: "CounterState".into(),
+        method: "reset".into(),
+        kind: MismatchKind::Wrong,
+        prediction: Some("count = 0".into()),
+        predicted: json!({ "count": 0 }),
+        actual: json!({ "count": 5 }),
+        diff: Vec::new(),
+    };
+    // Without a reporter the endpoint answers 404 so the client stops posting
+    let status = telemetry::telemetry_handler(Json(report.clone())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    telemetry::set_reporter(|m: &PredictionMismatch| {
+        RECEIVED.lock().unwrap().push(m.clone());
+    });
+    assert!(telemetry::is_enabled());
+    assert!(!telemetry::set_reporter(telemetry::LogReporter));
+
+    assert!(telemetry::report(&report));
+    assert!(RECEIVED.lock().unwrap().contains(&report));
+
+    let status = telemetry::telemetry_handler(Json(report)).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
@@ -0,0 +1,302 @@
+//! Validation Tests
+//!
+//! `#[derive(azumi::Validate)]` rules, `FieldErrors`, per-field messages,
+//! HTML5 constraint attributes on `bind` forms and `#[azumi::action(validate)]`.
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use axum::response::IntoResponse;
+use azumi::validate::{FieldErrors, Validate};
+use azumi::{html, Component};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+
+#[derive(serde::Deserialize, azumi::Validate)]
+pub struct Address {
+    #[validate(required)]
+    pub city: String,
+    #[validate(regex = "[0-9]{5}")]
+    pub zip: String,
+}
+
+fn not_reserved(handle: &String) -> Result<(), String> {
+    if handle == "admin" {
+        Err("This handle is reserved".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(azumi::Validate)]
+pub struct Signup {
+    #[validate(required, length(min = 2, max = 10))]
+    pub name: String,
+    #[validate(required, email)]
+    pub email: String,
+    #[validate(range(min = 13, max = 130))]
+    pub age: u32,
+    #[validate(range(min = -10, max = 10))]
+    pub offset: Option<i32>,
+    #[validate(regex = "[a-z_]+", custom = not_reserved)]
+    pub handle: String,
+    #[validate(length(max = 3))]
+    pub tags: Vec<String>,
+    #[validate(nested)]
+    pub address: Address,
+}
+
+fn valid() -> Signup {
+    Signup {
+        name: "Ann".into(),
+        email: "ann@example.com".into(),
+        age: 30,
+        offset: None,
+        handle: "ann_l".into(),
+        tags: vec![],
+        address: Address {
+            city: "Oslo".into(),
+            zip: "01234".into(),
+        },
+    }
+}
+
+fn codes(errors: &FieldErrors, field: &str) -> Vec<String> {
+    errors.get(field).iter().map(|e| e.code.clone()).collect()
+}
+
+#[test]
+fn test_valid_struct_passes() {
+    assert!(valid().validate().is_ok());
+}
+
+#[test]
+fn test_required_reports_only_required() {
+    let mut s = valid();
+    s.name = "  ".into();
+    let errors = s.validate().unwrap_err();
+    assert_eq!(codes(&errors, "name"), vec!["required"]);
+    assert_eq!(errors.first("name"), Some("This field is required"));
+}
+
+#[test]
+fn test_length_range_email_regex_custom() {
+    let mut s = valid();
+    s.name = "A".into();
+    s.email = "not-an-email".into();
+    s.age = 5;
+    s.offset = Some(11);
+    s.handle = "admin".into();
+    s.tags = vec!["a".into(); 4];
+    let errors = s.validate().unwrap_err();
+    assert_eq!(codes(&errors, "name"), vec!["length"]);
+    assert_eq!(codes(&errors, "email"), vec!["email"]);
+    assert_eq!(errors.first("age"), Some("Must be at least 13"));
+    assert_eq!(errors.first("offset"), Some("Must be at most 10"));
+    assert_eq!(codes(&errors, "handle"), vec!["custom"]);
+    assert_eq!(codes(&errors, "tags"), vec!["length"]);
+    assert_eq!(errors.len(), 6);
+
+    s = valid();
+    s.handle = "Ann!".into();
+    assert_eq!(codes(&s.validate().unwrap_err(), "handle"), vec!["regex"]);
+}
+
+#[test]
+fn test_regex_is_anchored() {
+    let mut s = valid();
+    s.address.zip = "123456".into();
+    let errors = s.validate().unwrap_err();
+    assert_eq!(codes(&errors, "address.zip"), vec!["regex"]);
+}
+
+#[test]
+fn test_nested_errors_are_prefixed() {
+    let mut s = valid();
+    s.address.city = String::new();
+    let errors = s.validate().unwrap_err();
+    assert!(errors.has("address.city"));
+    assert!(!errors.has("city"));
+}
+
+#[test]
+fn test_message_component() {
+    let mut s = valid();
+    s.email = "x".into();
+    let errors = s.validate().unwrap_err();
+    let html = azumi::render_to_string(&errors.message("email"));
+    assert_eq!(
+        html,
+        r#"<p class="field-error" id="email-error" role="alert">Must be a valid email address</p>"#
+    );
+    assert_eq!(azumi::render_to_string(&errors.message("name")), "");
+}
+
+#[test]
+fn test_field_errors_response() {
+    let mut errors = FieldErrors::new();
+    errors.add("email", "email", "bad");
+    let response = errors.into_response();
+    assert_eq!(
+        response.status(),
+        axum::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+}
+
+#[test]
+fn test_constraints() {
+    assert_eq!(
+        Signup::constraints("name"),
+        &[
+            ("required", None),
+            ("minlength", Some("2")),
+            ("maxlength", Some("10"))
+        ]
+    );
+    assert_eq!(
+        Signup::constraints("offset"),
+        &[("min", Some("-10")), ("max", Some("10"))]
+    );
+    assert_eq!(Signup::constraints("address.city"), &[("required", None)]);
+    assert!(Signup::constraints("missing").is_empty());
+}
+
+fn signup_form(errors: &FieldErrors) -> impl Component + '_ {
+    html! {
+        <form bind={Signup}>
+            <input name="name" type="text" />
+            {errors.message("name")}
+            <input name="email" />
+            <input name="handle" type="text" />
+            <input name="address.zip" type="text" />
+            <input name="age" type="number" min="18" />
+        </form>
+    }
+}
+
+#[test]
+fn test_bind_form_emits_constraint_attributes() {
+    let errors = FieldErrors::new();
+    let html = azumi::render_to_string(&signup_form(&errors));
+    assert!(
+        html.contains(r#"<input name="name" type="text" required minlength="2" maxlength="10">"#)
+    );
+    assert!(html.contains(r#"<input name="email" required type="email">"#));
+    assert!(html.contains(r#"<input name="handle" type="text" pattern="[a-z_]+">"#));
+    assert!(html.contains(r#"<input name="address.zip" type="text" pattern="[0-9]{5}">"#));
+    // Hand-written attributes win
+    assert!(html.contains(r#"<input name="age" type="number" min="18" max="130">"#));
+}
+
+struct Plain {
+    title: String,
+}
+
+#[test]
+fn test_bind_form_without_validate_has_no_constraints() {
+    let _ = Plain {
+        title: String::new(),
+    }
+    .title;
+    let html = azumi::render_to_string(&html! {
+        <form bind={Plain}>
+            <input name="title" type="text" />
+        </form>
+    });
+    assert!(html.contains(r#"<input name="title" type="text">"#));
+}
+
+#[derive(azumi::Validate)]
+pub struct Order {
+    #[validate(nested)]
+    pub billing: Option<Address>,
+    #[validate(nested)]
+    pub stops: Vec<Address>,
+}
+
+fn address(city: &str) -> Address {
+    Address {
+        city: city.into(),
+        zip: "01234".into(),
+    }
+}
+
+#[test]
+fn test_nested_option_and_vec() {
+    let order = Order {
+        billing: None,
+        stops: vec![address("Oslo")],
+    };
+    assert!(order.validate().is_ok());
+
+    let order = Order {
+        billing: Some(address("")),
+        stops: vec![address("Oslo"), address("")],
+    };
+    let errors = order.validate().unwrap_err();
+    assert_eq!(codes(&errors, "billing.city"), vec!["required"]);
+    assert_eq!(codes(&errors, "stops.1.city"), vec!["required"]);
+    assert!(!errors.has("stops.0.city"));
+
+    assert_eq!(Order::constraints("billing.city"), &[("required", None)]);
+    assert_eq!(Order::constraints("stops.0.city"), &[("required", None)]);
+    assert!(Order::constraints("stops.city").is_empty());
+}
+
+#[azumi::action(validate)]
+pub async fn save_address(data: Address) -> impl Component {
+    html! { <p>{data.city}</p> }
+}
+
+#[azumi::action(validate)]
+pub async fn save_address_form(data: azumi::action::Form<Address>) -> impl Component {
+    html! { <p>{&data.0.city}</p> }
+}
+
+async fn post(path: &str, content_type: &str, body: &str) -> (StatusCode, String) {
+    let router = azumi::action::register_actions(axum::Router::new());
+    let request = Request::post(path)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let res = router.oneshot(request).await.unwrap();
+    let status = res.status();
+    let bytes = res.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_action_validate_rejects_with_422() {
+    let (status, body) = post(
+        "/_azumi/action/save_address",
+        "application/json",
+        r#"{"city":"","zip":"1"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let errors: FieldErrors = serde_json::from_str(&body).unwrap();
+    assert!(errors.has("city"));
+    assert!(errors.has("zip"));
+
+    let (status, body) = post(
+        "/_azumi/action/save_address",
+        "application/json",
+        r#"{"city":"Oslo","zip":"01234"}"#,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.contains("<p>Oslo</p>"));
+}
+
+#[tokio::test]
+async fn test_action_validate_form_payload() {
+    let form = "application/x-www-form-urlencoded";
+    let (status, _) = post("/_azumi/action/save_address_form", form, "city=&zip=01234").await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    let (status, _) = post(
+        "/_azumi/action/save_address_form",
+        form,
+        "city=Oslo&zip=01234",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}